@@ -5,5 +5,6 @@ mod benchmarks;
 criterion_main! {
     benchmarks::plan::benches,
     benchmarks::problem::benches,
-    benchmarks::domain::benches
+    benchmarks::domain::benches,
+    benchmarks::ground::benches
 }
@@ -1,7 +1,7 @@
 use criterion::{criterion_group, Criterion};
 use spingus::domain::parse_domain;
 
-pub const DOMAIN: &'static str = r#"
+pub const DOMAIN: &str = r#"
     (define (domain d1)
         (:requirements :typing)
         (:types
@@ -0,0 +1,51 @@
+use std::{fs, path::PathBuf};
+
+use criterion::{criterion_group, BenchmarkId, Criterion};
+use spingus::{
+    compile::inference,
+    domain::{parse_domain, Domain},
+    ground::{GroundTask, Strategy},
+    owned::AsBorrowed,
+    problem,
+};
+
+/// The bundled IPC instances, by name
+pub fn instances() -> Vec<(String, Domain, String)> {
+    let mut paths: Vec<PathBuf> = fs::read_dir("tests/data")
+        .expect("Could not open the bundled instances")
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            let domain = fs::read_to_string(path.join("domain.pddl")).unwrap();
+            let problem = fs::read_to_string(path.join("problem.pddl")).unwrap();
+            (name, parse_domain(&domain).unwrap(), problem)
+        })
+        .collect()
+}
+
+fn bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ground");
+    group.sample_size(10);
+    for (name, domain, problem) in instances() {
+        let problem = problem::parse(&problem);
+        for (strategy, label) in [(Strategy::Naive, "naive"), (Strategy::Datalog, "datalog")] {
+            group.bench_with_input(BenchmarkId::new(label, &name), &strategy, |b, strategy| {
+                b.iter(|| GroundTask::with_strategy(&domain, &problem, *strategy))
+            });
+        }
+        // Untyped domains with inferred types, as types let the naive strategy try fewer objects
+        if let Ok(typed) = inference::infer(&domain, &problem) {
+            let problem = typed.problem.as_borrowed();
+            group.bench_function(BenchmarkId::new("naive-inferred", &name), |b| {
+                b.iter(|| GroundTask::with_strategy(&typed.domain, &problem, Strategy::Naive))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
@@ -1,3 +1,4 @@
 pub mod domain;
+pub mod ground;
 pub mod plan;
 pub mod problem;
@@ -1,7 +1,7 @@
 use criterion::{criterion_group, Criterion};
 use spingus::plan;
 
-pub const PLAN: &'static str = r#"
+pub const PLAN: &str = r#"
     (abcde abcde abcde)
     (abcde abcde abcde)
     (abcde abcde abcde)
@@ -1,7 +1,7 @@
 use criterion::{criterion_group, Criterion};
 use spingus::problem;
 
-pub const PROBLEM: &'static str = r#"
+pub const PROBLEM: &str = r#"
     (define (problem p1)
         (:domain d1)
         (:objects 
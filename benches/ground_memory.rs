@@ -0,0 +1,62 @@
+//! Prints the peak memory allocated while grounding each bundled instance, with each strategy
+//!
+//! Run with `cargo bench --bench ground_memory`
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use spingus::{
+    ground::{GroundTask, Strategy},
+    problem,
+};
+
+#[path = "benchmarks/ground.rs"]
+#[allow(dead_code)]
+mod ground;
+
+struct Counting;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// The peak memory allocated by `f` beyond what was allocated before, in bytes
+fn peak(f: impl FnOnce()) -> usize {
+    let before = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    f();
+    PEAK.load(Ordering::Relaxed) - before
+}
+
+fn main() {
+    println!(
+        "{:<28} {:>12} {:>12}",
+        "instance", "naive KiB", "datalog KiB"
+    );
+    for (name, domain, problem) in ground::instances() {
+        let problem = problem::parse(&problem);
+        let [naive, datalog] = [Strategy::Naive, Strategy::Datalog].map(|strategy| {
+            peak(|| {
+                GroundTask::with_strategy(&domain, &problem, strategy).unwrap();
+            })
+        });
+        println!("{:<28} {:>12} {:>12}", name, naive / 1024, datalog / 1024);
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use spingus::{
+    format::{format, FormatOptions, InitOrder},
+    writer::Indent,
+};
+
+const USAGE: &str = "Usage: pddl fmt [--check] [--sort-init] [--tabs | --indent <width>] [FILE]...
+
+Formats PDDL files in place, or stdin to stdout if no files are given
+
+Options:
+    --check            Only check whether the files are formatted, listing those that are not
+    --sort-init        Sort the facts of :init sections
+    --tabs             Indent with tabs
+    --indent <width>   Indent with <width> spaces (default 4)";
+
+struct Args {
+    check: bool,
+    options: FormatOptions,
+    files: Vec<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    match args.next().as_deref() {
+        Some("fmt") => {}
+        Some(command) => return Err(format!("unknown command '{}'", command)),
+        None => return Err("missing command".to_owned()),
+    }
+    let mut parsed = Args {
+        check: false,
+        options: FormatOptions::default(),
+        files: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => parsed.check = true,
+            "--sort-init" => parsed.options.init_order = InitOrder::Sorted,
+            "--tabs" => parsed.options.indent = Indent::Tabs,
+            "--indent" => {
+                let width = args.next().ok_or("missing width after --indent")?;
+                let width = width
+                    .parse()
+                    .map_err(|_| format!("invalid indentation width '{}'", width))?;
+                parsed.options.indent = Indent::Spaces(width);
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option '{}'", arg)),
+            _ => parsed.files.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+fn fmt(args: &Args) -> Result<bool, String> {
+    if args.files.is_empty() {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|err| format!("could not read stdin: {}", err))?;
+        let formatted = format(&input, &args.options)
+            .map_err(|(msg, span)| format!("<stdin>:{}: {}", span.start, msg))?;
+        if args.check {
+            return Ok(formatted == input);
+        }
+        print!("{}", formatted);
+        return Ok(true);
+    }
+
+    let mut formatted_all = true;
+    for file in &args.files {
+        let input =
+            fs::read_to_string(file).map_err(|err| format!("could not read {}: {}", file, err))?;
+        let formatted = format(&input, &args.options)
+            .map_err(|(msg, span)| format!("{}:{}: {}", file, span.start, msg))?;
+        if formatted == input {
+            continue;
+        }
+        if args.check {
+            println!("{}", file);
+            formatted_all = false;
+        } else {
+            fs::write(file, formatted)
+                .map_err(|err| format!("could not write {}: {}", file, err))?;
+        }
+    }
+    Ok(formatted_all)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match fmt(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::collections::HashSet;
+
+use crate::{domain::Domain, owned::OwnedProblem};
+
+/// Infers types for untyped domains and problems, from how the arguments of predicates and actions are used
+pub mod inference;
+
+/// Compiles ADL domains and problems into STRIPS, for planners which only support STRIPS
+pub mod strips;
+
+/// Compiles types into unary predicates, and infers types from unary predicates
+pub mod types;
+
+/// A domain and problem compiled together, as compilations may have to add facts to the problem for what they remove from the domain
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Compiled {
+    pub domain: Domain,
+    pub problem: OwnedProblem,
+}
+
+/// `name`, or `name-2`, `name-3` and so on if it is taken, which is then taken
+pub(crate) fn fresh(taken: &mut HashSet<String>, name: String) -> String {
+    let mut candidate = name.clone();
+    let mut index = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{}-{}", name, index);
+        index += 1;
+    }
+    candidate
+}
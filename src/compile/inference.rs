@@ -0,0 +1,566 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::{
+    fresh,
+    types::{add_subtype, subtypes, type_predicates},
+    Compiled,
+};
+use crate::{
+    domain::{
+        action::{effect::Effect, Action},
+        parameter::Parameter,
+        predicate::Predicate,
+        types::{hierarchy::OBJECT, Types},
+        Domain,
+    },
+    formula::{Atom, Formula},
+    owned::{AsBorrowed, OwnedFact, OwnedObject, OwnedProblem},
+    problem::Problem,
+    symbol::lowercase_problem,
+    term::Term,
+    visit::{walk_effect, walk_formula, Visitor},
+};
+
+/// Types an untyped domain and problem, with types inferred from how the arguments of predicates and the parameters of actions are used
+///
+/// Arguments of predicates share a sort when the same parameter or object occurs in both, which partitions them into sorts, the direct subtypes of [OBJECT].
+/// A sort is named after a unary static predicate which holds for exactly its objects, or else after the first predicate parameter of the sort.
+/// Unary static predicates which hold for some of the objects of a sort are its subtypes, as for [super::types::infer_types].
+/// Preconditions on these predicates are left out, as they type their parameter instead.
+///
+/// Parameters which do not occur in a positive literal of the precondition could be instantiated with any object, so they, the parameters of quantified effects, and the sorts they occur in stay untyped.
+/// Objects are of the most specific type they are inferred to have.
+///
+/// Fails for domains which are already typed
+///
+/// ## Example
+/// ```rust
+/// use spingus::{compile::inference::infer, domain::parse_domain, problem};
+/// let domain = parse_domain("(define (domain d)
+///     (:predicates (truck ?t) (location ?l) (airport ?l) (at ?t ?l))
+///     (:action drive :parameters (?t ?from ?to)
+///         :precondition (and (truck ?t) (location ?to) (at ?t ?from))
+///         :effect (and (not (at ?t ?from)) (at ?t ?to))))").unwrap();
+/// let problem = problem::parse("(define (problem p)
+///     (:objects t a b)
+///     (:init (truck t) (location a) (location b) (airport b) (at t a))
+///     (:goal (at t b)))");
+/// let typed = infer(&domain, &problem).unwrap();
+/// let types: Vec<String> = typed.domain.types.unwrap().iter().map(|t| t.to_string()).collect();
+/// assert_eq!(types, vec!["truck location - object", "airport - location"]);
+/// assert_eq!(
+///     typed.domain.actions[0].to_string(),
+///     "(:action drive
+///     :parameters (?t - truck ?from ?to - location)
+///     :precondition (and
+///         (at ?t ?from)
+///     )
+///     :effect (and
+///         (not (at ?t ?from))
+///         (at ?t ?to)
+///     )
+/// )"
+/// );
+/// assert_eq!(typed.problem.objects.unwrap()[2].to_string(), "b - airport");
+/// ```
+pub fn infer(domain: &Domain, problem: &Problem) -> Result<Compiled, String> {
+    let typed = |parameters: &[Parameter]| {
+        parameters
+            .iter()
+            .any(|parameter| !matches!(parameter, Parameter::Untyped { .. }))
+    };
+    if domain.types.is_some()
+        || domain.constants.as_deref().is_some_and(typed)
+        || domain
+            .predicates
+            .iter()
+            .any(|predicate| typed(&predicate.parameters))
+        || domain
+            .actions
+            .iter()
+            .any(|action| typed(&action.parameters))
+    {
+        return Err("the domain is already typed".to_owned());
+    }
+    let problem = lowercase_problem(problem);
+
+    let mut sorts = Sorts::default();
+    for predicate in &domain.predicates {
+        for index in 0..predicate.parameters.len() {
+            sorts.position(&predicate.name, index);
+        }
+    }
+
+    // The nodes of parameters which may be instantiated with any object
+    let mut unrestricted = Vec::new();
+    let mut occurrences: Vec<(String, usize)> = Vec::new();
+    let mut parameters: Vec<Vec<usize>> = Vec::new();
+    for action in &domain.actions {
+        let scope: HashMap<String, usize> = action
+            .parameters
+            .iter()
+            .map(|parameter| (parameter.name().to_owned(), sorts.node()))
+            .collect();
+        let mut visitor = ActionSorts {
+            sorts: &mut sorts,
+            scope,
+            occurrences: &mut occurrences,
+            unrestricted: &mut unrestricted,
+        };
+        if let Some(precondition) = &action.precondition {
+            visitor.visit_formula(precondition);
+        }
+        visitor.visit_effect(&action.effect);
+        let scope = visitor.scope;
+        let guarded: HashSet<&str> = positive_atoms(action.precondition.as_ref())
+            .iter()
+            .flat_map(|term| term.parameters.iter().map(String::as_str))
+            .collect();
+        let nodes: Vec<usize> = action
+            .parameters
+            .iter()
+            .map(|parameter| scope[parameter.name()])
+            .collect();
+        for (parameter, node) in action.parameters.iter().zip(&nodes) {
+            if !guarded.contains(parameter.name()) {
+                unrestricted.push(*node);
+            }
+        }
+        parameters.push(nodes);
+    }
+
+    let unrestricted: HashSet<usize> = unrestricted.into_iter().map(|n| sorts.find(n)).collect();
+    let mut facts: Vec<&OwnedFact> = problem.init.iter().flatten().collect();
+    if let Some(goal) = &problem.goal {
+        facts.extend(goal.atoms());
+    }
+    for fact in facts
+        .into_iter()
+        .filter(|fact| fact.predicate != crate::formula::EQUALITY)
+    {
+        for (index, object) in fact.objects.iter().enumerate() {
+            // Variables of quantified goals are not objects
+            if !object.starts_with('?') {
+                occurrences.push((object.clone(), sorts.position(&fact.predicate, index)));
+            }
+        }
+    }
+    let mut objects: Vec<String> = domain
+        .constants
+        .iter()
+        .flatten()
+        .map(|constant| constant.name().to_owned())
+        .collect();
+    objects.extend(
+        problem
+            .objects
+            .iter()
+            .flatten()
+            .map(|object| object.name.clone()),
+    );
+    for object in &objects {
+        sorts.object(object);
+    }
+    for (object, position) in occurrences {
+        if !unrestricted.contains(&sorts.find(position)) {
+            let object = sorts.object(&object);
+            sorts.union(object, position);
+        }
+    }
+
+    // The sorts, by the root of their nodes, in the order their first argument was declared
+    let mut roots: Vec<usize> = Vec::new();
+    for predicate in &domain.predicates {
+        for index in 0..predicate.parameters.len() {
+            let root = sorts.position(&predicate.name, index);
+            let root = sorts.find(root);
+            if !unrestricted.contains(&root) && !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+    }
+    let mut candidates = type_predicates(domain, &problem.as_borrowed());
+    candidates.retain(|(name, _)| *name != OBJECT);
+    // Unary static predicates name the types they become, so sorts named otherwise must not take their names
+    let mut taken: HashSet<String> = candidates
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .chain([OBJECT.to_owned()])
+        .collect();
+    let mut types: Types = Vec::new();
+    // The names of sorts, by root
+    let mut sort_names: HashMap<usize, String> = HashMap::new();
+    // The type of every object, and the type every unary static predicate implies
+    let mut object_types: HashMap<String, String> = HashMap::new();
+    let mut predicate_types: HashMap<String, (String, usize)> = HashMap::new();
+    for root in roots {
+        let members: BTreeSet<String> = objects
+            .iter()
+            .filter(|object| {
+                let node = sorts.object(object);
+                sorts.find(node) == root
+            })
+            .cloned()
+            .collect();
+        let mut in_sort = Vec::new();
+        for (name, objects) in &candidates {
+            let position = sorts.position(name, 0);
+            if sorts.find(position) == root {
+                in_sort.push((*name, objects.clone()));
+            }
+        }
+        let alias = in_sort
+            .iter()
+            .find(|(_, objects)| *objects == members)
+            .map(|(name, _)| name.to_string());
+        let name = alias.clone().unwrap_or_else(|| {
+            let (predicate, index) = domain
+                .predicates
+                .iter()
+                .flat_map(|predicate| {
+                    (0..predicate.parameters.len()).map(move |index| (predicate, index))
+                })
+                .find(|(predicate, index)| {
+                    let position = sorts.positions[&(predicate.name.clone(), *index)];
+                    sorts.find(position) == root
+                })
+                .expect("every sort has a predicate argument");
+            let name = predicate.parameters[index].name().trim_start_matches('?');
+            fresh(&mut taken, name.to_owned())
+        });
+        add_subtype(&mut types, OBJECT, &name);
+        if let Some(alias) = alias {
+            predicate_types.insert(alias, (name.clone(), members.len()));
+        }
+        for object in &members {
+            object_types.insert(object.clone(), name.clone());
+        }
+
+        in_sort.retain(|(_, objects)| *objects != members);
+        for (sub_type, sub_objects, parent) in subtypes(in_sort) {
+            add_subtype(&mut types, parent.unwrap_or(&name), sub_type);
+            // Subtypes are accepted before their own subtypes, so objects end up with the most specific
+            for object in &sub_objects {
+                object_types.insert(object.clone(), sub_type.to_owned());
+            }
+            predicate_types.insert(
+                sub_type.to_owned(),
+                (sub_type.to_owned(), sub_objects.len()),
+            );
+        }
+        sort_names.insert(root, name);
+    }
+
+    let sort_of = |sorts: &mut Sorts, node: usize| {
+        let root = sorts.find(node);
+        sort_names.get(&root).cloned()
+    };
+    let retype = |name: &str, type_name: Option<String>| match type_name {
+        Some(type_name) => Parameter::Typed {
+            name: name.to_owned(),
+            type_name,
+        },
+        None => Parameter::Untyped {
+            name: name.to_owned(),
+        },
+    };
+
+    let mut predicates = Vec::new();
+    for predicate in &domain.predicates {
+        let mut parameters = Vec::new();
+        for (index, parameter) in predicate.parameters.iter().enumerate() {
+            let position = sorts.position(&predicate.name, index);
+            parameters.push(retype(parameter.name(), sort_of(&mut sorts, position)));
+        }
+        predicates.push(Predicate {
+            name: predicate.name.clone(),
+            parameters,
+        });
+    }
+
+    let mut actions = Vec::new();
+    for (action, nodes) in domain.actions.iter().zip(parameters) {
+        let guards: Vec<&Term> = positive_atoms(action.precondition.as_ref())
+            .into_iter()
+            .filter(|term| {
+                term.parameters.len() == 1
+                    && term.parameters[0].starts_with('?')
+                    && predicate_types.contains_key(&term.name)
+            })
+            .collect();
+        let mut parameters = Vec::new();
+        // The guards on typed parameters, which their types imply
+        let mut implied: Vec<&Term> = Vec::new();
+        for (parameter, node) in action.parameters.iter().zip(nodes) {
+            let type_name = sort_of(&mut sorts, node).map(|sort| {
+                let on_parameter = guards
+                    .iter()
+                    .filter(|term| term.parameters[0] == parameter.name());
+                implied.extend(on_parameter.clone());
+                on_parameter
+                    .map(|term| &predicate_types[&term.name])
+                    .min_by_key(|(_, count)| *count)
+                    .map_or(sort, |(type_name, _)| type_name.clone())
+            });
+            parameters.push(retype(parameter.name(), type_name));
+        }
+        let precondition = match &action.precondition {
+            Some(Formula::And(children)) => Some(Formula::And(
+                children
+                    .iter()
+                    .filter(
+                        |child| !matches!(child, Formula::Atom(term) if implied.contains(&term)),
+                    )
+                    .cloned()
+                    .collect(),
+            )),
+            Some(Formula::Atom(term)) if implied.contains(&term) => None,
+            precondition => precondition.clone(),
+        };
+        actions.push(Action {
+            name: action.name.clone(),
+            parameters,
+            precondition,
+            effect: action.effect.clone(),
+        });
+    }
+
+    let mut requirements = domain.requirements.clone().unwrap_or_default();
+    if !requirements
+        .iter()
+        .any(|requirement| requirement == "typing")
+    {
+        requirements.push("typing".to_owned());
+    }
+    Ok(Compiled {
+        domain: Domain {
+            name: domain.name.clone(),
+            requirements: Some(requirements),
+            types: Some(types),
+            constants: domain.constants.as_ref().map(|constants| {
+                constants
+                    .iter()
+                    .map(|constant| {
+                        retype(constant.name(), object_types.get(constant.name()).cloned())
+                    })
+                    .collect()
+            }),
+            predicates,
+            actions,
+        },
+        problem: OwnedProblem {
+            objects: problem.objects.as_ref().map(|objects| {
+                objects
+                    .iter()
+                    .map(|object| OwnedObject {
+                        name: object.name.clone(),
+                        type_name: object_types.get(&object.name).cloned(),
+                    })
+                    .collect()
+            }),
+            ..problem
+        },
+    })
+}
+
+/// The atoms of the positive literals of a conjunctive precondition, which every instantiation of the action must satisfy
+fn positive_atoms(precondition: Option<&Formula<Term>>) -> Vec<&Term> {
+    match precondition {
+        Some(Formula::Atom(term)) if !term.is_equality() => vec![term],
+        Some(Formula::And(children)) => children
+            .iter()
+            .flat_map(|child| positive_atoms(Some(child)))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// A union-find over the arguments of predicates, the parameters of actions, and objects, where each set is a sort
+#[derive(Default)]
+struct Sorts {
+    parents: Vec<usize>,
+    positions: HashMap<(String, usize), usize>,
+    objects: HashMap<String, usize>,
+}
+
+impl Sorts {
+    fn node(&mut self) -> usize {
+        self.parents.push(self.parents.len());
+        self.parents.len() - 1
+    }
+
+    /// The node of argument `index` of `predicate`
+    fn position(&mut self, predicate: &str, index: usize) -> usize {
+        let key = (predicate.to_owned(), index);
+        match self.positions.get(&key) {
+            Some(node) => *node,
+            None => {
+                let node = self.node();
+                self.positions.insert(key, node);
+                node
+            }
+        }
+    }
+
+    fn object(&mut self, name: &str) -> usize {
+        match self.objects.get(name) {
+            Some(node) => *node,
+            None => {
+                let node = self.node();
+                self.objects.insert(name.to_owned(), node);
+                node
+            }
+        }
+    }
+
+    fn find(&mut self, mut node: usize) -> usize {
+        while self.parents[node] != node {
+            self.parents[node] = self.parents[self.parents[node]];
+            node = self.parents[node];
+        }
+        node
+    }
+
+    /// Merges the sets of `a` and `b`, keeping the smaller root so that roots follow the order nodes were made in
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a.max(b)] = a.min(b);
+    }
+
+    /// Merges the arguments of `term` with the parameters in `scope` occurring in them, collecting the objects which occur instead
+    fn term(
+        &mut self,
+        term: &Term,
+        scope: &HashMap<String, usize>,
+        occurrences: &mut Vec<(String, usize)>,
+    ) {
+        if term.is_equality() {
+            return;
+        }
+        for (index, argument) in term.parameters.iter().enumerate() {
+            let position = self.position(&term.name, index);
+            match scope.get(argument) {
+                Some(node) => self.union(*node, position),
+                None if argument.starts_with('?') => {}
+                None => occurrences.push((argument.clone(), position)),
+            }
+        }
+    }
+}
+
+/// Merges the arguments of the atoms of an action with its parameters, and with the parameters of its quantified effects
+struct ActionSorts<'s> {
+    sorts: &'s mut Sorts,
+    /// The nodes of the parameters in scope
+    scope: HashMap<String, usize>,
+    occurrences: &'s mut Vec<(String, usize)>,
+    unrestricted: &'s mut Vec<usize>,
+}
+
+impl<'ast> Visitor<'ast, Term> for ActionSorts<'_> {
+    fn visit_atom(&mut self, term: &'ast Term) {
+        self.sorts.term(term, &self.scope, self.occurrences);
+    }
+
+    fn visit_formula(&mut self, formula: &'ast Formula<Term>) {
+        let (Formula::Forall { parameters, .. } | Formula::Exists { parameters, .. }) = formula
+        else {
+            return walk_formula(self, formula);
+        };
+        let outer = self.quantify(parameters);
+        walk_formula(self, formula);
+        self.scope = outer;
+    }
+
+    fn visit_effect(&mut self, effect: &'ast Effect) {
+        let Effect::Forall { parameters, .. } = effect else {
+            return walk_effect(self, effect);
+        };
+        let outer = self.quantify(parameters);
+        walk_effect(self, effect);
+        self.scope = outer;
+    }
+}
+
+impl ActionSorts<'_> {
+    /// Brings `parameters` into scope as parameters which may be instantiated with any object, returning the scope before
+    fn quantify(&mut self, parameters: &[Parameter]) -> HashMap<String, usize> {
+        let outer = self.scope.clone();
+        for parameter in parameters {
+            let node = self.sorts.node();
+            self.unrestricted.push(node);
+            self.scope.insert(parameter.name().to_owned(), node);
+        }
+        outer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::infer;
+    use crate::{domain::parse_domain, problem};
+
+    #[test]
+    fn unrestricted() {
+        let domain = parse_domain(
+            "(define (domain d)
+                (:predicates (block ?b) (on ?b ?c) (made ?x) (marked ?x))
+                (:action stack
+                    :parameters (?b ?c)
+                    :precondition (and (block ?b) (block ?c) (not (on ?b ?c)))
+                    :effect (on ?b ?c))
+                (:action make
+                    :parameters (?x)
+                    :effect (made ?x))
+                (:action mark
+                    :parameters ()
+                    :effect (forall (?x) (marked ?x))))",
+        )
+        .unwrap();
+        let problem = problem::parse(
+            "(define (problem p)
+                (:objects a b)
+                (:init (block a) (block b))
+                (:goal (and (on a b) (made a))))",
+        );
+        let typed = infer(&domain, &problem).unwrap();
+        let predicates: Vec<String> = typed
+            .domain
+            .predicates
+            .iter()
+            .map(|predicate| predicate.to_string())
+            .collect();
+        assert_eq!(
+            predicates,
+            vec![
+                "(block ?b - block)",
+                "(on ?b ?c - block)",
+                "(made ?x)",
+                "(marked ?x)"
+            ]
+        );
+        let parameters: Vec<String> = typed
+            .domain
+            .actions
+            .iter()
+            .map(|action| {
+                let parameters: Vec<String> =
+                    action.parameters.iter().map(|p| p.to_string()).collect();
+                parameters.join(" ")
+            })
+            .collect();
+        assert_eq!(parameters, vec!["?b - block ?c - block", "?x", ""]);
+        assert_eq!(
+            typed.domain.actions[0]
+                .precondition
+                .as_ref()
+                .unwrap()
+                .to_string(),
+            "(and\n    (not (on ?b ?c))\n)"
+        );
+        assert_eq!(
+            infer(&typed.domain, &problem).unwrap_err(),
+            "the domain is already typed"
+        );
+    }
+}
@@ -0,0 +1,1071 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use super::{fresh, Compiled};
+use crate::{
+    domain::{
+        action::{effect::Effect, Action},
+        parameter::Parameter,
+        predicate::Predicate,
+        types::hierarchy::{first_declarations, TypeHierarchy, OBJECT},
+        Domain,
+    },
+    formula::{Atom, Formula, EQUALITY},
+    owned::{AsBorrowed, IntoOwned, OwnedFact, OwnedObject, OwnedProblem},
+    problem::Problem,
+    symbol::lowercase_problem,
+    term::Term,
+    visit::{fold_children, fold_effect_children, walk_effect, walk_formula, Fold, Visitor},
+};
+
+/// The predicate which replaces equality, true for every pair of an object with itself
+pub const SAME: &str = "same";
+
+/// The predicate of the goal, when the goal is compiled into actions
+pub const GOAL_REACHED: &str = "goal-reached";
+
+/// The domain and problem compiled by [compile]
+pub type Strips = Compiled;
+
+/// Compiles `domain` and `problem` into an equivalent pair using only STRIPS and typing, see [compile_untyped] for removing the types as well
+///
+/// + Preconditions are split into one action per clause of their disjunctive normal form, see [Formula::dnf_clauses], leaving out clauses which contradict themselves or are implied by another.
+///   A goal with several clauses gets an action `reach-goal-i` per clause, achieving the new goal `(goal-reached)`
+/// + Quantified formulas and effects are expanded over the objects of the problem and the constants of the domain
+/// + An action with `k` distinct conditions of conditional effects becomes `2^k` actions, one for each set of conditions which are assumed to hold while the others do not
+/// + Equalities become atoms of the static predicate [SAME], which holds for every object with itself
+/// + Parameters of `(either ...)` types get the closest common supertype, and a precondition on a static predicate such as `(either-ball-box ?x)`
+/// + Negated atoms of a predicate `p` become atoms of a new predicate `not-p`, which is deleted whenever `p` is added and vice versa, and holds initially for every instantiation of `p` which does not.
+///   As adds take effect after deletes, an action which may delete an atom it adds, such as `(not (at ?from))` and `(at ?to)` with `?from` and `?to` the same, only deletes it under the condition that they differ
+///
+/// Compiled actions keep the name of their action, with suffixes for each action it becomes, and new predicates get fresh names if theirs are taken.
+///
+/// Compiling fails for numeric effects, constants of `(either ...)` types, and if an action or the goal would become more than `max_actions` actions.
+///
+/// ## Example
+/// ```rust
+/// use spingus::{compile::strips::compile, domain::parse_domain, problem};
+/// let domain = parse_domain("(define (domain d)
+///     (:requirements :adl)
+///     (:predicates (at ?x) (lit ?x))
+///     (:action go :parameters (?from ?to)
+///         :precondition (and (at ?from) (not (= ?from ?to)))
+///         :effect (and (not (at ?from)) (at ?to) (when (lit ?to) (not (lit ?to))))))").unwrap();
+/// let problem = problem::parse("(define (problem p) (:objects a b) (:init (at a)) (:goal (at b)))");
+/// let strips = compile(&domain, &problem, 64).unwrap();
+/// let names: Vec<&str> = strips.domain.actions.iter().map(|action| action.name.as_str()).collect();
+/// assert_eq!(names, vec!["go-1", "go-2"]);
+/// assert_eq!(
+///     strips.domain.actions[0].precondition.as_ref().unwrap().to_string(),
+///     "(and\n    (at ?from)\n    (not-same ?from ?to)\n    (not-lit ?to)\n)"
+/// );
+/// // (at a), two facts of (same ?x ?y), and two each of its negation and of (not-lit ?x)
+/// assert_eq!(strips.problem.init.unwrap().len(), 7);
+/// ```
+pub fn compile(domain: &Domain, problem: &Problem, max_actions: usize) -> Result<Strips, String> {
+    let problem = lowercase_problem(problem);
+    let problem = problem.as_borrowed();
+    Compiler::new(domain, &problem, max_actions)?.compile(domain, &problem)
+}
+
+/// Compiles `domain` and `problem` into an equivalent pair using only STRIPS, by compiling the types of [compile] into unary predicates with [super::types::compile]
+///
+/// ## Example
+/// ```rust
+/// use spingus::{compile::strips::compile_untyped, domain::parse_domain, problem};
+/// let domain = parse_domain("(define (domain d)
+///     (:requirements :adl)
+///     (:types room)
+///     (:predicates (at ?r - room))
+///     (:action go :parameters (?from ?to - room)
+///         :precondition (and (at ?from) (not (= ?from ?to)))
+///         :effect (and (not (at ?from)) (at ?to))))").unwrap();
+/// let problem = problem::parse("(define (problem p) (:objects a b - room) (:init (at a)) (:goal (at b)))");
+/// let strips = compile_untyped(&domain, &problem, 64).unwrap();
+/// assert_eq!(strips.domain.types, None);
+/// assert_eq!(strips.domain.requirements, Some(vec!["strips".to_owned()]));
+/// assert_eq!(
+///     strips.domain.actions[0].precondition.as_ref().unwrap().to_string(),
+///     "(and\n    (room ?from)\n    (room ?to)\n    (at ?from)\n    (not-same ?from ?to)\n)"
+/// );
+/// ```
+pub fn compile_untyped(
+    domain: &Domain,
+    problem: &Problem,
+    max_actions: usize,
+) -> Result<Strips, String> {
+    let typed = compile(domain, problem, max_actions)?;
+    super::types::compile(&typed.domain, &typed.problem.as_borrowed())
+}
+
+struct Compiler {
+    hierarchy: TypeHierarchy,
+    /// The constants of the domain and the objects of the problem
+    objects: Vec<OwnedObject>,
+    predicate_names: HashSet<String>,
+    predicates: Vec<Predicate>,
+    init: Vec<OwnedFact>,
+    /// The guard predicates of `(either ...)` types, by their types
+    guards: BTreeMap<Vec<String>, String>,
+    /// The predicate replacing equality, once it is used
+    same: Option<String>,
+    /// The predicates which may occur negated in the preconditions of compiled actions, see [Compiler::guard_deletes]
+    negatable: HashSet<String>,
+    max_actions: usize,
+}
+
+type ConditionalEffect = (Vec<Formula<Term>>, Effect);
+
+/// Collects the literals of an effect, with the conditions they are under and quantified parameters replaced by objects
+struct Expansion<'c> {
+    compiler: &'c Compiler,
+    conditions: Vec<Formula<Term>>,
+    binding: HashMap<String, String>,
+    effects: Vec<ConditionalEffect>,
+    /// The first error, after which the remaining effects are skipped
+    result: Result<(), String>,
+}
+
+impl Expansion<'_> {
+    /// Expands `effect` for every instantiation of `parameters`
+    fn forall(&mut self, parameters: &[Parameter], effect: &Effect) {
+        let Some((parameter, rest)) = parameters.split_first() else {
+            return self.visit_effect(effect);
+        };
+        for object in self.compiler.objects_of(parameter) {
+            let outer = self.binding.clone();
+            self.binding.insert(parameter.name().to_owned(), object);
+            self.forall(rest, effect);
+            self.binding = outer;
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast, Term> for Expansion<'_> {
+    fn visit_effect(&mut self, effect: &'ast Effect) {
+        if self.result.is_err() {
+            return;
+        }
+        match effect {
+            Effect::Add(term) => self.effects.push((
+                self.conditions.clone(),
+                Effect::Add(substitute(term, &self.binding)),
+            )),
+            Effect::Delete(term) => self.effects.push((
+                self.conditions.clone(),
+                Effect::Delete(substitute(term, &self.binding)),
+            )),
+            Effect::And(_) => walk_effect(self, effect),
+            Effect::When { condition, effect } => {
+                let binding = &self.binding;
+                self.conditions.push(
+                    ExpandQuantifiers(self.compiler)
+                        .fold_formula(condition.clone())
+                        .map(&mut |term| substitute(&term, binding)),
+                );
+                self.visit_effect(effect);
+                self.conditions.pop();
+            }
+            Effect::Forall { parameters, effect } => self.forall(parameters, effect),
+            Effect::Numeric { .. } => {
+                self.result = Err("numeric effects are not supported".to_owned())
+            }
+        }
+    }
+}
+
+/// Expands quantified formulas into conjunctions and disjunctions over the objects their parameters can be instantiated with
+struct ExpandQuantifiers<'c>(&'c Compiler);
+
+impl Fold<Term> for ExpandQuantifiers<'_> {
+    fn fold_formula(&mut self, formula: Formula<Term>) -> Formula<Term> {
+        let (parameters, formula, universal) = match formula {
+            Formula::Forall {
+                parameters,
+                formula,
+            } => (parameters, formula, true),
+            Formula::Exists {
+                parameters,
+                formula,
+            } => (parameters, formula, false),
+            formula => return fold_children(self, formula),
+        };
+        // Expanding the inner quantifiers first leaves no variables of theirs for the outer ones to replace
+        let mut instances = vec![self.fold_formula(*formula)];
+        for parameter in &parameters {
+            let objects = self.0.objects_of(parameter);
+            instances = instances
+                .iter()
+                .flat_map(|instance| {
+                    objects.iter().map(|object| {
+                        let binding =
+                            HashMap::from([(parameter.name().to_owned(), object.clone())]);
+                        instance
+                            .clone()
+                            .map(&mut |term| substitute(&term, &binding))
+                    })
+                })
+                .collect();
+        }
+        match universal {
+            true => Formula::And(instances),
+            false => Formula::Or(instances),
+        }
+    }
+}
+
+/// Collects the predicates which may occur negated in the preconditions of compiled actions, from formulas in negation normal form and effects
+#[derive(Default)]
+struct Negatable(HashSet<String>);
+
+impl<'ast> Visitor<'ast, Term> for Negatable {
+    fn visit_formula(&mut self, formula: &'ast Formula<Term>) {
+        match formula {
+            Formula::Not(child) => self.0.extend(names(child)),
+            _ => walk_formula(self, formula),
+        }
+    }
+
+    fn visit_effect(&mut self, effect: &'ast Effect) {
+        match effect {
+            // Conditions are negated by the actions assuming that they do not hold
+            Effect::When { condition, effect } => {
+                self.0.extend(names(condition));
+                self.visit_effect(effect);
+            }
+            _ => walk_effect(self, effect),
+        }
+    }
+}
+
+fn names(formula: &Formula<Term>) -> impl Iterator<Item = String> + '_ {
+    formula.predicate_names().into_iter().map(str::to_owned)
+}
+
+impl Compiler {
+    fn new(domain: &Domain, problem: &Problem, max_actions: usize) -> Result<Self, String> {
+        let (types, _) = first_declarations(domain.types.as_deref().unwrap_or_default());
+        let hierarchy = TypeHierarchy::new(&types).map_err(|err| err.to_string())?;
+
+        let mut objects: Vec<OwnedObject> = Vec::new();
+        let mut declared = HashSet::new();
+        for constant in domain.constants.iter().flatten() {
+            let type_name = match constant {
+                Parameter::Untyped { .. } => None,
+                Parameter::Typed { type_name, .. } => Some(type_name.clone()),
+                Parameter::Either { name, .. } => {
+                    return Err(format!(
+                        "constant '{}' has an either type, which STRIPS cannot express",
+                        name
+                    ))
+                }
+            };
+            if declared.insert(constant.name().to_owned()) {
+                objects.push(OwnedObject {
+                    name: constant.name().to_owned(),
+                    type_name,
+                });
+            }
+        }
+        for object in problem.objects.iter().flatten() {
+            if declared.insert(object.name.to_owned()) {
+                objects.push(object.clone().into_owned());
+            }
+        }
+
+        Ok(Compiler {
+            hierarchy,
+            objects,
+            predicate_names: domain
+                .predicates
+                .iter()
+                .map(|predicate| predicate.name.clone())
+                .collect(),
+            predicates: Vec::new(),
+            init: Vec::new(),
+            guards: BTreeMap::new(),
+            same: None,
+            negatable: HashSet::new(),
+            max_actions,
+        })
+    }
+
+    fn compile(mut self, domain: &Domain, problem: &Problem) -> Result<Compiled, String> {
+        for predicate in &domain.predicates {
+            let parameters = predicate
+                .parameters
+                .iter()
+                .map(|parameter| self.retype(parameter))
+                .collect();
+            self.predicates.push(Predicate {
+                name: predicate.name.clone(),
+                parameters,
+            });
+        }
+        for fact in problem.init.iter().flatten() {
+            self.init.push(fact.clone().into_owned());
+        }
+
+        let goal_formula = problem.goal.clone().map(|formula| {
+            let formula = formula.map(&mut |fact| Term {
+                name: fact.predicate.to_owned(),
+                parameters: fact.objects.iter().map(|&o| o.to_owned()).collect(),
+            });
+            ExpandQuantifiers(&self).fold_formula(formula)
+        });
+        let mut negatable = Negatable::default();
+        for action in &domain.actions {
+            if let Some(precondition) = &action.precondition {
+                negatable.visit_formula(&precondition.clone().to_nnf());
+            }
+            negatable.visit_effect(&action.effect);
+        }
+        if let Some(formula) = &goal_formula {
+            negatable.visit_formula(&formula.clone().to_nnf());
+        }
+        self.negatable = negatable.0;
+
+        let mut actions = Vec::new();
+        for action in &domain.actions {
+            actions.extend(self.actions(action)?);
+        }
+
+        let mut goal = None;
+        if let Some(formula) = goal_formula {
+            let formula = self.replace_equality(formula);
+            let clauses: Vec<Vec<Formula<Term>>> = formula
+                .dnf_clauses(self.max_actions)
+                .map_err(|err| format!("goal: {}", err))?
+                .into_iter()
+                .filter_map(consistent)
+                .collect();
+            goal = Some(match <[_; 1]>::try_from(clauses) {
+                Ok([clause]) => clause,
+                Err(clauses) => {
+                    let reached = fresh(&mut self.predicate_names, GOAL_REACHED.to_owned());
+                    self.predicates.push(Predicate {
+                        name: reached.clone(),
+                        parameters: vec![],
+                    });
+                    let atom = Term {
+                        name: reached,
+                        parameters: vec![],
+                    };
+                    for (index, clause) in clauses.into_iter().enumerate() {
+                        actions.push(Action {
+                            name: format!("reach-goal-{}", index + 1),
+                            parameters: vec![],
+                            precondition: (!clause.is_empty()).then_some(Formula::And(clause)),
+                            effect: Effect::Add(atom.clone()),
+                        });
+                    }
+                    vec![Formula::Atom(atom)]
+                }
+            });
+        }
+
+        let negated = self.compile_negations(&mut actions, goal.as_mut())?;
+        for action in &mut actions {
+            action.effect = NegateEffects(&negated).fold_effect(action.effect.clone());
+        }
+        let mut action_names = HashSet::new();
+        for action in &mut actions {
+            action.name = fresh(&mut action_names, action.name.clone());
+        }
+
+        let mut requirements = vec!["strips".to_owned()];
+        if domain.types.is_some() {
+            requirements.push("typing".to_owned());
+        }
+        Ok(Compiled {
+            domain: Domain {
+                name: domain.name.clone(),
+                requirements: Some(requirements),
+                types: domain.types.clone(),
+                constants: domain.constants.clone(),
+                predicates: self.predicates,
+                actions,
+            },
+            problem: OwnedProblem {
+                name: problem.name.map(str::to_owned),
+                domain: problem.domain.map(str::to_owned),
+                objects: problem
+                    .objects
+                    .clone()
+                    .map(|objects| objects.into_iter().map(IntoOwned::into_owned).collect()),
+                init: Some(self.init),
+                goal: goal.map(|literals| {
+                    Formula::And(literals).map(&mut |term| OwnedFact {
+                        predicate: term.name,
+                        objects: term.parameters,
+                    })
+                }),
+            },
+        })
+    }
+
+    /// The STRIPS actions `action` compiles into
+    fn actions(&mut self, action: &Action) -> Result<Vec<Action>, String> {
+        let with_name = |err: String| format!("action '{}': {}", action.name, err);
+        let mut parameters = Vec::new();
+        let mut precondition: Vec<Formula<Term>> = action
+            .precondition
+            .iter()
+            .map(|precondition| ExpandQuantifiers(self).fold_formula(precondition.clone()))
+            .collect();
+        for parameter in &action.parameters {
+            if let Parameter::Either { name, type_names } = parameter {
+                precondition.push(Formula::Atom(Term {
+                    name: self.guard(type_names),
+                    parameters: vec![name.clone()],
+                }));
+            }
+            parameters.push(self.retype(parameter));
+        }
+
+        let mut expansion = Expansion {
+            compiler: self,
+            conditions: Vec::new(),
+            binding: HashMap::new(),
+            effects: Vec::new(),
+            result: Ok(()),
+        };
+        expansion.visit_effect(&action.effect);
+        expansion.result.map_err(with_name)?;
+        let effects = self.guard_deletes(expansion.effects);
+        let mut unconditional = Vec::new();
+        let mut conditional: Vec<(Formula<Term>, Vec<Effect>)> = Vec::new();
+        for (conditions, effect) in effects {
+            if conditions.is_empty() {
+                unconditional.push(effect);
+                continue;
+            }
+            let condition = Formula::And(conditions);
+            match conditional
+                .iter_mut()
+                .find(|(known, _)| *known == condition)
+            {
+                Some((_, known)) => known.push(effect),
+                None => conditional.push((condition, vec![effect])),
+            }
+        }
+        let max_actions = self.max_actions;
+        let too_many = || with_name(format!("compiles into more than {} actions", max_actions));
+        if conditional.len() >= usize::BITS as usize || 1 << conditional.len() > max_actions {
+            return Err(too_many());
+        }
+
+        let mut compiled = Vec::new();
+        for assumption in 0..1 << conditional.len() {
+            let mut precondition = precondition.clone();
+            let mut effect = unconditional.clone();
+            for (index, (condition, effects)) in conditional.iter().enumerate() {
+                match assumption >> index & 1 == 1 {
+                    true => {
+                        precondition.push(condition.clone());
+                        effect.extend(effects.iter().cloned());
+                    }
+                    false => precondition.push(Formula::Not(Box::new(condition.clone()))),
+                }
+            }
+            let clauses: Vec<Vec<Formula<Term>>> = self
+                .replace_equality(Formula::And(precondition))
+                .dnf_clauses(max_actions)
+                .map_err(with_name)?
+                .into_iter()
+                .filter_map(consistent)
+                .collect();
+            for (index, clause) in clauses.iter().enumerate() {
+                let subsumed = clauses.iter().enumerate().any(|(other, weaker)| {
+                    other != index
+                        && weaker.iter().all(|literal| clause.contains(literal))
+                        && (weaker.len() < clause.len() || other < index)
+                });
+                if !subsumed {
+                    compiled.push((clause.clone(), Effect::And(effect.clone())));
+                }
+            }
+            if compiled.len() > max_actions {
+                return Err(too_many());
+            }
+        }
+
+        let count = compiled.len();
+        Ok(compiled
+            .into_iter()
+            .enumerate()
+            .map(|(index, (clause, effect))| Action {
+                name: match count {
+                    1 => action.name.clone(),
+                    _ => format!("{}-{}", action.name, index + 1),
+                },
+                parameters: parameters.clone(),
+                precondition: (!clause.is_empty()).then_some(Formula::And(clause)),
+                effect,
+            })
+            .collect())
+    }
+
+    /// Makes each delete of a [Compiler::negatable] predicate conditional on no add of the action being the same atom, as adds take effect after deletes
+    ///
+    /// Otherwise an action deleting `(at ?from)` and adding `(at ?to)` would make `(not-at ?to)` true while `(at ?to)` holds when `?from` and `?to` are the same object
+    fn guard_deletes(&self, effects: Vec<ConditionalEffect>) -> Vec<ConditionalEffect> {
+        let adds: Vec<(&[Formula<Term>], &Term)> = effects
+            .iter()
+            .filter_map(|(conditions, effect)| match effect {
+                Effect::Add(term) => Some((conditions.as_slice(), term)),
+                _ => None,
+            })
+            .collect();
+        let mut guarded = Vec::new();
+        'effects: for (conditions, effect) in &effects {
+            let mut conditions = conditions.clone();
+            if let Effect::Delete(deleted) = effect {
+                if self.negatable.contains(&deleted.name) {
+                    for (add_conditions, added) in &adds {
+                        let Some(equalities) = equalities(deleted, added) else {
+                            continue;
+                        };
+                        if add_conditions.is_empty() && equalities.is_empty() {
+                            continue 'effects;
+                        }
+                        let mut overridden = add_conditions.to_vec();
+                        overridden.extend(equalities);
+                        conditions.push(Formula::Not(Box::new(Formula::And(overridden))));
+                    }
+                }
+            }
+            guarded.push((conditions, effect.clone()));
+        }
+        guarded
+    }
+
+    /// The objects which can instantiate `parameter`
+    fn objects_of(&self, parameter: &Parameter) -> Vec<String> {
+        let types: Vec<&str> = match parameter {
+            Parameter::Untyped { .. } => vec![OBJECT],
+            Parameter::Typed { type_name, .. } => vec![type_name],
+            Parameter::Either { type_names, .. } => type_names.iter().map(String::as_str).collect(),
+        };
+        self.objects
+            .iter()
+            .filter(|object| {
+                let type_name = object.type_name.as_deref().unwrap_or(OBJECT);
+                types
+                    .iter()
+                    .any(|t| *t == OBJECT || self.hierarchy.is_subtype(type_name, t))
+            })
+            .map(|object| object.name.clone())
+            .collect()
+    }
+
+    /// `parameter` with the closest common supertype of its types, if it is of an `(either ...)` type
+    fn retype(&self, parameter: &Parameter) -> Parameter {
+        let Parameter::Either { name, type_names } = parameter else {
+            return parameter.clone();
+        };
+        let types = self.either_types(type_names);
+        let supertype = std::iter::once(types[0].as_str())
+            .chain(self.hierarchy.ancestors(&types[0]))
+            .find(|candidate| {
+                types
+                    .iter()
+                    .all(|t| self.hierarchy.is_subtype(t, candidate))
+            })
+            .unwrap_or(OBJECT);
+        Parameter::Typed {
+            name: name.clone(),
+            type_name: supertype.to_owned(),
+        }
+    }
+
+    /// The types of an `(either ...)` type, sorted and without those which are subtypes of another
+    fn either_types(&self, type_names: &[String]) -> Vec<String> {
+        let mut types: Vec<String> = type_names
+            .iter()
+            .filter(|t| {
+                !type_names
+                    .iter()
+                    .any(|other| other != *t && self.hierarchy.is_subtype(t, other))
+            })
+            .cloned()
+            .collect();
+        types.sort();
+        types.dedup();
+        types
+    }
+
+    /// The static predicate which holds for the objects of an `(either ...)` type, declared on first use
+    fn guard(&mut self, type_names: &[String]) -> String {
+        let types = self.either_types(type_names);
+        if let Some(guard) = self.guards.get(&types) {
+            return guard.clone();
+        }
+        let guard = fresh(
+            &mut self.predicate_names,
+            format!("either-{}", types.join("-")),
+        );
+        let parameter = Parameter::Either {
+            name: "?x".to_owned(),
+            type_names: types.clone(),
+        };
+        for object in self.objects_of(&parameter) {
+            self.init.push(OwnedFact {
+                predicate: guard.clone(),
+                objects: vec![object],
+            });
+        }
+        self.predicates.push(Predicate {
+            name: guard.clone(),
+            parameters: vec![self.retype(&parameter)],
+        });
+        self.guards.insert(types, guard.clone());
+        guard
+    }
+
+    /// Replaces equalities in `formula` by [SAME], declaring it on first use
+    fn replace_equality(&mut self, formula: Formula<Term>) -> Formula<Term> {
+        formula.map(&mut |term| {
+            if !term.is_equality() {
+                return term;
+            }
+            let same = match &self.same {
+                Some(same) => same.clone(),
+                None => {
+                    let same = fresh(&mut self.predicate_names, SAME.to_owned());
+                    self.predicates.push(Predicate {
+                        name: same.clone(),
+                        parameters: ["?x", "?y"]
+                            .map(|name| Parameter::Untyped {
+                                name: name.to_owned(),
+                            })
+                            .to_vec(),
+                    });
+                    for object in &self.objects {
+                        self.init.push(OwnedFact {
+                            predicate: same.clone(),
+                            objects: vec![object.name.clone(); 2],
+                        });
+                    }
+                    self.same = Some(same.clone());
+                    same
+                }
+            };
+            Term {
+                name: same,
+                parameters: term.parameters,
+            }
+        })
+    }
+
+    /// Replaces the negated literals of preconditions and `goal` by atoms of new predicates, returning them by the predicate they negate
+    fn compile_negations(
+        &mut self,
+        actions: &mut [Action],
+        goal: Option<&mut Vec<Formula<Term>>>,
+    ) -> Result<BTreeMap<String, String>, String> {
+        let mut literals: Vec<&mut Formula<Term>> = actions
+            .iter_mut()
+            .filter_map(|action| match &mut action.precondition {
+                Some(Formula::And(clause)) => Some(clause.iter_mut()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        if let Some(goal) = goal {
+            literals.extend(goal.iter_mut());
+        }
+
+        let negated_names: BTreeSet<String> = literals
+            .iter()
+            .filter_map(|literal| match &**literal {
+                Formula::Not(atom) => match &**atom {
+                    Formula::Atom(term) => Some(term.name.clone()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        let mut negated = BTreeMap::new();
+        for name in negated_names {
+            let Some(predicate) = self.predicates.iter().find(|p| p.name == name).cloned() else {
+                return Err(format!("predicate '{}' is not declared", name));
+            };
+            let negation = fresh(&mut self.predicate_names, format!("not-{}", name));
+            let holds: HashSet<&OwnedFact> = self.init.iter().collect();
+            let mut complement = Vec::new();
+            let mut tuples: Vec<Vec<String>> = vec![vec![]];
+            for parameter in &predicate.parameters {
+                let objects = self.objects_of(parameter);
+                tuples = tuples
+                    .into_iter()
+                    .flat_map(|tuple| {
+                        objects.iter().map(move |object| {
+                            let mut tuple = tuple.clone();
+                            tuple.push(object.clone());
+                            tuple
+                        })
+                    })
+                    .collect();
+            }
+            for objects in tuples {
+                let fact = OwnedFact {
+                    predicate: name.clone(),
+                    objects,
+                };
+                if !holds.contains(&fact) {
+                    complement.push(OwnedFact {
+                        predicate: negation.clone(),
+                        objects: fact.objects,
+                    });
+                }
+            }
+            self.init.extend(complement);
+            self.predicates.push(Predicate {
+                name: negation.clone(),
+                parameters: predicate.parameters,
+            });
+            negated.insert(name, negation);
+        }
+
+        for literal in literals {
+            if let Formula::Not(atom) = literal {
+                if let Formula::Atom(term) = &**atom {
+                    *literal = Formula::Atom(Term {
+                        name: negated[&term.name].clone(),
+                        parameters: term.parameters.clone(),
+                    });
+                }
+            }
+        }
+        Ok(negated)
+    }
+}
+
+/// `term` with its parameters bound in `binding` replaced
+fn substitute(term: &Term, binding: &HashMap<String, String>) -> Term {
+    Term {
+        name: term.name.clone(),
+        parameters: term
+            .parameters
+            .iter()
+            .map(|parameter| binding.get(parameter).unwrap_or(parameter).clone())
+            .collect(),
+    }
+}
+
+/// The equalities of arguments under which `a` and `b` are the same atom, or `None` if they never are
+fn equalities(a: &Term, b: &Term) -> Option<Vec<Formula<Term>>> {
+    if a.name != b.name || a.parameters.len() != b.parameters.len() {
+        return None;
+    }
+    let mut equalities = Vec::new();
+    for (x, y) in a.parameters.iter().zip(&b.parameters) {
+        if x == y {
+            continue;
+        }
+        if !x.starts_with('?') && !y.starts_with('?') {
+            return None;
+        }
+        equalities.push(Formula::Atom(Term {
+            name: EQUALITY.to_owned(),
+            parameters: vec![x.clone(), y.clone()],
+        }));
+    }
+    Some(equalities)
+}
+
+/// `clause` without duplicate literals, or `None` if it contains a literal and its negation
+fn consistent(clause: Vec<Formula<Term>>) -> Option<Vec<Formula<Term>>> {
+    let mut literals: Vec<Formula<Term>> = Vec::new();
+    for literal in clause {
+        let negation = match &literal {
+            Formula::Not(atom) => (**atom).clone(),
+            atom => Formula::Not(Box::new(atom.clone())),
+        };
+        if literals.contains(&negation) {
+            return None;
+        }
+        if !literals.contains(&literal) {
+            literals.push(literal);
+        }
+    }
+    Some(literals)
+}
+
+/// Makes the literals of an effect also update the atoms of the predicates negating theirs, by the names of the negated predicates
+struct NegateEffects<'n>(&'n BTreeMap<String, String>);
+
+impl Fold<Term> for NegateEffects<'_> {
+    fn fold_effect(&mut self, effect: Effect) -> Effect {
+        let negation = |term: &Term, negated: &String| Term {
+            name: negated.clone(),
+            parameters: term.parameters.clone(),
+        };
+        match effect {
+            // Keep the effect a flat conjunction of literals
+            Effect::And(children) => Effect::And(
+                children
+                    .into_iter()
+                    .flat_map(|child| match self.fold_effect(child) {
+                        Effect::And(literals) => literals,
+                        literal => vec![literal],
+                    })
+                    .collect(),
+            ),
+            Effect::Add(term) if self.0.contains_key(&term.name) => {
+                let delete = Effect::Delete(negation(&term, &self.0[&term.name]));
+                Effect::And(vec![Effect::Add(term), delete])
+            }
+            Effect::Delete(term) if self.0.contains_key(&term.name) => {
+                let add = Effect::Add(negation(&term, &self.0[&term.name]));
+                Effect::And(vec![Effect::Delete(term), add])
+            }
+            effect => fold_effect_children(self, effect),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeSet, HashSet, VecDeque};
+
+    use super::compile;
+    use crate::{
+        domain::parse_domain, ground::GroundTask, owned::AsBorrowed, problem, symbol::AtomId,
+    };
+
+    /// The states reachable from the initial state of `task` with the length of a shortest plan to each, found by breadth-first search
+    fn reachable(task: &GroundTask) -> Vec<(BTreeSet<AtomId>, usize)> {
+        let mut queue = VecDeque::from([(task.init.clone(), 0)]);
+        let mut visited = HashSet::from([task.init.clone()]);
+        let mut states = Vec::new();
+        while let Some((state, length)) = queue.pop_front() {
+            for action in &task.actions {
+                if action.precondition.iter().all(|atom| state.contains(atom))
+                    && action
+                        .negative_precondition
+                        .iter()
+                        .all(|atom| !state.contains(atom))
+                {
+                    let mut next: BTreeSet<_> = state.clone();
+                    action.del.iter().for_each(|atom| {
+                        next.remove(atom);
+                    });
+                    next.extend(&action.add);
+                    if visited.insert(next.clone()) {
+                        queue.push_back((next, length + 1));
+                    }
+                }
+            }
+            states.push((state, length));
+        }
+        states
+    }
+
+    /// The length of a shortest plan of `task`
+    fn plan_length(task: &GroundTask) -> Option<usize> {
+        reachable(task)
+            .into_iter()
+            .find(|(state, _)| task.goal.is_subset(state) && task.negative_goal.is_disjoint(state))
+            .map(|(_, length)| length)
+    }
+
+    #[test]
+    fn compile_adl() {
+        let domain = parse_domain(
+            "(define (domain lights)
+                (:requirements :adl)
+                (:types room switch)
+                (:constants main - switch)
+                (:predicates (at ?r - room) (connected ?a ?b - room) (on ?s - switch)
+                    (lit ?r - room) (wired ?s - switch ?r - room)
+                    (touched ?t - (either room switch)))
+                (:action go
+                    :parameters (?from ?to - room)
+                    :precondition (and (at ?from) (not (= ?from ?to))
+                        (or (connected ?from ?to) (connected ?to ?from)))
+                    :effect (and (not (at ?from)) (at ?to)))
+                (:action flip
+                    :parameters (?s - switch ?r - room)
+                    :precondition (and (at ?r) (wired ?s ?r))
+                    :effect (and (when (on ?s) (not (on ?s))) (when (not (on ?s)) (on ?s))
+                        (forall (?x - room) (when (and (wired ?s ?x) (not (on ?s))) (lit ?x)))))
+                (:action touch
+                    :parameters (?t - (either switch room))
+                    :effect (touched ?t)))",
+        )
+        .unwrap();
+        let problem = problem::parse(
+            "(define (problem p)
+                (:objects A b - room)
+                (:init (at a) (connected b a) (wired main b))
+                (:goal (and (lit b) (touched main) (not (at a)))))",
+        );
+        let strips = compile(&domain, &problem, 64).unwrap();
+        let names: Vec<&str> = strips
+            .domain
+            .actions
+            .iter()
+            .map(|action| action.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["go-1", "go-2", "flip-1", "flip-2", "flip-3", "flip-4", "flip-5", "touch"]
+        );
+        assert_eq!(
+            strips.domain.actions[5].to_string(),
+            "(:action flip-4
+    :parameters (?s - switch ?r - room)
+    :precondition (and
+        (at ?r)
+        (wired ?s ?r)
+        (not-on ?s)
+        (not-wired ?s a)
+        (wired ?s b)
+    )
+    :effect (and
+        (on ?s)
+        (not (not-on ?s))
+        (lit b)
+    )
+)"
+        );
+        assert_eq!(
+            strips.domain.actions[7].parameters[0].to_string(),
+            "?t - object"
+        );
+        let init = strips.problem.init.as_ref().unwrap();
+        for fact in [
+            "(either-room-switch main)",
+            "(same a a)",
+            "(not-same a b)",
+            "(not-at b)",
+        ] {
+            assert!(init.iter().any(|f| f.to_string() == fact), "{}", fact);
+        }
+        assert!(!init.iter().any(|f| f.to_string() == "(not-wired main b)"));
+        assert_eq!(
+            parse_domain(&strips.domain.to_string()).unwrap(),
+            strips.domain
+        );
+
+        let task = GroundTask::new(&strips.domain, &strips.problem.as_borrowed()).unwrap();
+        assert!(task
+            .actions
+            .iter()
+            .all(|action| action.negative_precondition.is_empty()));
+        assert_eq!(plan_length(&task), Some(3));
+    }
+
+    #[test]
+    fn errors() {
+        let problem = problem::parse("(define (problem p) (:objects a b c) (:goal (p a)))");
+        let compile_domain = |input: &str, max_actions| {
+            compile(&parse_domain(input).unwrap(), &problem, max_actions).map(|_| ())
+        };
+        assert_eq!(
+            compile_domain(
+                "(define (domain d)
+                    (:predicates (p ?x))
+                    (:action a :parameters () :effect (increase (total-cost) 1)))",
+                8
+            ),
+            Err("action 'a': numeric effects are not supported".to_owned())
+        );
+        assert_eq!(
+            compile_domain(
+                "(define (domain d)
+                    (:types x y)
+                    (:constants k - (either x y))
+                    (:predicates (p ?x))
+                    (:action a :parameters () :effect (p k)))",
+                8
+            ),
+            Err("constant 'k' has an either type, which STRIPS cannot express".to_owned())
+        );
+        let forall = "(define (domain d)
+            (:predicates (p ?x) (q ?x))
+            (:action a :parameters () :effect (forall (?x) (when (p ?x) (q ?x)))))";
+        assert_eq!(
+            compile_domain(forall, 4),
+            Err("action 'a': compiles into more than 4 actions".to_owned())
+        );
+        assert_eq!(compile_domain(forall, 8), Ok(()));
+    }
+
+    #[test]
+    fn quantified() {
+        let domain = parse_domain(
+            "(define (domain marks)
+                (:requirements :strips :typing :quantified-preconditions)
+                (:types block)
+                (:predicates (clear ?x - block) (marked ?x - block) (done))
+                (:action mark
+                    :parameters (?x - block)
+                    :precondition (and (clear ?x) (not (marked ?x)))
+                    :effect (marked ?x))
+                (:action finish
+                    :parameters ()
+                    :precondition (forall (?x - block) (or (marked ?x) (not (clear ?x))))
+                    :effect (done)))",
+        )
+        .unwrap();
+        let problem = problem::parse(
+            "(define (problem p)
+                (:objects a b c - block)
+                (:init (clear a) (clear c))
+                (:goal (and (done) (exists (?x - block) (and (marked ?x) (not (clear ?x)))))))",
+        );
+        let strips = compile(&domain, &problem, 64).unwrap();
+        let task = GroundTask::new(&strips.domain, &strips.problem.as_borrowed()).unwrap();
+        // Only b is not clear, and it cannot be marked
+        assert_eq!(task.unreachable_goal.len(), 1);
+
+        let problem = problem::parse(
+            "(define (problem p)
+                (:objects a b c - block)
+                (:init (clear a) (clear c))
+                (:goal (and (done) (forall (?x - block) (exists (?y - block) (marked ?y))))))",
+        );
+        let strips = compile(&domain, &problem, 64).unwrap();
+        let task = GroundTask::new(&strips.domain, &strips.problem.as_borrowed()).unwrap();
+        // Marking a and c, finishing and reaching the disjunctive goal
+        assert_eq!(plan_length(&task), Some(4));
+    }
+
+    #[test]
+    fn deletes_overridden_by_adds() {
+        let domain = parse_domain(
+            "(define (domain robot)
+                (:requirements :strips :negative-preconditions)
+                (:predicates (at ?x) (visited ?x))
+                (:action move
+                    :parameters (?from ?to)
+                    :precondition (at ?from)
+                    :effect (and (not (at ?from)) (at ?to) (visited ?to))))",
+        )
+        .unwrap();
+        let problem = problem::parse(
+            "(define (problem p)
+                (:objects a b)
+                (:init (at a))
+                (:goal (and (visited a) (not (at b)))))",
+        );
+        let strips = compile(&domain, &problem, 64).unwrap();
+        let task = GroundTask::new(&strips.domain, &strips.problem.as_borrowed()).unwrap();
+        let states = reachable(&task);
+        for (state, _) in &states {
+            let names: HashSet<String> = state.iter().map(|&atom| task.atom_name(atom)).collect();
+            for object in ["a", "b"] {
+                assert!(
+                    !(names.contains(&format!("(at {})", object))
+                        && names.contains(&format!("(not-at {})", object))),
+                    "{:?}",
+                    names
+                );
+            }
+        }
+        // Moving from a to a visits it without leaving it
+        assert_eq!(plan_length(&task), Some(1));
+    }
+}
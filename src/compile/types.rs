@@ -0,0 +1,493 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashSet},
+};
+
+use super::{fresh, Compiled};
+use crate::{
+    domain::{
+        action::{effect::Effect, Action},
+        parameter::Parameter,
+        predicate::Predicate,
+        types::{
+            hierarchy::{first_declarations, TypeHierarchy, OBJECT},
+            Type, Types,
+        },
+        Domain,
+    },
+    formula::Formula,
+    owned::{AsBorrowed, IntoOwned, OwnedFact, OwnedObject, OwnedProblem},
+    problem::Problem,
+    symbol::lowercase_problem,
+    term::Term,
+    visit::{fold_children, fold_effect_children, Fold},
+};
+
+/// Compiles the types of `domain` and `problem` into unary predicates, leaving both untyped
+///
+/// Every type other than [OBJECT] becomes a static predicate of the same name, or a fresh one if it is taken.
+/// Typed parameters of actions become untyped parameters with preconditions on these predicates, and typed parameters of quantified effects get conditions on them instead.
+/// Quantified formulas are restricted likewise, `(forall (?x - t) f)` becoming `(forall (?x) (imply (t ?x) f))` and `(exists (?x - t) f)` becoming `(exists (?x) (and (t ?x) f))`.
+/// Parameters of `(either ...)` types get a disjunction of the predicates, see [super::strips::compile] for removing them.
+/// The initial state gets a fact for each type of every object and constant, and the supertypes of these types.
+///
+/// ## Example
+/// ```rust
+/// use spingus::{compile::types::compile, domain::parse_domain, problem};
+/// let domain = parse_domain("(define (domain d)
+///     (:requirements :strips :typing)
+///     (:types truck - vehicle vehicle place - object)
+///     (:predicates (at ?v - vehicle ?p - place))
+///     (:action drive :parameters (?t - truck ?from ?to - place)
+///         :precondition (at ?t ?from)
+///         :effect (and (not (at ?t ?from)) (at ?t ?to))))").unwrap();
+/// let problem = problem::parse("(define (problem p) (:objects t - truck a b - place) (:init (at t a)) (:goal (at t b)))");
+/// let untyped = compile(&domain, &problem).unwrap();
+/// assert_eq!(untyped.domain.types, None);
+/// assert_eq!(
+///     untyped.domain.actions[0].precondition.as_ref().unwrap().to_string(),
+///     "(and\n    (truck ?t)\n    (place ?from)\n    (place ?to)\n    (at ?t ?from)\n)"
+/// );
+/// let init: Vec<String> = untyped.problem.init.unwrap().iter().map(|fact| fact.to_string()).collect();
+/// assert_eq!(init, vec!["(at t a)", "(truck t)", "(vehicle t)", "(place a)", "(place b)"]);
+/// ```
+pub fn compile(domain: &Domain, problem: &Problem) -> Result<Compiled, String> {
+    let problem = lowercase_problem(problem);
+    let problem = problem.as_borrowed();
+    let (types, _) = first_declarations(domain.types.as_deref().unwrap_or_default());
+    let hierarchy = TypeHierarchy::new(&types).map_err(|err| err.to_string())?;
+    let mut untyper = Untyper {
+        hierarchy,
+        taken: domain
+            .predicates
+            .iter()
+            .map(|predicate| predicate.name.clone())
+            .collect(),
+        predicates: BTreeMap::new(),
+        declared: Vec::new(),
+    };
+    let declared: Vec<String> = untyper.hierarchy.types().map(str::to_owned).collect();
+    for type_name in &declared {
+        untyper.predicate(type_name);
+    }
+
+    let mut actions = Vec::new();
+    for action in &domain.actions {
+        let guards = untyper.guards(&action.parameters);
+        let precondition = action
+            .precondition
+            .clone()
+            .map(|precondition| untyper.fold_formula(precondition));
+        let precondition = match (guards.is_empty(), precondition) {
+            (true, precondition) => precondition,
+            (false, None) => Some(Formula::And(guards)),
+            (false, Some(Formula::And(children))) => {
+                Some(Formula::And(guards.into_iter().chain(children).collect()))
+            }
+            (false, Some(precondition)) => Some(Formula::And(
+                guards.into_iter().chain([precondition]).collect(),
+            )),
+        };
+        actions.push(Action {
+            name: action.name.clone(),
+            parameters: untyped(&action.parameters),
+            precondition,
+            effect: untyper.fold_effect(action.effect.clone()),
+        });
+    }
+
+    let mut init: Vec<OwnedFact> = problem
+        .init
+        .iter()
+        .flatten()
+        .map(|fact| fact.clone().into_owned())
+        .collect();
+    for constant in domain.constants.iter().flatten() {
+        let types: Vec<&str> = match constant {
+            Parameter::Untyped { .. } => vec![],
+            Parameter::Typed { type_name, .. } => vec![type_name],
+            Parameter::Either { type_names, .. } => type_names.iter().map(String::as_str).collect(),
+        };
+        init.extend(untyper.type_facts(constant.name(), &types));
+    }
+    let mut objects = Vec::new();
+    for object in problem.objects.iter().flatten() {
+        if let Some(type_name) = object.type_name {
+            init.extend(untyper.type_facts(object.name, &[type_name]));
+        }
+        objects.push(OwnedObject {
+            name: object.name.to_owned(),
+            type_name: None,
+        });
+    }
+
+    let mut predicates: Vec<Predicate> = domain
+        .predicates
+        .iter()
+        .map(|predicate| Predicate {
+            name: predicate.name.clone(),
+            parameters: untyped(&predicate.parameters),
+        })
+        .collect();
+    predicates.extend(untyper.declared.iter().map(|name| Predicate {
+        name: name.clone(),
+        parameters: vec![Parameter::Untyped {
+            name: "?x".to_owned(),
+        }],
+    }));
+
+    Ok(Compiled {
+        domain: Domain {
+            name: domain.name.clone(),
+            requirements: domain.requirements.as_ref().map(|requirements| {
+                requirements
+                    .iter()
+                    .filter(|requirement| *requirement != "typing")
+                    .cloned()
+                    .collect()
+            }),
+            types: None,
+            constants: domain
+                .constants
+                .as_ref()
+                .map(|constants| untyped(constants)),
+            predicates,
+            actions,
+        },
+        problem: OwnedProblem {
+            name: problem.name.map(str::to_owned),
+            domain: problem.domain.map(str::to_owned),
+            objects: problem.objects.as_ref().map(|_| objects),
+            init: Some(init),
+            goal: problem.goal.clone().map(|goal| {
+                let goal = goal.map(&mut |fact| Term {
+                    name: fact.predicate.to_owned(),
+                    parameters: fact.objects.iter().map(|&o| o.to_owned()).collect(),
+                });
+                untyper.fold_formula(goal).map(&mut |term| OwnedFact {
+                    predicate: term.name,
+                    objects: term.parameters,
+                })
+            }),
+        },
+    })
+}
+
+struct Untyper {
+    hierarchy: TypeHierarchy,
+    taken: HashSet<String>,
+    /// The predicates of types, by type
+    predicates: BTreeMap<String, String>,
+    /// The predicates of types, in the order they were declared
+    declared: Vec<String>,
+}
+
+impl Untyper {
+    /// The predicate of `type_name`, declared on first use, or `None` for [OBJECT] which every object is of
+    fn predicate(&mut self, type_name: &str) -> Option<String> {
+        if type_name == OBJECT {
+            return None;
+        }
+        if let Some(predicate) = self.predicates.get(type_name) {
+            return Some(predicate.clone());
+        }
+        let predicate = fresh(&mut self.taken, type_name.to_owned());
+        self.predicates
+            .insert(type_name.to_owned(), predicate.clone());
+        self.declared.push(predicate.clone());
+        Some(predicate)
+    }
+
+    /// The preconditions on the types of `parameters`
+    fn guards(&mut self, parameters: &[Parameter]) -> Vec<Formula<Term>> {
+        let mut guards = Vec::new();
+        for parameter in parameters {
+            let mut atom = |type_name: &str| {
+                self.predicate(type_name).map(|predicate| {
+                    Formula::Atom(Term {
+                        name: predicate,
+                        parameters: vec![parameter.name().to_owned()],
+                    })
+                })
+            };
+            match parameter {
+                Parameter::Untyped { .. } => {}
+                Parameter::Typed { type_name, .. } => guards.extend(atom(type_name)),
+                Parameter::Either { type_names, .. } => {
+                    let atoms: Option<Vec<Formula<Term>>> =
+                        type_names.iter().map(|type_name| atom(type_name)).collect();
+                    guards.extend(atoms.map(Formula::Or));
+                }
+            }
+        }
+        guards
+    }
+
+    /// The facts stating that `object` is of `types`, and of their supertypes
+    fn type_facts(&mut self, object: &str, types: &[&str]) -> Vec<OwnedFact> {
+        let mut all: Vec<String> = Vec::new();
+        for type_name in types {
+            for t in std::iter::once(*type_name).chain(self.hierarchy.ancestors(type_name)) {
+                if !all.iter().any(|known| known == t) {
+                    all.push(t.to_owned());
+                }
+            }
+        }
+        all.iter()
+            .filter_map(|type_name| self.predicate(type_name))
+            .map(|predicate| OwnedFact {
+                predicate,
+                objects: vec![object.to_owned()],
+            })
+            .collect()
+    }
+}
+
+impl Fold<Term> for Untyper {
+    /// Makes quantified formulas range over the objects of the types of their parameters
+    fn fold_formula(&mut self, formula: Formula<Term>) -> Formula<Term> {
+        match formula {
+            Formula::Forall {
+                parameters,
+                formula,
+            } => {
+                let guards = self.guards(&parameters);
+                let formula = self.fold_formula(*formula);
+                Formula::Forall {
+                    parameters: untyped(&parameters),
+                    formula: Box::new(match conjunction(guards) {
+                        Some(guard) => Formula::Imply(Box::new(guard), Box::new(formula)),
+                        None => formula,
+                    }),
+                }
+            }
+            Formula::Exists {
+                parameters,
+                formula,
+            } => {
+                let mut guards = self.guards(&parameters);
+                guards.push(self.fold_formula(*formula));
+                Formula::Exists {
+                    parameters: untyped(&parameters),
+                    formula: Box::new(conjunction(guards).expect("there is at least one formula")),
+                }
+            }
+            formula => fold_children(self, formula),
+        }
+    }
+
+    /// Conditions quantified effects on the types of their parameters
+    fn fold_effect(&mut self, effect: Effect) -> Effect {
+        let Effect::Forall { parameters, effect } = effect else {
+            return fold_effect_children(self, effect);
+        };
+        let guards = self.guards(&parameters);
+        let effect = self.fold_effect(*effect);
+        Effect::Forall {
+            parameters: untyped(&parameters),
+            effect: Box::new(match conjunction(guards) {
+                Some(condition) => Effect::When {
+                    condition,
+                    effect: Box::new(effect),
+                },
+                None => effect,
+            }),
+        }
+    }
+}
+
+/// The conjunction of `formulas`, which is the formula itself if there is one, and `None` if there are none
+fn conjunction(formulas: Vec<Formula<Term>>) -> Option<Formula<Term>> {
+    match <[_; 1]>::try_from(formulas) {
+        Ok([formula]) => Some(formula),
+        Err(formulas) if formulas.is_empty() => None,
+        Err(formulas) => Some(Formula::And(formulas)),
+    }
+}
+
+fn untyped(parameters: &[Parameter]) -> Vec<Parameter> {
+    parameters
+        .iter()
+        .map(|parameter| Parameter::Untyped {
+            name: parameter.name().to_owned(),
+        })
+        .collect()
+}
+
+/// Infers a type hierarchy for an untyped domain, from the unary static predicates which hold for its objects, which is the reverse of [compile]
+///
+/// Every unary static predicate which holds for some object is a candidate type.
+/// Candidates are subtypes of the smallest candidate holding for a superset of their objects, and of [OBJECT] if there is none.
+/// Candidates which hold for the same objects as a larger candidate are left out, as are candidates which share some objects with another but not all, as types do not overlap
+///
+/// ## Example
+/// ```rust
+/// use spingus::{compile::types::infer_types, domain::parse_domain, problem};
+/// let domain = parse_domain("(define (domain d)
+///     (:predicates (location ?x) (airport ?x) (at ?x ?y))
+///     (:action fly :parameters (?from ?to)
+///         :precondition (and (airport ?from) (airport ?to) (at ?from ?from))
+///         :effect (and (not (at ?from ?from)) (at ?to ?to))))").unwrap();
+/// let problem = problem::parse("(define (problem p)
+///     (:objects a b c)
+///     (:init (location a) (location b) (location c) (airport a) (airport b) (at a a))
+///     (:goal (at b b)))");
+/// let types = infer_types(&domain, &problem);
+/// assert_eq!(types.iter().map(|t| t.to_string()).collect::<Vec<_>>(), vec!["location - object", "airport - location"]);
+/// ```
+pub fn infer_types(domain: &Domain, problem: &Problem) -> Types {
+    let problem = lowercase_problem(problem);
+    let mut types: Types = Vec::new();
+    for (name, _, parent) in subtypes(type_predicates(domain, &problem.as_borrowed())) {
+        add_subtype(&mut types, parent.unwrap_or(OBJECT), name);
+    }
+    types
+}
+
+/// The unary static predicates of `domain`, with the objects they hold for in `problem`, if any
+///
+/// The names of `problem` must be lowercased, see [lowercase_problem]
+pub(super) fn type_predicates<'a>(
+    domain: &'a Domain,
+    problem: &Problem,
+) -> Vec<(&'a str, BTreeSet<String>)> {
+    let kinds = domain.predicate_kinds();
+    domain
+        .predicates
+        .iter()
+        .filter(|predicate| {
+            predicate.parameters.len() == 1
+                && kinds
+                    .get(predicate.name.as_str())
+                    .is_some_and(|kind| kind.is_static())
+        })
+        .map(|predicate| {
+            let objects = problem
+                .init
+                .iter()
+                .flatten()
+                .filter(|fact| fact.predicate == predicate.name)
+                .filter_map(|fact| fact.objects.first())
+                .map(|&object| object.to_owned())
+                .collect();
+            (predicate.name.as_str(), objects)
+        })
+        .filter(|(_, objects): &(&str, BTreeSet<String>)| !objects.is_empty())
+        .collect()
+}
+
+/// The candidates which are types, with their objects and supertype, which is `None` for those which are subtypes of no other candidate, see [infer_types]
+pub(super) fn subtypes(
+    mut candidates: Vec<(&str, BTreeSet<String>)>,
+) -> Vec<(&str, BTreeSet<String>, Option<&str>)> {
+    // Supertypes hold for more objects, so they are accepted before their subtypes
+    candidates.sort_by_key(|(_, objects)| Reverse(objects.len()));
+    let mut accepted: Vec<(&str, BTreeSet<String>, Option<&str>)> = Vec::new();
+    'candidates: for (name, objects) in candidates {
+        let mut parent = None;
+        for (known, known_objects, _) in &accepted {
+            if objects.is_subset(known_objects) {
+                if objects.len() == known_objects.len() {
+                    continue 'candidates;
+                }
+                parent = Some(*known);
+            } else if !objects.is_disjoint(known_objects) {
+                continue 'candidates;
+            }
+        }
+        accepted.push((name, objects, parent));
+    }
+    accepted
+}
+
+/// Declares `sub_type` as a subtype of `super_type` in `types`
+pub(super) fn add_subtype(types: &mut Types, super_type: &str, sub_type: &str) {
+    match types.iter_mut().find(|t| t.name == super_type) {
+        Some(t) => t.sub_types.push(sub_type.to_owned()),
+        None => types.push(Type {
+            name: super_type.to_owned(),
+            sub_types: vec![sub_type.to_owned()],
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compile, infer_types};
+    use crate::{domain::parse_domain, owned::AsBorrowed, problem};
+
+    #[test]
+    fn untype() {
+        let domain = parse_domain(
+            "(define (domain d)
+                (:requirements :adl :typing)
+                (:types ball box - item item room - object)
+                (:constants r0 - room)
+                (:predicates (at ?i - item ?r - room) (box ?x))
+                (:action sweep
+                    :parameters (?r - room ?t - (either ball box))
+                    :effect (forall (?i - item) (when (at ?i ?r) (at ?i r0)))))",
+        )
+        .unwrap();
+        let problem = problem::parse(
+            "(define (problem p)
+                (:objects B1 - ball c - box r1 - room)
+                (:init (at b1 r1))
+                (:goal (at b1 r0)))",
+        );
+        let untyped = compile(&domain, &problem).unwrap();
+        assert_eq!(
+            untyped.domain.to_string(),
+            "(define (domain d)
+    (:requirements :adl)
+    (:constants
+        r0
+    )
+    (:predicates
+        (at ?i ?r)
+        (box ?x)
+        (item ?x)
+        (ball ?x)
+        (box-2 ?x)
+        (room ?x)
+    )
+
+    (:action sweep
+        :parameters (?r ?t)
+        :precondition (and
+            (room ?r)
+            (or
+                (ball ?t)
+                (box-2 ?t)
+            )
+        )
+        :effect (forall (?i) (when (item ?i) (when (at ?i ?r) (at ?i r0))))
+    )
+)"
+        );
+        let init: Vec<String> = untyped
+            .problem
+            .init
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|fact| fact.to_string())
+            .collect();
+        assert_eq!(
+            init,
+            vec![
+                "(at b1 r1)",
+                "(room r0)",
+                "(ball b1)",
+                "(item b1)",
+                "(box-2 c)",
+                "(item c)",
+                "(room r1)"
+            ]
+        );
+
+        let types = infer_types(&untyped.domain, &untyped.problem.as_borrowed());
+        let types: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+        assert_eq!(types, vec!["item room - object", "ball box-2 - item"]);
+    }
+}
@@ -0,0 +1,623 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use smallvec::SmallVec;
+
+use crate::symbol::{id, Id, Interner, ObjId};
+
+id!(
+    /// The ID of a relation of a [Program]
+    RelId
+);
+
+/// The constants of a fact, in order
+pub type Tuple = SmallVec<[ObjId; 4]>;
+
+/// An argument of an [Atom], where variables are numbered within their [Rule]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Arg {
+    Variable(u32),
+    Constant(ObjId),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Atom {
+    pub relation: RelId,
+    pub args: SmallVec<[Arg; 4]>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Fact {
+    pub relation: RelId,
+    pub args: Tuple,
+}
+
+/// Derives `head` for every binding of its variables satisfying `body`, where no atom of `negative` is a fact
+///
+/// Every variable must occur in `body`, and relations of `negative` must not be derived by any rule, so that negation is evaluated against given facts only
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Rule {
+    pub head: Atom,
+    pub body: Vec<Atom>,
+    pub negative: Vec<Atom>,
+}
+
+impl Rule {
+    fn variables(&self) -> usize {
+        std::iter::once(&self.head)
+            .chain(&self.body)
+            .chain(&self.negative)
+            .flat_map(|atom| &atom.args)
+            .filter_map(|arg| match arg {
+                Arg::Variable(variable) => Some(*variable as usize + 1),
+                Arg::Constant(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// The facts of a relation, in order of derivation, indexed by the values of some of their positions
+#[derive(Debug, Clone, Default)]
+struct Relation {
+    tuples: Vec<Tuple>,
+    set: HashSet<Tuple>,
+    /// Indexes from the values of the positions in a bit mask, to the indices of the tuples with those values
+    indexes: HashMap<u64, HashMap<Tuple, Vec<u32>>>,
+}
+
+fn key(tuple: &[ObjId], mask: u64) -> Tuple {
+    tuple
+        .iter()
+        .enumerate()
+        .filter(|(position, _)| mask & (1 << position) != 0)
+        .map(|(_, value)| *value)
+        .collect()
+}
+
+impl Relation {
+    fn insert(&mut self, tuple: Tuple) -> bool {
+        if self.set.contains(&tuple) {
+            return false;
+        }
+        let index = self.tuples.len() as u32;
+        for (mask, index_map) in &mut self.indexes {
+            index_map.entry(key(&tuple, *mask)).or_default().push(index);
+        }
+        self.set.insert(tuple.clone());
+        self.tuples.push(tuple);
+        true
+    }
+
+    fn add_index(&mut self, mask: u64) {
+        if mask == 0 || self.indexes.contains_key(&mask) {
+            return;
+        }
+        let mut index_map: HashMap<Tuple, Vec<u32>> = HashMap::new();
+        for (index, tuple) in self.tuples.iter().enumerate() {
+            index_map
+                .entry(key(tuple, mask))
+                .or_default()
+                .push(index as u32);
+        }
+        self.indexes.insert(mask, index_map);
+    }
+}
+
+/// The order in which the body of a rule is joined, starting with the atom matched against new facts
+struct JoinPlan {
+    order: Vec<usize>,
+    /// For each step, the positions of the atom whose values are known before matching it
+    masks: Vec<u64>,
+}
+
+fn plan(rule: &Rule, first: usize) -> JoinPlan {
+    let mut bound: HashSet<u32> = HashSet::new();
+    let mut order = Vec::new();
+    let mut masks = Vec::new();
+    let mut remaining: Vec<usize> = (0..rule.body.len()).collect();
+    let mut next = first;
+    loop {
+        remaining.retain(|position| *position != next);
+        let atom = &rule.body[next];
+        let mut mask = 0;
+        for (position, arg) in atom.args.iter().enumerate().take(64) {
+            let known = match arg {
+                Arg::Variable(variable) => bound.contains(variable),
+                Arg::Constant(_) => true,
+            };
+            if known {
+                mask |= 1 << position;
+            }
+        }
+        bound.extend(atom.args.iter().filter_map(|arg| match arg {
+            Arg::Variable(variable) => Some(*variable),
+            Arg::Constant(_) => None,
+        }));
+        order.push(next);
+        masks.push(mask);
+
+        // Greedily joins the atom with the most known arguments, and the fewest unknown ones
+        let score = |position: &usize| {
+            let args = &rule.body[*position].args;
+            let known = args
+                .iter()
+                .filter(|arg| match arg {
+                    Arg::Variable(variable) => bound.contains(variable),
+                    Arg::Constant(_) => true,
+                })
+                .count();
+            (known, usize::MAX - (args.len() - known))
+        };
+        match remaining.iter().max_by_key(|position| score(position)) {
+            Some(position) => next = *position,
+            None => return JoinPlan { order, masks },
+        }
+    }
+}
+
+/// A set of facts and rules, evaluated bottom-up into the [Model] of all derivable facts
+///
+/// ## Example
+/// ```rust
+/// use spingus::{datalog::{Arg, Atom, Fact, Program, Rule}, symbol::ObjId};
+/// let mut program = Program::default();
+/// let edge = program.relation("edge");
+/// let path = program.relation("path");
+/// for (from, to) in [(0, 1), (1, 2), (2, 3)] {
+///     program.add_fact(Fact { relation: edge, args: vec![ObjId(from), ObjId(to)].into() }).unwrap();
+/// }
+/// let atom = |relation, args: &[u32]| Atom {
+///     relation,
+///     args: args.iter().map(|v| Arg::Variable(*v)).collect(),
+/// };
+/// program.add_rule(Rule { head: atom(path, &[0, 1]), body: vec![atom(edge, &[0, 1])], negative: vec![] }).unwrap();
+/// program.add_rule(Rule {
+///     head: atom(path, &[0, 2]),
+///     body: vec![atom(path, &[0, 1]), atom(edge, &[1, 2])],
+///     negative: vec![],
+/// }).unwrap();
+/// let model = program.evaluate().unwrap();
+/// assert_eq!(model.tuples(path).len(), 6);
+/// assert!(model.contains(&Fact { relation: path, args: vec![ObjId(0), ObjId(3)].into() }));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub relations: Interner<RelId>,
+    /// The arity of every relation, as given by its first fact or atom
+    arities: HashMap<RelId, usize>,
+    facts: Vec<Fact>,
+    rules: Vec<Rule>,
+}
+
+impl Program {
+    /// The relation named `name`, which is created if it does not exist
+    pub fn relation(&mut self, name: &str) -> RelId {
+        self.relations.intern(name)
+    }
+
+    /// Checks that every relation is used with the given number of arguments, as it was before, recording them only if all are
+    fn check_arities(
+        &mut self,
+        uses: impl IntoIterator<Item = (RelId, usize)>,
+    ) -> Result<(), String> {
+        let mut recorded = Vec::new();
+        for (relation, arity) in uses {
+            match self.arities.get(&relation) {
+                Some(expected) if *expected != arity => {
+                    let error = format!(
+                        "relation '{}' has {} arguments, but is used with {}",
+                        self.relations.name(relation),
+                        expected,
+                        arity
+                    );
+                    for relation in recorded {
+                        self.arities.remove(&relation);
+                    }
+                    return Err(error);
+                }
+                Some(_) => {}
+                None => {
+                    self.arities.insert(relation, arity);
+                    recorded.push(relation);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds `fact`, failing if its relation was used with a different number of arguments
+    pub fn add_fact(&mut self, fact: Fact) -> Result<(), String> {
+        self.check_arities([(fact.relation, fact.args.len())])?;
+        self.facts.push(fact);
+        Ok(())
+    }
+
+    /// Adds `rule`, failing if it has a variable which does not occur in its body, or uses a relation with a different number of arguments than before
+    pub fn add_rule(&mut self, rule: Rule) -> Result<(), String> {
+        let bound: HashSet<u32> = rule
+            .body
+            .iter()
+            .flat_map(|atom| &atom.args)
+            .filter_map(|arg| match arg {
+                Arg::Variable(variable) => Some(*variable),
+                Arg::Constant(_) => None,
+            })
+            .collect();
+        let unbound = std::iter::once(&rule.head)
+            .chain(&rule.negative)
+            .flat_map(|atom| &atom.args)
+            .find(|arg| matches!(arg, Arg::Variable(variable) if !bound.contains(variable)));
+        if let Some(Arg::Variable(variable)) = unbound {
+            return Err(format!(
+                "variable {} of a rule for '{}' does not occur in its body",
+                variable,
+                self.relations.name(rule.head.relation)
+            ));
+        }
+        let uses: Vec<(RelId, usize)> = std::iter::once(&rule.head)
+            .chain(&rule.body)
+            .chain(&rule.negative)
+            .map(|atom| (atom.relation, atom.args.len()))
+            .collect();
+        self.check_arities(uses)?;
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    /// Derives every fact with semi-naive evaluation, joining only with facts new since the previous round
+    ///
+    /// Fails if a relation is both negated and derived
+    pub fn evaluate(&self) -> Result<Model, String> {
+        let derived: HashSet<RelId> = self.rules.iter().map(|rule| rule.head.relation).collect();
+        for atom in self.rules.iter().flat_map(|rule| &rule.negative) {
+            if derived.contains(&atom.relation) {
+                return Err(format!(
+                    "relation '{}' is negated, but derived by a rule",
+                    self.relations.name(atom.relation)
+                ));
+            }
+        }
+
+        let mut relations = vec![Relation::default(); self.relations.len()];
+        let plans: Vec<Vec<JoinPlan>> = self
+            .rules
+            .iter()
+            .map(|rule| {
+                (0..rule.body.len())
+                    .map(|first| plan(rule, first))
+                    .collect()
+            })
+            .collect();
+        for (rule, rule_plans) in self.rules.iter().zip(&plans) {
+            for plan in rule_plans {
+                for (position, mask) in plan.order.iter().zip(&plan.masks) {
+                    relations[rule.body[*position].relation.index()].add_index(*mask);
+                }
+            }
+        }
+        for fact in &self.facts {
+            relations[fact.relation.index()].insert(fact.args.clone());
+        }
+        let mut binding = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.body.is_empty()) {
+            if let Some(tuple) = instantiate(&rule.head, &[]) {
+                relations[rule.head.relation.index()].insert(tuple);
+            }
+        }
+
+        let mut previous = vec![0; relations.len()];
+        loop {
+            let current: Vec<usize> = relations.iter().map(|r| r.tuples.len()).collect();
+            if current == previous {
+                break;
+            }
+            let mut new = Vec::new();
+            for (rule, rule_plans) in self.rules.iter().zip(&plans) {
+                for (first, plan) in rule_plans.iter().enumerate() {
+                    let relation = rule.body[first].relation.index();
+                    if previous[relation] == current[relation] {
+                        continue;
+                    }
+                    // Atoms before the first are joined with old facts only, so that each derivation is found once
+                    let ranges: Vec<Range<usize>> = rule
+                        .body
+                        .iter()
+                        .enumerate()
+                        .map(|(position, atom)| {
+                            let relation = atom.relation.index();
+                            match position.cmp(&first) {
+                                std::cmp::Ordering::Less => 0..previous[relation],
+                                std::cmp::Ordering::Equal => previous[relation]..current[relation],
+                                std::cmp::Ordering::Greater => 0..current[relation],
+                            }
+                        })
+                        .collect();
+                    binding.clear();
+                    binding.resize(rule.variables(), None);
+                    let mut join = Join {
+                        relations: &relations,
+                        rule,
+                        plan,
+                        ranges: &ranges,
+                        new: &mut new,
+                    };
+                    join.step(0, &mut binding);
+                }
+            }
+            previous = current;
+            for (relation, tuple) in new {
+                relations[relation.index()].insert(tuple);
+            }
+        }
+        Ok(Model { relations })
+    }
+}
+
+fn instantiate(atom: &Atom, binding: &[Option<ObjId>]) -> Option<Tuple> {
+    atom.args
+        .iter()
+        .map(|arg| match arg {
+            Arg::Variable(variable) => binding.get(*variable as usize).copied().flatten(),
+            Arg::Constant(constant) => Some(*constant),
+        })
+        .collect()
+}
+
+struct Join<'a> {
+    relations: &'a [Relation],
+    rule: &'a Rule,
+    plan: &'a JoinPlan,
+    ranges: &'a [Range<usize>],
+    new: &'a mut Vec<(RelId, Tuple)>,
+}
+
+impl Join<'_> {
+    fn step(&mut self, step: usize, binding: &mut Vec<Option<ObjId>>) {
+        if step == self.plan.order.len() {
+            let negated = self.rule.negative.iter().any(|atom| {
+                instantiate(atom, binding)
+                    .is_some_and(|tuple| self.relations[atom.relation.index()].set.contains(&tuple))
+            });
+            if !negated {
+                if let Some(tuple) = instantiate(&self.rule.head, binding) {
+                    let relation = &self.relations[self.rule.head.relation.index()];
+                    if !relation.set.contains(&tuple) {
+                        self.new.push((self.rule.head.relation, tuple));
+                    }
+                }
+            }
+            return;
+        }
+        let position = self.plan.order[step];
+        let mask = self.plan.masks[step];
+        let atom = &self.rule.body[position];
+        let relation = &self.relations[atom.relation.index()];
+        let range = self.ranges[position].clone();
+        let matching: &[u32] = match mask {
+            0 => &[],
+            _ => {
+                let known = instantiate_known(atom, binding, mask);
+                match relation.indexes[&mask].get(&known) {
+                    Some(indices) => indices,
+                    None => return,
+                }
+            }
+        };
+        let mut visit = |tuple: &Tuple, binding: &mut Vec<Option<ObjId>>| {
+            let mut assigned: SmallVec<[u32; 4]> = SmallVec::new();
+            let matches = atom.args.iter().zip(tuple).all(|(arg, value)| match arg {
+                Arg::Constant(constant) => constant == value,
+                Arg::Variable(variable) => match binding[*variable as usize] {
+                    Some(bound) => bound == *value,
+                    None => {
+                        binding[*variable as usize] = Some(*value);
+                        assigned.push(*variable);
+                        true
+                    }
+                },
+            });
+            if matches {
+                self.step(step + 1, binding);
+            }
+            for variable in assigned {
+                binding[variable as usize] = None;
+            }
+        };
+        match mask {
+            0 => {
+                for tuple in &relation.tuples[range] {
+                    visit(tuple, binding);
+                }
+            }
+            _ => {
+                let start = matching.partition_point(|index| (*index as usize) < range.start);
+                for index in &matching[start..] {
+                    if *index as usize >= range.end {
+                        break;
+                    }
+                    visit(&relation.tuples[*index as usize], binding);
+                }
+            }
+        }
+    }
+}
+
+fn instantiate_known(atom: &Atom, binding: &[Option<ObjId>], mask: u64) -> Tuple {
+    atom.args
+        .iter()
+        .enumerate()
+        .filter(|(position, _)| mask & (1 << position) != 0)
+        .map(|(_, arg)| match arg {
+            Arg::Variable(variable) => binding[*variable as usize].expect("known variable"),
+            Arg::Constant(constant) => *constant,
+        })
+        .collect()
+}
+
+/// Every fact derived by a [Program]
+#[derive(Debug, Clone)]
+pub struct Model {
+    relations: Vec<Relation>,
+}
+
+impl Model {
+    /// The facts of `relation`, in order of derivation
+    pub fn tuples(&self, relation: RelId) -> &[Tuple] {
+        self.relations
+            .get(relation.index())
+            .map_or(&[], |relation| &relation.tuples)
+    }
+
+    pub fn contains(&self, fact: &Fact) -> bool {
+        self.relations
+            .get(fact.relation.index())
+            .is_some_and(|relation| relation.set.contains(&fact.args))
+    }
+
+    /// The number of facts of every relation
+    pub fn len(&self) -> usize {
+        self.relations
+            .iter()
+            .map(|relation| relation.tuples.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Arg, Atom, Fact, Program, Rule, Tuple};
+    use crate::symbol::ObjId;
+
+    fn atom(relation: super::RelId, args: &[Arg]) -> Atom {
+        Atom {
+            relation,
+            args: args.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn evaluate() {
+        let mut program = Program::default();
+        let node = program.relation("node");
+        let edge = program.relation("edge");
+        let blocked = program.relation("blocked");
+        let reached = program.relation("reached");
+        let looped = program.relation("looped");
+        for n in 0..5 {
+            program
+                .add_fact(Fact {
+                    relation: node,
+                    args: vec![ObjId(n)].into(),
+                })
+                .unwrap();
+        }
+        for (from, to) in [(0, 1), (1, 2), (2, 2), (2, 3), (3, 4)] {
+            program
+                .add_fact(Fact {
+                    relation: edge,
+                    args: vec![ObjId(from), ObjId(to)].into(),
+                })
+                .unwrap();
+        }
+        program
+            .add_fact(Fact {
+                relation: blocked,
+                args: vec![ObjId(4)].into(),
+            })
+            .unwrap();
+        let (x, y) = (Arg::Variable(0), Arg::Variable(1));
+        let rules = [
+            Rule {
+                head: atom(reached, &[Arg::Constant(ObjId(0))]),
+                body: vec![],
+                negative: vec![],
+            },
+            Rule {
+                head: atom(reached, &[y]),
+                body: vec![atom(edge, &[x, y]), atom(reached, &[x])],
+                negative: vec![atom(blocked, &[y])],
+            },
+            Rule {
+                head: atom(looped, &[x]),
+                body: vec![atom(reached, &[x]), atom(edge, &[x, x])],
+                negative: vec![],
+            },
+        ];
+        for rule in rules {
+            program.add_rule(rule).unwrap();
+        }
+        let model = program.evaluate().unwrap();
+        let mut tuples: Vec<u32> = model.tuples(reached).iter().map(|t| t[0].0).collect();
+        tuples.sort();
+        assert_eq!(tuples, vec![0, 1, 2, 3]);
+        assert_eq!(
+            model.tuples(looped).to_vec(),
+            vec![Tuple::from(vec![ObjId(2)])]
+        );
+        assert_eq!(model.len(), 5 + 5 + 1 + 4 + 1);
+
+        let unsafe_rule = Rule {
+            head: atom(reached, &[y]),
+            body: vec![atom(node, &[x])],
+            negative: vec![],
+        };
+        assert!(program.add_rule(unsafe_rule).is_err());
+        let negated = Rule {
+            head: atom(blocked, &[x]),
+            body: vec![atom(node, &[x])],
+            negative: vec![atom(reached, &[x])],
+        };
+        program.add_rule(negated).unwrap();
+        assert_eq!(
+            program.evaluate().unwrap_err(),
+            "relation 'blocked' is negated, but derived by a rule"
+        );
+    }
+
+    #[test]
+    fn arities() {
+        let mut program = Program::default();
+        let edge = program.relation("edge");
+        let q = program.relation("q");
+        let h = program.relation("h");
+        program
+            .add_fact(Fact {
+                relation: edge,
+                args: vec![ObjId(0)].into(),
+            })
+            .unwrap();
+        program
+            .add_fact(Fact {
+                relation: q,
+                args: vec![ObjId(0)].into(),
+            })
+            .unwrap();
+        let (x, y) = (Arg::Variable(0), Arg::Variable(1));
+        let rule = Rule {
+            head: atom(h, &[x, y]),
+            body: vec![atom(edge, &[x, y]), atom(q, &[y])],
+            negative: vec![],
+        };
+        assert_eq!(
+            program.add_rule(rule).unwrap_err(),
+            "relation 'edge' has 1 arguments, but is used with 2"
+        );
+        assert_eq!(
+            program
+                .add_fact(Fact {
+                    relation: q,
+                    args: vec![ObjId(0), ObjId(1)].into(),
+                })
+                .unwrap_err(),
+            "relation 'q' has 1 arguments, but is used with 2"
+        );
+        assert_eq!(program.evaluate().unwrap().len(), 2);
+    }
+}
@@ -1,32 +1,42 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Write},
+};
+
 use nom::{
     branch::permutation, bytes::complete::tag, character::complete::char, combinator::opt,
-    multi::many1, sequence::delimited, IResult,
+    multi::many1, sequence::delimited,
 };
 
 use crate::{
+    compile::fresh,
     domain::requirement::parse_requirements,
-    shared::{remove_comments, spaced},
+    shared::{error_message, remove_comments, spaced, IResult},
+    writer::{impl_display, PddlWriter, WritePddl},
 };
 
 use self::{
     action::{parse_action, Actions},
     constants::parse_constants,
     name::parse_name,
-    parameter::Parameters,
+    parameter::{write_parameters, Parameters},
     predicate::{parse_predicates, Predicates},
     requirement::Requirements,
     types::{parse_types, Types},
 };
 
 pub mod action;
+pub mod builder;
 pub mod constants;
 mod name;
 pub mod parameter;
 pub mod predicate;
 pub mod requirement;
 pub mod types;
+pub mod validate;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Domain {
     pub name: String,
     pub requirements: Option<Requirements>,
@@ -36,6 +46,81 @@ pub struct Domain {
     pub actions: Actions,
 }
 
+impl WritePddl for Domain {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        write!(w, "(define (domain {})", self.name)?;
+        w.indented(|w| {
+            if let Some(requirements) = &self.requirements {
+                w.newline()?;
+                w.write_str("(:requirements")?;
+                for requirement in requirements {
+                    write!(w, " :{}", requirement)?;
+                }
+                w.write_char(')')?;
+            }
+            if let Some(types) = &self.types {
+                w.newline()?;
+                w.block(":types", types)?;
+            }
+            if let Some(constants) = &self.constants {
+                w.newline()?;
+                w.write_str("(:constants")?;
+                w.indented(|w| {
+                    w.newline()?;
+                    write_parameters(w, constants)
+                })?;
+                w.newline()?;
+                w.write_char(')')?;
+            }
+            w.newline()?;
+            w.block(":predicates", &self.predicates)?;
+            for action in &self.actions {
+                w.blank_line()?;
+                w.newline()?;
+                action.write_pddl(w)?;
+            }
+            Ok(())
+        })?;
+        w.newline()?;
+        w.write_char(')')
+    }
+}
+
+impl_display!(Domain);
+
+impl Domain {
+    /// The domain with every action split by [action::Action::split_disjunctions], so that every precondition is a conjunction of literals
+    ///
+    /// Split actions whose names are taken by another action get fresh ones, e.g. `x-1-2` if the domain has actions `x` and `x-1`
+    pub fn split_disjunctive_actions(&self, max_actions: usize) -> Result<Domain, String> {
+        let mut taken: HashSet<String> = self
+            .actions
+            .iter()
+            .map(|action| action.name.clone())
+            .collect();
+        let mut actions = Vec::new();
+        for action in &self.actions {
+            let split = action.split_disjunctions(max_actions)?;
+            if split.len() == 1 {
+                actions.extend(split);
+                continue;
+            }
+            actions.extend(split.into_iter().map(|mut part| {
+                part.name = fresh(&mut taken, part.name);
+                part
+            }));
+        }
+        Ok(Domain {
+            name: self.name.clone(),
+            requirements: self.requirements.clone(),
+            types: self.types.clone(),
+            constants: self.constants.clone(),
+            predicates: self.predicates.clone(),
+            actions,
+        })
+    }
+}
+
 fn parse_internal(input: &str) -> IResult<&str, Domain> {
     let (remaining, _) = spaced(tag("define"))(input)?;
     let (remaining, (name, requirements, types, predicates, constants, actions)) =
@@ -65,7 +150,7 @@ pub fn parse_domain(input: &str) -> Result<Domain, String> {
     let (_, domain) = match delimited(spaced(char('(')), parse_internal, spaced(char(')')))(&clean)
     {
         Ok(it) => it,
-        Err(err) => return Err(err.to_string()),
+        Err(err) => return Err(error_message(err)),
     };
     Ok(domain)
 }
@@ -74,13 +159,14 @@ pub fn parse_domain(input: &str) -> Result<Domain, String> {
 mod test {
     use crate::{
         domain::{
-            action::{string_expression::StringExpression, Action},
+            action::{effect::Effect, Action},
             parameter::Parameter,
             parse_domain,
             predicate::Predicate,
             types::Type,
             Domain,
         },
+        formula::Formula,
         term::Term,
     };
 
@@ -106,7 +192,7 @@ mod test {
                         name: "?a".to_string(),
                     }],
                     precondition: None,
-                    effect: StringExpression::And(vec![StringExpression::Predicate(Term {
+                    effect: Effect::And(vec![Effect::Add(Term {
                         name: "predicate".to_string(),
                         parameters: vec!["?a".to_string()]
                     }),])
@@ -158,7 +244,7 @@ mod test {
                         type_name: "type1".to_owned()
                     }],
                     precondition: None,
-                    effect: StringExpression::And(vec![StringExpression::Predicate(Term {
+                    effect: Effect::And(vec![Effect::Add(Term {
                         name: "predicate".to_string(),
                         parameters: vec!["?a".to_string()]
                     }),])
@@ -223,22 +309,22 @@ mod test {
                         name: "?a".to_string(),
                         type_name: "type1".to_string()
                     }],
-                    precondition: Some(StringExpression::And(vec![
-                        StringExpression::Predicate(Term {
+                    precondition: Some(Formula::And(vec![
+                        Formula::Atom(Term {
                             name: "predicate1".to_string(),
                             parameters: vec!["?a".to_string()]
                         }),
-                        StringExpression::Not(Box::new(StringExpression::Predicate(Term {
+                        Formula::Not(Box::new(Formula::Atom(Term {
                             name: "predicate2".to_string(),
                             parameters: vec!["?a".to_string()]
                         })))
                     ])),
-                    effect: StringExpression::And(vec![
-                        StringExpression::Predicate(Term {
+                    effect: Effect::And(vec![
+                        Effect::Add(Term {
                             name: "predicate1".to_string(),
                             parameters: vec!["?a".to_string()]
                         }),
-                        StringExpression::Predicate(Term {
+                        Effect::Add(Term {
                             name: "predicate2".to_string(),
                             parameters: vec!["?a".to_string()]
                         })
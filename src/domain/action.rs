@@ -1,24 +1,116 @@
+pub mod effect;
 pub mod string_expression;
-use self::string_expression::{parse_expression, StringExpression};
+use self::{
+    effect::{parse_effect as parse_effect_body, Effect},
+    string_expression::parse_expression,
+};
 
 use nom::{
     branch::permutation, bytes::complete::tag, character::complete::char, combinator::opt,
-    sequence::delimited, IResult,
+    sequence::delimited,
 };
 
-use crate::shared::{named, spaced};
+use std::fmt::{self, Write};
+
+use crate::{
+    formula::Formula,
+    shared::{named, spaced, IResult},
+    term::Term,
+    writer::{impl_display, PddlWriter, WritePddl},
+};
 
-use super::parameter::{self, Parameters};
+use super::parameter::{self, write_parameters, Parameters};
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Action {
     pub name: String,
     pub parameters: Parameters,
-    pub precondition: Option<StringExpression>,
-    pub effect: StringExpression,
+    pub precondition: Option<Formula<Term>>,
+    pub effect: Effect,
 }
 pub type Actions = Vec<Action>;
 
+impl WritePddl for Action {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        write!(w, "(:action {}", self.name)?;
+        w.indented(|w| {
+            w.newline()?;
+            w.write_str(":parameters (")?;
+            write_parameters(w, &self.parameters)?;
+            w.write_char(')')?;
+            if let Some(precondition) = &self.precondition {
+                w.newline()?;
+                w.write_str(":precondition ")?;
+                precondition.write_pddl(w)?;
+            }
+            w.newline()?;
+            w.write_str(":effect ")?;
+            self.effect.write_pddl(w)
+        })?;
+        w.newline()?;
+        w.write_char(')')
+    }
+}
+
+impl_display!(Action);
+
+impl Action {
+    /// The atoms added by the action, for STRIPS actions
+    ///
+    /// Atoms added by conditional or quantified effects are not included
+    pub fn add_list(&self) -> Vec<&Term> {
+        self.effect.add_list()
+    }
+
+    /// The atoms deleted by the action, for STRIPS actions
+    ///
+    /// Atoms deleted by conditional or quantified effects are not included
+    pub fn del_list(&self) -> Vec<&Term> {
+        self.effect.del_list()
+    }
+
+    /// Splits the action into one action per clause of the disjunctive normal form of its precondition, so that every precondition is a conjunction of literals
+    ///
+    /// The actions are named `name-1`, `name-2` and so on, unless there is only one, which keeps the name.
+    /// There are none if the precondition is unsatisfiable, and splitting fails if there would be more than `max_actions`, see [Formula::dnf_clauses]
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spingus::domain::parse_domain;
+    /// let domain = parse_domain("(define (domain d)
+    ///     (:predicates (a) (b) (c))
+    ///     (:action x :parameters () :precondition (or (a) (b)) :effect (c)))").unwrap();
+    /// let actions = domain.actions[0].split_disjunctions(8).unwrap();
+    /// assert_eq!(actions.len(), 2);
+    /// assert_eq!(actions[1].name, "x-2");
+    /// assert_eq!(actions[1].precondition.as_ref().unwrap().to_string(), "(and\n    (b)\n)");
+    /// ```
+    pub fn split_disjunctions(&self, max_actions: usize) -> Result<Vec<Action>, String> {
+        let Some(precondition) = &self.precondition else {
+            return Ok(vec![self.clone()]);
+        };
+        let clauses = precondition
+            .clone()
+            .dnf_clauses(max_actions)
+            .map_err(|err| format!("action '{}': {}", self.name, err))?;
+        let count = clauses.len();
+        Ok(clauses
+            .into_iter()
+            .enumerate()
+            .map(|(index, clause)| Action {
+                name: match count {
+                    1 => self.name.clone(),
+                    _ => format!("{}-{}", self.name, index + 1),
+                },
+                parameters: self.parameters.clone(),
+                precondition: (!clause.is_empty()).then_some(Formula::And(clause)),
+                effect: self.effect.clone(),
+            })
+            .collect())
+    }
+}
+
 fn parse_name(input: &str) -> IResult<&str, String> {
     let (remainder, name) = spaced(named)(input)?;
     Ok((remainder, name.to_string()))
@@ -33,14 +125,14 @@ fn parse_parameters(input: &str) -> IResult<&str, Parameters> {
     )(remainder)
 }
 
-fn parse_precondition(input: &str) -> IResult<&str, StringExpression> {
+fn parse_precondition(input: &str) -> IResult<&str, Formula<Term>> {
     let (remainder, _) = spaced(tag(":precondition"))(input)?;
     parse_expression(remainder)
 }
 
-fn parse_effect(input: &str) -> IResult<&str, StringExpression> {
+fn parse_effect(input: &str) -> IResult<&str, Effect> {
     let (remainder, _) = spaced(tag(":effect"))(input)?;
-    parse_expression(remainder)
+    parse_effect_body(remainder)
 }
 
 pub fn parse_action(input: &str) -> IResult<&str, Action> {
@@ -63,4 +155,15 @@ pub fn parse_action(input: &str) -> IResult<&str, Action> {
 }
 
 #[test]
-fn test() {}
+fn write() {
+    let action = "(:action move
+    :parameters (?from ?to - place)
+    :precondition (at ?from)
+    :effect (and
+        (at ?to)
+        (not (at ?from))
+    )
+)";
+    let (_, parsed) = parse_action(&action[1..action.len() - 1]).unwrap();
+    assert_eq!(action, parsed.to_string());
+}
@@ -0,0 +1,504 @@
+use nom::{
+    branch::alt,
+    character::complete::{char, one_of},
+    combinator::{map, opt},
+    multi::many0,
+    number::complete::recognize_float,
+    sequence::delimited,
+};
+
+use std::fmt::{self, Write};
+
+use crate::{
+    domain::parameter::{parse_parameters, write_parameters, Parameter},
+    formula::Formula,
+    shared::{invalid, keyword, named, spaced, IResult},
+    term::{parse_term, Term},
+    visit::{walk_effect, Visitor},
+    writer::{impl_display, PddlWriter, WritePddl},
+};
+
+use super::string_expression::parse_expression;
+
+/// The effect of an action, over atoms of type `A` and quantified parameters of type `P`
+///
+/// Parsed effects are over [Term] and [Parameter], other representations are obtained with [Effect::map]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Effect<A = Term, P = Parameter> {
+    /// Makes the atom true
+    Add(A),
+    /// Makes the atom false, written `(not atom)`
+    Delete(A),
+    And(Vec<Effect<A, P>>),
+    /// Applies `effect` only in states satisfying `condition`, written `(when condition effect)`
+    When {
+        condition: Formula<A>,
+        effect: Box<Effect<A, P>>,
+    },
+    /// Applies `effect` for every instantiation of `parameters`, written `(forall (parameters) effect)`
+    Forall {
+        parameters: Vec<P>,
+        effect: Box<Effect<A, P>>,
+    },
+    /// Updates a numeric fluent, such as `(increase (total-cost) 1)`
+    Numeric {
+        operator: AssignOperator,
+        fluent: A,
+        value: NumericExpression<A>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AssignOperator {
+    Assign,
+    Increase,
+    Decrease,
+    ScaleUp,
+    ScaleDown,
+}
+
+impl AssignOperator {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            AssignOperator::Assign => "assign",
+            AssignOperator::Increase => "increase",
+            AssignOperator::Decrease => "decrease",
+            AssignOperator::ScaleUp => "scale-up",
+            AssignOperator::ScaleDown => "scale-down",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl BinaryOperator {
+    pub fn symbol(&self) -> char {
+        match self {
+            BinaryOperator::Add => '+',
+            BinaryOperator::Subtract => '-',
+            BinaryOperator::Multiply => '*',
+            BinaryOperator::Divide => '/',
+        }
+    }
+}
+
+/// The value assigned by a numeric effect
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NumericExpression<A = Term> {
+    /// A number, kept as written
+    Number(String),
+    Fluent(A),
+    Binary(
+        BinaryOperator,
+        Box<NumericExpression<A>>,
+        Box<NumericExpression<A>>,
+    ),
+    Negate(Box<NumericExpression<A>>),
+}
+
+impl<A> NumericExpression<A> {
+    /// Converts every fluent with `atom`, keeping the structure of the expression
+    pub fn map<B, F: FnMut(A) -> B>(self, atom: &mut F) -> NumericExpression<B> {
+        match self {
+            NumericExpression::Number(number) => NumericExpression::Number(number),
+            NumericExpression::Fluent(fluent) => NumericExpression::Fluent(atom(fluent)),
+            NumericExpression::Binary(operator, left, right) => NumericExpression::Binary(
+                operator,
+                Box::new(left.map(atom)),
+                Box::new(right.map(atom)),
+            ),
+            NumericExpression::Negate(child) => {
+                NumericExpression::Negate(Box::new(child.map(atom)))
+            }
+        }
+    }
+}
+
+impl<A, P> Effect<A, P> {
+    /// Converts every atom with `atom` and every quantified parameter with `parameter`, keeping the structure of the effect
+    pub fn map<B, Q, F: FnMut(A) -> B, G: FnMut(P) -> Q>(
+        self,
+        atom: &mut F,
+        parameter: &mut G,
+    ) -> Effect<B, Q> {
+        match self {
+            Effect::Add(a) => Effect::Add(atom(a)),
+            Effect::Delete(a) => Effect::Delete(atom(a)),
+            Effect::And(children) => Effect::And(
+                children
+                    .into_iter()
+                    .map(|child| child.map(atom, parameter))
+                    .collect(),
+            ),
+            Effect::When { condition, effect } => Effect::When {
+                condition: condition.map(atom),
+                effect: Box::new(effect.map(atom, parameter)),
+            },
+            Effect::Forall { parameters, effect } => Effect::Forall {
+                parameters: parameters.into_iter().map(&mut *parameter).collect(),
+                effect: Box::new(effect.map(atom, parameter)),
+            },
+            Effect::Numeric {
+                operator,
+                fluent,
+                value,
+            } => Effect::Numeric {
+                operator,
+                fluent: atom(fluent),
+                value: value.map(atom),
+            },
+        }
+    }
+
+    /// The atoms added and deleted outside of conditional and quantified effects
+    fn literals<'a>(&'a self, adds: &mut Vec<&'a A>, deletes: &mut Vec<&'a A>) {
+        match self {
+            Effect::Add(term) => adds.push(term),
+            Effect::Delete(term) => deletes.push(term),
+            Effect::And(children) => children
+                .iter()
+                .for_each(|child| child.literals(adds, deletes)),
+            Effect::When { .. } | Effect::Forall { .. } | Effect::Numeric { .. } => {}
+        }
+    }
+
+    /// The atoms made true, excluding those of conditional and quantified effects
+    pub fn add_list(&self) -> Vec<&A> {
+        let mut adds = Vec::new();
+        self.literals(&mut adds, &mut Vec::new());
+        adds
+    }
+
+    /// The atoms made false, excluding those of conditional and quantified effects
+    pub fn del_list(&self) -> Vec<&A> {
+        let mut deletes = Vec::new();
+        self.literals(&mut Vec::new(), &mut deletes);
+        deletes
+    }
+
+    /// The atoms which may be added and deleted, including those of conditional and quantified effects
+    pub fn possible_literals(&self) -> (Vec<&A>, Vec<&A>) {
+        let mut literals = PossibleLiterals {
+            adds: Vec::new(),
+            deletes: Vec::new(),
+        };
+        literals.visit_effect(self);
+        (literals.adds, literals.deletes)
+    }
+}
+
+struct PossibleLiterals<'a, A> {
+    adds: Vec<&'a A>,
+    deletes: Vec<&'a A>,
+}
+
+impl<'a, A, P: 'a> Visitor<'a, A, P> for PossibleLiterals<'a, A> {
+    fn visit_effect(&mut self, effect: &'a Effect<A, P>) {
+        match effect {
+            Effect::Add(atom) => self.adds.push(atom),
+            Effect::Delete(atom) => self.deletes.push(atom),
+            // Conditions are not changed by the effect
+            Effect::When { effect, .. } => self.visit_effect(effect),
+            _ => walk_effect(self, effect),
+        }
+    }
+}
+
+impl WritePddl for Effect {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        match self {
+            Effect::Add(term) => term.write_pddl(w),
+            Effect::Delete(term) => {
+                w.write_str("(not ")?;
+                term.write_pddl(w)?;
+                w.write_char(')')
+            }
+            Effect::And(children) => w.block("and", children),
+            Effect::When { condition, effect } => {
+                w.write_str("(when ")?;
+                condition.write_pddl(w)?;
+                w.write_char(' ')?;
+                effect.write_pddl(w)?;
+                w.write_char(')')
+            }
+            Effect::Forall { parameters, effect } => {
+                w.write_str("(forall (")?;
+                write_parameters(w, parameters)?;
+                w.write_str(") ")?;
+                effect.write_pddl(w)?;
+                w.write_char(')')
+            }
+            Effect::Numeric {
+                operator,
+                fluent,
+                value,
+            } => {
+                write!(w, "({} ", operator.keyword())?;
+                fluent.write_pddl(w)?;
+                write!(w, " {})", value)
+            }
+        }
+    }
+}
+
+impl WritePddl for NumericExpression {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        match self {
+            NumericExpression::Number(number) => w.write_str(number),
+            NumericExpression::Fluent(term) => term.write_pddl(w),
+            NumericExpression::Binary(operator, left, right) => {
+                write!(w, "({} ", operator.symbol())?;
+                left.write_pddl(w)?;
+                w.write_char(' ')?;
+                right.write_pddl(w)?;
+                w.write_char(')')
+            }
+            NumericExpression::Negate(child) => {
+                w.write_str("(- ")?;
+                child.write_pddl(w)?;
+                w.write_char(')')
+            }
+        }
+    }
+}
+
+impl_display!(Effect, NumericExpression);
+
+fn parse_atom(input: &str) -> IResult<&str, Term> {
+    delimited(spaced(char('(')), parse_term, spaced(char(')')))(input)
+}
+
+fn parse_and(input: &str) -> IResult<&str, Effect> {
+    let (remainder, _) = keyword("and")(input)?;
+    let (remainder, children) = many0(parse_effect)(remainder)?;
+    Ok((remainder, Effect::And(children)))
+}
+
+fn parse_not(input: &str) -> IResult<&str, Effect> {
+    let (remainder, _) = keyword("not")(input)?;
+    match parse_atom(remainder) {
+        Ok((remainder, term)) => Ok((remainder, Effect::Delete(term))),
+        Err(_) => invalid(remainder, "only atoms can be negated in effects".to_owned()),
+    }
+}
+
+fn parse_when(input: &str) -> IResult<&str, Effect> {
+    let (remainder, _) = keyword("when")(input)?;
+    let (remainder, condition) = parse_expression(remainder)?;
+    let (remainder, effect) = parse_effect(remainder)?;
+    Ok((
+        remainder,
+        Effect::When {
+            condition,
+            effect: Box::new(effect),
+        },
+    ))
+}
+
+fn parse_forall(input: &str) -> IResult<&str, Effect> {
+    let (remainder, _) = keyword("forall")(input)?;
+    let (remainder, parameters) =
+        delimited(spaced(char('(')), parse_parameters, spaced(char(')')))(remainder)?;
+    let (remainder, effect) = parse_effect(remainder)?;
+    Ok((
+        remainder,
+        Effect::Forall {
+            parameters,
+            effect: Box::new(effect),
+        },
+    ))
+}
+
+/// A fluent, with or without parentheses when it has no arguments
+fn parse_fluent(input: &str) -> IResult<&str, Term> {
+    alt((
+        parse_atom,
+        map(spaced(named), |name| Term {
+            name,
+            parameters: vec![],
+        }),
+    ))(input)
+}
+
+fn parse_operation(input: &str) -> IResult<&str, NumericExpression> {
+    let (remainder, symbol) = spaced(one_of("+-*/"))(input)?;
+    let (remainder, left) = parse_numeric_expression(remainder)?;
+    let (remainder, right) = opt(parse_numeric_expression)(remainder)?;
+    let operator = match symbol {
+        '+' => BinaryOperator::Add,
+        '-' => BinaryOperator::Subtract,
+        '*' => BinaryOperator::Multiply,
+        _ => BinaryOperator::Divide,
+    };
+    match (operator, right) {
+        (BinaryOperator::Subtract, None) => {
+            Ok((remainder, NumericExpression::Negate(Box::new(left))))
+        }
+        (_, None) => invalid(
+            remainder,
+            format!("'{}' takes two arguments", operator.symbol()),
+        ),
+        (operator, Some(right)) => Ok((
+            remainder,
+            NumericExpression::Binary(operator, Box::new(left), Box::new(right)),
+        )),
+    }
+}
+
+fn parse_numeric_expression(input: &str) -> IResult<&str, NumericExpression> {
+    alt((
+        map(spaced(recognize_float), |number: &str| {
+            NumericExpression::Number(number.to_owned())
+        }),
+        delimited(spaced(char('(')), parse_operation, spaced(char(')'))),
+        map(parse_fluent, NumericExpression::Fluent),
+    ))(input)
+}
+
+fn parse_numeric(input: &str) -> IResult<&str, Effect> {
+    let (remainder, operator) = alt((
+        map(keyword("assign"), |_| AssignOperator::Assign),
+        map(keyword("increase"), |_| AssignOperator::Increase),
+        map(keyword("decrease"), |_| AssignOperator::Decrease),
+        map(keyword("scale-up"), |_| AssignOperator::ScaleUp),
+        map(keyword("scale-down"), |_| AssignOperator::ScaleDown),
+    ))(input)?;
+    let (remainder, fluent) = parse_fluent(remainder)?;
+    let (remainder, value) = parse_numeric_expression(remainder)?;
+    Ok((
+        remainder,
+        Effect::Numeric {
+            operator,
+            fluent,
+            value,
+        },
+    ))
+}
+
+/// Rejects the connectives of conditions which have no meaning in effects
+fn parse_invalid(input: &str) -> IResult<&str, Effect> {
+    let (_, word) = alt((
+        keyword("or"),
+        keyword("imply"),
+        keyword("exists"),
+        keyword("="),
+    ))(input)?;
+    invalid(
+        input,
+        format!("'{}' is not allowed in effects", word.to_lowercase()),
+    )
+}
+
+fn parse_add(input: &str) -> IResult<&str, Effect> {
+    map(parse_term, Effect::Add)(input)
+}
+
+pub(super) fn parse_effect(input: &str) -> IResult<&str, Effect> {
+    delimited(
+        spaced(char('(')),
+        alt((
+            parse_and,
+            parse_not,
+            parse_when,
+            parse_forall,
+            parse_numeric,
+            parse_invalid,
+            parse_add,
+        )),
+        spaced(char(')')),
+    )(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_effect, AssignOperator, BinaryOperator, Effect, NumericExpression};
+    use crate::{formula::Formula, term::Term};
+
+    fn term(name: &str, parameters: &[&str]) -> Term {
+        Term {
+            name: name.to_owned(),
+            parameters: parameters.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_effect("(and (p ?a) (not (q ?a ?b)))"),
+            Ok((
+                "",
+                Effect::And(vec![
+                    Effect::Add(term("p", &["?a"])),
+                    Effect::Delete(term("q", &["?a", "?b"]))
+                ])
+            ))
+        );
+        assert_eq!(
+            parse_effect("(when (p ?a) (not (p ?a)))"),
+            Ok((
+                "",
+                Effect::When {
+                    condition: Formula::Atom(term("p", &["?a"])),
+                    effect: Box::new(Effect::Delete(term("p", &["?a"])))
+                }
+            ))
+        );
+        assert_eq!(
+            parse_effect("(increase (total-cost) (* (distance ?a ?b) 2))"),
+            Ok((
+                "",
+                Effect::Numeric {
+                    operator: AssignOperator::Increase,
+                    fluent: term("total-cost", &[]),
+                    value: NumericExpression::Binary(
+                        BinaryOperator::Multiply,
+                        Box::new(NumericExpression::Fluent(term("distance", &["?a", "?b"]))),
+                        Box::new(NumericExpression::Number("2".to_owned()))
+                    )
+                }
+            ))
+        );
+        assert_eq!(
+            parse_effect("(ored ?a)"),
+            Ok(("", Effect::Add(term("ored", &["?a"]))))
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for (input, message) in [
+            ("(or (p) (q))", "'or' is not allowed in effects"),
+            (
+                "(and (p) (imply (p) (q)))",
+                "'imply' is not allowed in effects",
+            ),
+            ("(= ?a ?b)", "'=' is not allowed in effects"),
+            ("(not (and (p)))", "only atoms can be negated in effects"),
+        ] {
+            match parse_effect(input) {
+                Err(nom::Err::Failure(err)) => assert_eq!(err.message.as_deref(), Some(message)),
+                result => panic!("expected failure for {}, got {:?}", input, result),
+            }
+        }
+    }
+
+    #[test]
+    fn write() {
+        let effect = "(and
+    (forall (?b - block) (when (on ?b ?a) (not (on ?b ?a))))
+    (increase (total-cost) (- 1))
+)";
+        let (_, parsed) = parse_effect(effect).unwrap();
+        assert_eq!(parsed.to_string(), effect);
+    }
+}
@@ -4,15 +4,23 @@ use nom::{
     character::complete::{char, multispace0},
     multi::{many0, many1},
     sequence::{delimited, preceded, separated_pair},
-    IResult,
 };
 
+use std::fmt;
+
 use crate::{
-    shared::{named, spaced},
+    domain::parameter::{parse_parameters, Parameters},
+    formula::{Atom, Formula, EQUALITY},
+    shared::{keyword, named, spaced, IResult},
     term::{parse_term, Term},
+    writer::{PddlWriter, WritePddl, WriterConfig},
 };
 
+/// The formula type of domains before [Formula] was introduced
+#[deprecated(note = "use `Formula<Term>` instead")]
+#[allow(deprecated)]
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StringExpression {
     Predicate(Term),
     Equal(Vec<String>),
@@ -20,47 +28,152 @@ pub enum StringExpression {
     Or(StringExpressions),
     Not(Box<StringExpression>),
     Imply(Box<StringExpression>, Box<StringExpression>),
+    Forall(Parameters, Box<StringExpression>),
+    Exists(Parameters, Box<StringExpression>),
 }
+#[deprecated(note = "use `Vec<Formula<Term>>` instead")]
+#[allow(deprecated)]
 pub type StringExpressions = Vec<StringExpression>;
-fn parse_predicate(input: &str) -> IResult<&str, StringExpression> {
+
+#[allow(deprecated)]
+impl From<StringExpression> for Formula<Term> {
+    fn from(expression: StringExpression) -> Self {
+        let children =
+            |children: StringExpressions| children.into_iter().map(Formula::from).collect();
+        match expression {
+            StringExpression::Predicate(term) => Formula::Atom(term),
+            StringExpression::Equal(names) => Formula::Atom(Term {
+                name: EQUALITY.to_owned(),
+                parameters: names,
+            }),
+            StringExpression::And(c) => Formula::And(children(c)),
+            StringExpression::Or(c) => Formula::Or(children(c)),
+            StringExpression::Not(child) => Formula::Not(Box::new((*child).into())),
+            StringExpression::Imply(antecedent, consequent) => Formula::Imply(
+                Box::new((*antecedent).into()),
+                Box::new((*consequent).into()),
+            ),
+            StringExpression::Forall(parameters, child) => Formula::Forall {
+                parameters,
+                formula: Box::new((*child).into()),
+            },
+            StringExpression::Exists(parameters, child) => Formula::Exists {
+                parameters,
+                formula: Box::new((*child).into()),
+            },
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<Formula<Term>> for StringExpression {
+    fn from(formula: Formula<Term>) -> Self {
+        let children = |children: Vec<Formula<Term>>| {
+            children.into_iter().map(StringExpression::from).collect()
+        };
+        match formula {
+            Formula::Atom(term) if term.is_equality() => StringExpression::Equal(term.parameters),
+            Formula::Atom(term) => StringExpression::Predicate(term),
+            Formula::And(c) => StringExpression::And(children(c)),
+            Formula::Or(c) => StringExpression::Or(children(c)),
+            Formula::Not(child) => StringExpression::Not(Box::new((*child).into())),
+            Formula::Imply(antecedent, consequent) => StringExpression::Imply(
+                Box::new((*antecedent).into()),
+                Box::new((*consequent).into()),
+            ),
+            Formula::Forall {
+                parameters,
+                formula,
+            } => StringExpression::Forall(parameters, Box::new((*formula).into())),
+            Formula::Exists {
+                parameters,
+                formula,
+            } => StringExpression::Exists(parameters, Box::new((*formula).into())),
+        }
+    }
+}
+
+/// Written as the equivalent [Formula]
+#[allow(deprecated)]
+impl WritePddl for StringExpression {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        Formula::from(self.clone()).write_pddl(w)
+    }
+}
+
+#[allow(deprecated)]
+impl fmt::Display for StringExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let config = WriterConfig::default();
+        self.write_pddl(&mut PddlWriter::new(f, &config))
+    }
+}
+
+fn parse_predicate(input: &str) -> IResult<&str, Formula<Term>> {
     let (remainder, term) = parse_term(input)?;
-    Ok((remainder, StringExpression::Predicate(term)))
+    Ok((remainder, Formula::Atom(term)))
 }
 
-fn parse_equal(input: &str) -> IResult<&str, StringExpression> {
+fn parse_equal(input: &str) -> IResult<&str, Formula<Term>> {
     let (remainder, _) = preceded(multispace0, tag_no_case("="))(input)?;
-    println!("{}", remainder);
     let (remainder, children) = many1(preceded(multispace0, named))(remainder)?;
-    Ok((remainder, StringExpression::Equal(children)))
+    Ok((
+        remainder,
+        Formula::Atom(Term {
+            name: EQUALITY.to_owned(),
+            parameters: children,
+        }),
+    ))
 }
 
-fn parse_and(input: &str) -> IResult<&str, StringExpression> {
+fn parse_and(input: &str) -> IResult<&str, Formula<Term>> {
     let (remainder, _) = preceded(multispace0, tag_no_case("and"))(input)?;
     let (remainder, children) = many0(parse_expression)(remainder)?;
-    Ok((remainder, StringExpression::And(children)))
+    Ok((remainder, Formula::And(children)))
 }
 
-fn parse_or(input: &str) -> IResult<&str, StringExpression> {
+fn parse_or(input: &str) -> IResult<&str, Formula<Term>> {
     let (remainder, _) = preceded(multispace0, tag_no_case("or"))(input)?;
     let (remainder, children) = many1(parse_expression)(remainder)?;
-    Ok((remainder, StringExpression::Or(children)))
+    Ok((remainder, Formula::Or(children)))
 }
-fn parse_not(input: &str) -> IResult<&str, StringExpression> {
+fn parse_not(input: &str) -> IResult<&str, Formula<Term>> {
     let (remainder, _) = preceded(multispace0, tag_no_case("not"))(input)?;
     let (remainder, child) = parse_expression(remainder)?;
-    Ok((remainder, StringExpression::Not(Box::new(child))))
+    Ok((remainder, Formula::Not(Box::new(child))))
+}
+fn parse_quantified(input: &str) -> IResult<&str, Formula<Term>> {
+    let (remainder, quantifier) = alt((keyword("forall"), keyword("exists")))(input)?;
+    let (remainder, parameters) =
+        delimited(spaced(char('(')), parse_parameters, spaced(char(')')))(remainder)?;
+    let (remainder, formula) = parse_expression(remainder)?;
+    let formula = Box::new(formula);
+    Ok((
+        remainder,
+        match quantifier.to_lowercase().as_str() {
+            "forall" => Formula::Forall {
+                parameters,
+                formula,
+            },
+            _ => Formula::Exists {
+                parameters,
+                formula,
+            },
+        },
+    ))
 }
-fn parse_imply(input: &str) -> IResult<&str, StringExpression> {
+
+fn parse_imply(input: &str) -> IResult<&str, Formula<Term>> {
     let (remainder, _) = preceded(multispace0, tag_no_case("imply"))(input)?;
     let (remainder, (antecendent, consequent)) =
         separated_pair(parse_expression, multispace0, parse_expression)(remainder)?;
     Ok((
         remainder,
-        StringExpression::Imply(Box::new(antecendent), Box::new(consequent)),
+        Formula::Imply(Box::new(antecendent), Box::new(consequent)),
     ))
 }
 
-pub(super) fn parse_expression(input: &str) -> IResult<&str, StringExpression> {
+pub(super) fn parse_expression(input: &str) -> IResult<&str, Formula<Term>> {
     delimited(
         spaced(char('(')),
         alt((
@@ -69,18 +182,41 @@ pub(super) fn parse_expression(input: &str) -> IResult<&str, StringExpression> {
             parse_not,
             parse_equal,
             parse_imply,
+            parse_quantified,
             parse_predicate,
         )),
         spaced(char(')')),
     )(input)
 }
 
+#[test]
+fn write() {
+    let expression = Formula::And(vec![
+        Formula::Atom(Term {
+            name: "predicate".to_string(),
+            parameters: vec!["?a".to_string()],
+        }),
+        Formula::Not(Box::new(Formula::Atom(Term {
+            name: "=".to_string(),
+            parameters: vec!["?a".to_string(), "?b".to_string()],
+        }))),
+    ]);
+    assert_eq!(
+        "(and\n    (predicate ?a)\n    (not (= ?a ?b))\n)",
+        expression.to_string()
+    );
+    assert_eq!(
+        Ok(("", expression.clone())),
+        parse_expression(&expression.to_string())
+    );
+}
+
 #[test]
 fn test() {
     assert_eq!(
         Ok((
             "",
-            StringExpression::Predicate(Term {
+            Formula::Atom(Term {
                 name: "predicate".to_string(),
                 parameters: vec![]
             })
@@ -90,7 +226,7 @@ fn test() {
     assert_eq!(
         Ok((
             "",
-            StringExpression::Predicate(Term {
+            Formula::Atom(Term {
                 name: "predicate".to_string(),
                 parameters: vec!["?a".to_string()]
             })
@@ -100,7 +236,7 @@ fn test() {
     assert_eq!(
         Ok((
             "",
-            StringExpression::Predicate(Term {
+            Formula::Atom(Term {
                 name: "predicate".to_string(),
                 parameters: vec!["?a".to_string(), "?b".to_string()]
             })
@@ -110,7 +246,7 @@ fn test() {
     assert_eq!(
         Ok((
             "",
-            StringExpression::Not(Box::new(StringExpression::Predicate(Term {
+            Formula::Not(Box::new(Formula::Atom(Term {
                 name: "predicate".to_string(),
                 parameters: vec!["?a".to_string()]
             })))
@@ -120,7 +256,7 @@ fn test() {
     assert_eq!(
         Ok((
             "",
-            StringExpression::And(vec![StringExpression::Predicate(Term {
+            Formula::And(vec![Formula::Atom(Term {
                 name: "predicate".to_string(),
                 parameters: vec!["?a".to_string()]
             })])
@@ -130,12 +266,12 @@ fn test() {
     assert_eq!(
         Ok((
             "",
-            StringExpression::And(vec![
-                StringExpression::Predicate(Term {
+            Formula::And(vec![
+                Formula::Atom(Term {
                     name: "predicate".to_string(),
                     parameters: vec!["?a".to_string()]
                 }),
-                StringExpression::Predicate(Term {
+                Formula::Atom(Term {
                     name: "predicate".to_string(),
                     parameters: vec!["?b".to_string()]
                 })
@@ -146,7 +282,7 @@ fn test() {
     assert_eq!(
         Ok((
             "",
-            StringExpression::Or(vec![StringExpression::Predicate(Term {
+            Formula::Or(vec![Formula::Atom(Term {
                 name: "predicate".to_string(),
                 parameters: vec!["?a".to_string()]
             })])
@@ -156,12 +292,12 @@ fn test() {
     assert_eq!(
         Ok((
             "",
-            StringExpression::Or(vec![
-                StringExpression::Predicate(Term {
+            Formula::Or(vec![
+                Formula::Atom(Term {
                     name: "predicate".to_string(),
                     parameters: vec!["?a".to_string()]
                 }),
-                StringExpression::Predicate(Term {
+                Formula::Atom(Term {
                     name: "predicate".to_string(),
                     parameters: vec!["?b".to_string()]
                 }),
@@ -172,19 +308,22 @@ fn test() {
     assert_eq!(
         Ok((
             "",
-            StringExpression::Equal(vec!["?a".to_string(), "?b".to_string(),])
+            Formula::Atom(Term {
+                name: "=".to_string(),
+                parameters: vec!["?a".to_string(), "?b".to_string()]
+            })
         )),
         parse_expression("(= ?a ?b)")
     );
     assert_eq!(
         Ok((
             "",
-            StringExpression::Imply(
-                Box::new(StringExpression::Predicate(Term {
+            Formula::Imply(
+                Box::new(Formula::Atom(Term {
                     name: "predicate".to_string(),
                     parameters: vec!["?a".to_string()]
                 })),
-                Box::new(StringExpression::Predicate(Term {
+                Box::new(Formula::Atom(Term {
                     name: "predicate".to_string(),
                     parameters: vec!["?b".to_string()]
                 }))
@@ -195,12 +334,12 @@ fn test() {
     assert_eq!(
         Ok((
             "",
-            StringExpression::Imply(
-                Box::new(StringExpression::Predicate(Term {
+            Formula::Imply(
+                Box::new(Formula::Atom(Term {
                     name: "closer".to_string(),
                     parameters: vec!["?a2".to_string(), "?a1".to_string()]
                 })),
-                Box::new(StringExpression::Predicate(Term {
+                Box::new(Formula::Atom(Term {
                     name: "free".to_string(),
                     parameters: vec!["?a2".to_string(), "?t".to_string()]
                 }))
@@ -209,3 +348,25 @@ fn test() {
         parse_expression("(imply (closer ?a2 ?a1) (free ?a2 ?t))")
     );
 }
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_expression() {
+    let expression = StringExpression::Imply(
+        Box::new(StringExpression::Equal(vec![
+            "?a".to_string(),
+            "?b".to_string(),
+        ])),
+        Box::new(StringExpression::Predicate(Term {
+            name: "predicate".to_string(),
+            parameters: vec!["?a".to_string()],
+        })),
+    );
+    assert_eq!(expression.to_string(), "(imply (= ?a ?b) (predicate ?a))");
+    let formula = Formula::from(expression.clone());
+    assert_eq!(
+        Ok(("", formula.clone())),
+        parse_expression("(imply (= ?a ?b) (predicate ?a))")
+    );
+    assert_eq!(StringExpression::from(formula), expression);
+}
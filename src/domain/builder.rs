@@ -0,0 +1,405 @@
+use crate::{
+    formula::{Formula, EQUALITY},
+    term::Term,
+};
+
+use super::{
+    action::{effect::Effect, Action},
+    parameter::{Parameter, Parameters},
+    predicate::Predicate,
+    types::{Type, Types},
+    validate::Severity,
+    Domain,
+};
+
+/// Whether a name can be written as PDDL and parsed back
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let name = name.strip_prefix('?').unwrap_or(name);
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// An untyped parameter, such as `?x`
+pub fn untyped(name: &str) -> Parameter {
+    Parameter::Untyped {
+        name: name.to_lowercase(),
+    }
+}
+
+/// A typed parameter, such as `?x - type`
+pub fn typed(name: &str, type_name: &str) -> Parameter {
+    Parameter::Typed {
+        name: name.to_lowercase(),
+        type_name: type_name.to_lowercase(),
+    }
+}
+
+/// A parameter of any of multiple types, such as `?x - (either a b)`
+pub fn either<'a>(name: &str, type_names: impl IntoIterator<Item = &'a str>) -> Parameter {
+    Parameter::Either {
+        name: name.to_lowercase(),
+        type_names: type_names.into_iter().map(str::to_lowercase).collect(),
+    }
+}
+
+/// The atom `(name arguments...)`
+pub fn atom<'a>(name: &str, arguments: impl IntoIterator<Item = &'a str>) -> Formula<Term> {
+    Formula::Atom(Term {
+        name: name.to_lowercase(),
+        parameters: arguments.into_iter().map(str::to_lowercase).collect(),
+    })
+}
+
+/// The equality `(= arguments...)`
+pub fn equal<'a>(arguments: impl IntoIterator<Item = &'a str>) -> Formula<Term> {
+    atom(EQUALITY, arguments)
+}
+
+pub fn and(children: impl IntoIterator<Item = Formula<Term>>) -> Formula<Term> {
+    Formula::And(children.into_iter().collect())
+}
+
+pub fn or(children: impl IntoIterator<Item = Formula<Term>>) -> Formula<Term> {
+    Formula::Or(children.into_iter().collect())
+}
+
+pub fn not(child: Formula<Term>) -> Formula<Term> {
+    Formula::Not(Box::new(child))
+}
+
+pub fn imply(antecedent: Formula<Term>, consequent: Formula<Term>) -> Formula<Term> {
+    Formula::Imply(Box::new(antecedent), Box::new(consequent))
+}
+
+/// The effect adding the atom `(name arguments...)`
+pub fn add<'a>(name: &str, arguments: impl IntoIterator<Item = &'a str>) -> Effect {
+    Effect::Add(Term {
+        name: name.to_lowercase(),
+        parameters: arguments.into_iter().map(str::to_lowercase).collect(),
+    })
+}
+
+/// The effect deleting the atom `(name arguments...)`
+pub fn delete<'a>(name: &str, arguments: impl IntoIterator<Item = &'a str>) -> Effect {
+    Effect::Delete(Term {
+        name: name.to_lowercase(),
+        parameters: arguments.into_iter().map(str::to_lowercase).collect(),
+    })
+}
+
+pub fn when(condition: Formula<Term>, effect: Effect) -> Effect {
+    Effect::When {
+        condition,
+        effect: Box::new(effect),
+    }
+}
+
+pub fn forall(parameters: impl IntoIterator<Item = Parameter>, effect: Effect) -> Effect {
+    Effect::Forall {
+        parameters: parameters.into_iter().collect(),
+        effect: Box::new(effect),
+    }
+}
+
+/// Combines parts of a precondition, avoiding a conjunction of a single part
+fn conjoin(mut parts: Vec<Formula<Term>>) -> Option<Formula<Term>> {
+    match parts.len() {
+        0 => None,
+        1 => parts.pop(),
+        _ => Some(Formula::And(parts)),
+    }
+}
+
+/// Builds an [Action]
+///
+/// Names are lowercased, as they are when parsing
+#[derive(Debug, Clone, Default)]
+pub struct ActionBuilder {
+    name: String,
+    parameters: Parameters,
+    precondition: Vec<Formula<Term>>,
+    effect: Vec<Effect>,
+}
+
+impl ActionBuilder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_lowercase(),
+            ..Default::default()
+        }
+    }
+
+    pub fn parameter(mut self, parameter: Parameter) -> Self {
+        self.parameters.push(parameter);
+        self
+    }
+
+    pub fn parameters(mut self, parameters: impl IntoIterator<Item = Parameter>) -> Self {
+        self.parameters.extend(parameters);
+        self
+    }
+
+    /// Adds a part of the precondition, multiple parts are conjoined
+    pub fn precondition(mut self, precondition: Formula<Term>) -> Self {
+        self.precondition.push(precondition);
+        self
+    }
+
+    /// Adds a part of the effect, multiple parts are conjoined
+    pub fn effect(mut self, effect: Effect) -> Self {
+        self.effect.push(effect);
+        self
+    }
+
+    fn into_action(mut self) -> Action {
+        Action {
+            name: self.name,
+            parameters: self.parameters,
+            precondition: conjoin(self.precondition),
+            effect: match self.effect.len() {
+                1 => self.effect.pop().unwrap(),
+                _ => Effect::And(self.effect),
+            },
+        }
+    }
+}
+
+/// Builds a [Domain], validating it on [DomainBuilder::build]
+///
+/// Names are lowercased, as they are when parsing
+///
+/// ## Example
+/// ```rust
+/// use spingus::domain::builder::{add, atom, delete, typed, ActionBuilder, DomainBuilder};
+/// use spingus::writer::WritePddl;
+/// let domain = DomainBuilder::new("rooms")
+///     .requirement("typing")
+///     .types(["room"], "object")
+///     .predicate("at", [typed("?r", "room")])
+///     .action(
+///         ActionBuilder::new("move")
+///             .parameters([typed("?from", "room"), typed("?to", "room")])
+///             .precondition(atom("at", ["?from"]))
+///             .effect(add("at", ["?to"]))
+///             .effect(delete("at", ["?from"])),
+///     )
+///     .build()
+///     .unwrap();
+/// assert_eq!(spingus::domain::parse_domain(&domain.to_pddl()), Ok(domain));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DomainBuilder {
+    name: String,
+    requirements: Option<Vec<String>>,
+    types: Option<Types>,
+    constants: Option<Parameters>,
+    predicates: Vec<Predicate>,
+    actions: Vec<ActionBuilder>,
+}
+
+impl DomainBuilder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_lowercase(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a requirement, given without the leading colon
+    pub fn requirement(mut self, requirement: &str) -> Self {
+        self.requirements
+            .get_or_insert_with(Vec::new)
+            .push(requirement.trim_start_matches(':').to_lowercase());
+        self
+    }
+
+    /// Declares `sub_types` as subtypes of `type_name`
+    pub fn types<'a>(
+        mut self,
+        sub_types: impl IntoIterator<Item = &'a str>,
+        type_name: &str,
+    ) -> Self {
+        let type_name = type_name.to_lowercase();
+        let sub_types = sub_types.into_iter().map(str::to_lowercase);
+        let types = self.types.get_or_insert_with(Vec::new);
+        match types.iter_mut().find(|t| t.name == type_name) {
+            Some(t) => t.sub_types.extend(sub_types),
+            None => types.push(Type {
+                name: type_name,
+                sub_types: sub_types.collect(),
+            }),
+        }
+        self
+    }
+
+    pub fn constant(mut self, constant: Parameter) -> Self {
+        self.constants.get_or_insert_with(Vec::new).push(constant);
+        self
+    }
+
+    pub fn predicate(
+        mut self,
+        name: &str,
+        parameters: impl IntoIterator<Item = Parameter>,
+    ) -> Self {
+        self.predicates.push(Predicate {
+            name: name.to_lowercase(),
+            parameters: parameters.into_iter().collect(),
+        });
+        self
+    }
+
+    pub fn action(mut self, action: ActionBuilder) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Builds the domain
+    ///
+    /// Fails if a name is not a valid identifier, or with the first error reported by [Domain::validate], such as a name declared twice or an action using an undeclared predicate
+    pub fn build(self) -> Result<Domain, String> {
+        let domain = Domain {
+            name: self.name,
+            requirements: self.requirements,
+            types: self.types,
+            constants: self.constants,
+            predicates: self.predicates,
+            actions: self
+                .actions
+                .into_iter()
+                .map(ActionBuilder::into_action)
+                .collect(),
+        };
+        check_domain(&domain)?;
+        Ok(domain)
+    }
+}
+
+fn check_name(kind: &str, name: &str) -> Result<(), String> {
+    match is_identifier(name) {
+        true => Ok(()),
+        false => Err(format!("invalid {} name '{}'", kind, name)),
+    }
+}
+
+fn check_names<'a>(kind: &str, names: impl IntoIterator<Item = &'a str>) -> Result<(), String> {
+    names
+        .into_iter()
+        .try_for_each(|name| check_name(kind, name))
+}
+
+fn check_domain(domain: &Domain) -> Result<(), String> {
+    check_name("domain", &domain.name)?;
+    if let Some(types) = &domain.types {
+        check_names(
+            "type",
+            types
+                .iter()
+                .flat_map(|t| t.sub_types.iter().map(String::as_str)),
+        )?;
+    }
+    if let Some(constants) = &domain.constants {
+        check_names("constant", constants.iter().map(Parameter::name))?;
+    }
+    check_names(
+        "predicate",
+        domain.predicates.iter().map(|p| p.name.as_str()),
+    )?;
+    check_names("action", domain.actions.iter().map(|a| a.name.as_str()))?;
+    let parameters = domain
+        .predicates
+        .iter()
+        .flat_map(|p| &p.parameters)
+        .chain(domain.actions.iter().flat_map(|a| &a.parameters));
+    check_names("parameter", parameters.map(Parameter::name))?;
+    match domain
+        .validate()
+        .into_iter()
+        .find(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        Some(error) => Err(error.message),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        add, atom, delete, equal, forall, not, typed, untyped, when, ActionBuilder, DomainBuilder,
+    };
+    use crate::{domain::parse_domain, writer::WritePddl};
+
+    fn builder() -> DomainBuilder {
+        DomainBuilder::new("Blocks")
+            .requirement(":strips")
+            .requirement("typing")
+            .types(["block"], "object")
+            .constant(typed("table", "block"))
+            .predicate("on", [typed("?x", "block"), typed("?y", "block")])
+            .predicate("clear", [typed("?x", "block")])
+    }
+
+    #[test]
+    fn build() {
+        let domain = builder()
+            .action(
+                ActionBuilder::new("Stack")
+                    .parameters([typed("?x", "block"), typed("?y", "block")])
+                    .precondition(atom("clear", ["?x"]))
+                    .precondition(not(equal(["?x", "?y"])))
+                    .effect(add("on", ["?x", "?y"]))
+                    .effect(forall(
+                        [typed("?z", "block")],
+                        when(atom("on", ["?z", "?x"]), delete("clear", ["?y"])),
+                    )),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(domain.name, "blocks");
+        assert_eq!(
+            domain.requirements,
+            Some(vec!["strips".to_owned(), "typing".to_owned()])
+        );
+        assert_eq!(domain.actions[0].name, "stack");
+        assert_eq!(parse_domain(&domain.to_pddl()), Ok(domain));
+    }
+
+    #[test]
+    fn build_errors() {
+        let action = || ActionBuilder::new("a").parameter(untyped("?x"));
+        let errors = [
+            (
+                builder().predicate("clear", []),
+                "predicate 'clear' is declared twice",
+            ),
+            (
+                builder().action(action().effect(add("free", ["?x"]))),
+                "action 'a': predicate 'free' is not declared",
+            ),
+            (
+                builder().action(action().effect(add("clear", ["?x", "?x"]))),
+                "action 'a': predicate 'clear' takes 1 arguments, but is given 2",
+            ),
+            (
+                builder().action(action().effect(delete("clear", ["?y"]))),
+                "action 'a': variable '?y' is not bound",
+            ),
+            (
+                builder().action(action().parameter(typed("?y", "ball"))),
+                "action 'a': type 'ball' is not declared",
+            ),
+            (
+                builder().action(action()).action(action()),
+                "action 'a' is declared twice",
+            ),
+            (
+                builder().predicate("has space", []),
+                "invalid predicate name 'has space'",
+            ),
+        ];
+        for (builder, error) in errors {
+            assert_eq!(builder.build(), Err(error.to_owned()));
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use nom::{bytes::complete::tag_no_case, IResult};
+use nom::bytes::complete::tag_no_case;
 
-use crate::shared::spaced;
+use crate::shared::{spaced, IResult};
 
 use super::parameter::{parse_parameters, Parameters};
 
@@ -1,6 +1,6 @@
-use nom::{bytes::complete::tag, character::complete::multispace0, sequence::preceded, IResult};
+use nom::{bytes::complete::tag, character::complete::multispace0, sequence::preceded};
 
-use crate::shared::named;
+use crate::shared::{named, IResult};
 
 pub(super) fn parse_name(input: &str) -> IResult<&str, String> {
     let (remainder, _) = tag("domain")(input)?;
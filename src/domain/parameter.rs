@@ -4,12 +4,18 @@ use nom::{
     character::complete::{char, multispace0, multispace1},
     multi::{fold_many0, many1, separated_list1},
     sequence::{delimited, preceded, separated_pair},
-    IResult,
 };
 
-use crate::shared::{named, spaced};
+use std::fmt::{self, Write};
+
+use crate::{
+    domain::types::hierarchy::OBJECT,
+    shared::{named, spaced, IResult},
+    writer::{impl_display, PddlWriter, WritePddl},
+};
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Parameter {
     Untyped {
         name: String,
@@ -91,33 +97,116 @@ pub(super) fn parse_parameters(input: &str) -> IResult<&str, Parameters> {
     )(input)
 }
 
-pub fn parameters_to_string(parameters: &Parameters) -> String {
-    let mut s: String = " ".to_string();
+impl Parameter {
+    pub fn name(&self) -> &str {
+        match self {
+            Parameter::Untyped { name }
+            | Parameter::Typed { name, .. }
+            | Parameter::Either { name, .. } => name,
+        }
+    }
 
-    for parameter in parameters {
-        let parameter_s;
-        match parameter {
-            Parameter::Untyped { name } => {
-                parameter_s = name.to_string();
+    fn same_type(&self, other: &Parameter) -> bool {
+        match (self, other) {
+            (Parameter::Untyped { .. }, Parameter::Untyped { .. }) => true,
+            (Parameter::Typed { type_name: a, .. }, Parameter::Typed { type_name: b, .. }) => {
+                a == b
             }
-            Parameter::Typed { name, type_name } => {
-                parameter_s = format!("{} - {}", name, type_name);
+            (Parameter::Either { type_names: a, .. }, Parameter::Either { type_names: b, .. }) => {
+                a == b
             }
-            Parameter::Either { name, type_names } => {
-                let mut s = "".to_string();
-                type_names
-                    .iter()
-                    .for_each(|n| s.push_str(&format!(" {}", n)));
-                parameter_s = format!("{} - (either {})", name, format!("(either{})", s));
+            _ => false,
+        }
+    }
+
+    fn write_type(&self, w: &mut PddlWriter) -> fmt::Result {
+        match self {
+            Parameter::Untyped { .. } => Ok(()),
+            Parameter::Typed { type_name, .. } => write!(w, " - {}", type_name),
+            Parameter::Either { type_names, .. } => {
+                w.write_str(" - (either")?;
+                for type_name in type_names {
+                    write!(w, " {}", type_name)?;
+                }
+                w.write_char(')')
+            }
+        }
+    }
+}
+
+impl WritePddl for Parameter {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        w.write_str(self.name())?;
+        self.write_type(w)
+    }
+}
+
+impl_display!(Parameter);
+
+/// Writes parameters as a typed list, where consecutive parameters of the same type share it
+///
+/// E.g. `?a ?b - type1 ?c - type2`.
+/// Untyped parameters followed by typed ones are written as of type `object`, as the type after them would apply to them as well
+pub fn write_parameters(w: &mut PddlWriter, parameters: &[Parameter]) -> fmt::Result {
+    for (i, parameter) in parameters.iter().enumerate() {
+        if i > 0 {
+            w.write_char(' ')?;
+        }
+        w.write_str(parameter.name())?;
+        match parameters.get(i + 1) {
+            Some(next) if parameter.same_type(next) => {}
+            Some(_) if matches!(parameter, Parameter::Untyped { .. }) => {
+                write!(w, " - {}", OBJECT)?
             }
+            _ => parameter.write_type(w)?,
         }
-        s.push_str(&parameter_s);
-        s.push_str(" ");
+    }
+    Ok(())
+}
+
+pub fn parameters_to_string(parameters: &Parameters) -> String {
+    let mut s: String = " ".to_string();
+
+    for parameter in parameters {
+        s.push_str(&parameter.to_string());
+        s.push(' ');
     }
 
     s
 }
 
+#[test]
+fn write() {
+    use crate::writer::WriterConfig;
+
+    let parameters = vec![
+        Parameter::Typed {
+            name: "?p1".to_string(),
+            type_name: "type".to_string(),
+        },
+        Parameter::Typed {
+            name: "?p2".to_string(),
+            type_name: "type".to_string(),
+        },
+        Parameter::Either {
+            name: "?p3".to_string(),
+            type_names: vec!["type_a".to_string(), "type_b".to_string()],
+        },
+        Parameter::Untyped {
+            name: "?p4".to_string(),
+        },
+    ];
+    let mut out = String::new();
+    let config = WriterConfig::default();
+    write_parameters(&mut PddlWriter::new(&mut out, &config), &parameters).unwrap();
+    assert_eq!("?p1 ?p2 - type ?p3 - (either type_a type_b) ?p4", out);
+    assert_eq!(Ok(("", parameters.clone())), parse_parameters(&out));
+    assert_eq!(
+        " ?p3 - (either type_a type_b) ",
+        parameters_to_string(&vec![parameters[2].clone()])
+    );
+}
+
 #[test]
 fn test() {
     assert_eq!(
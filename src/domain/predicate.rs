@@ -3,20 +3,117 @@ use nom::{
     character::complete::{char, multispace0},
     multi::many1,
     sequence::{delimited, preceded},
-    IResult,
 };
 
-use crate::shared::{named, spaced};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+};
 
-use super::parameter::{parse_parameters, Parameters};
+use crate::{
+    formula::Atom,
+    shared::{named, spaced, IResult},
+    writer::{impl_display, PddlWriter, WritePddl},
+};
+
+use super::{
+    parameter::{parse_parameters, write_parameters, Parameters},
+    Domain,
+};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Predicate {
     pub name: String,
     pub parameters: Parameters,
 }
 pub type Predicates = Vec<Predicate>;
 
+impl WritePddl for Predicate {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        write!(w, "({}", self.name)?;
+        if !self.parameters.is_empty() {
+            w.write_char(' ')?;
+            write_parameters(w, &self.parameters)?;
+        }
+        w.write_char(')')
+    }
+}
+
+impl_display!(Predicate);
+
+/// How the actions of a domain can change the atoms of a predicate, see [Domain::predicate_kinds]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum PredicateKind {
+    /// No action adds or deletes the predicate, so its atoms keep their initial value
+    Static,
+    /// Actions add the predicate, but never delete it
+    AddOnly,
+    /// Actions delete the predicate, but never add it
+    DeleteOnly,
+    /// Actions both add and delete the predicate
+    Fluent,
+}
+
+impl PredicateKind {
+    pub fn is_static(&self) -> bool {
+        *self == PredicateKind::Static
+    }
+}
+
+impl Domain {
+    /// The [PredicateKind] of every declared predicate, by name
+    ///
+    /// Conditional and quantified effects count as changing their atoms, as they may.
+    /// Equality is not a declared predicate, and is always static
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spingus::domain::{parse_domain, predicate::PredicateKind};
+    /// let domain = parse_domain("(define (domain d)
+    ///     (:predicates (road ?a ?b) (at ?a) (visited ?a))
+    ///     (:action go :parameters (?a ?b)
+    ///         :precondition (and (at ?a) (road ?a ?b))
+    ///         :effect (and (not (at ?a)) (at ?b) (visited ?b))))").unwrap();
+    /// let kinds = domain.predicate_kinds();
+    /// assert_eq!(kinds["road"], PredicateKind::Static);
+    /// assert_eq!(kinds["at"], PredicateKind::Fluent);
+    /// assert_eq!(kinds["visited"], PredicateKind::AddOnly);
+    /// ```
+    pub fn predicate_kinds(&self) -> BTreeMap<&str, PredicateKind> {
+        let mut changes: BTreeMap<&str, (bool, bool)> = self
+            .predicates
+            .iter()
+            .map(|predicate| (predicate.name.as_str(), (false, false)))
+            .collect();
+        for action in &self.actions {
+            let (adds, deletes) = action.effect.possible_literals();
+            for term in adds {
+                if let Some((added, _)) = changes.get_mut(term.predicate()) {
+                    *added = true;
+                }
+            }
+            for term in deletes {
+                if let Some((_, deleted)) = changes.get_mut(term.predicate()) {
+                    *deleted = true;
+                }
+            }
+        }
+        changes
+            .into_iter()
+            .map(|(name, changes)| {
+                let kind = match changes {
+                    (false, false) => PredicateKind::Static,
+                    (true, false) => PredicateKind::AddOnly,
+                    (false, true) => PredicateKind::DeleteOnly,
+                    (true, true) => PredicateKind::Fluent,
+                };
+                (name, kind)
+            })
+            .collect()
+    }
+}
+
 pub fn parse_predicate(input: &str) -> IResult<&str, Predicate> {
     let (remainder, name) = preceded(multispace0, named)(input)?;
     let (remainder, parameters) = parse_parameters(remainder)?;
@@ -44,9 +141,29 @@ pub(super) fn parse_predicates(input: &str) -> IResult<&str, Predicates> {
 mod test {
     use crate::domain::{
         parameter::Parameter,
-        predicate::{parse_predicates, Predicate},
+        parse_domain,
+        predicate::{parse_predicates, Predicate, PredicateKind},
     };
 
+    #[test]
+    fn kinds() {
+        let domain = parse_domain(
+            "(define (domain d)
+                (:predicates (a) (b) (c) (d) (e))
+                (:action x :parameters ()
+                    :effect (and (not (a)) (forall (?o) (when (c) (b)))))
+                (:action y :parameters () :precondition (e) :effect (and (not (b)))))",
+        )
+        .unwrap();
+        let kinds = domain.predicate_kinds();
+        assert_eq!(kinds["a"], PredicateKind::DeleteOnly);
+        assert_eq!(kinds["b"], PredicateKind::Fluent);
+        assert_eq!(kinds["c"], PredicateKind::Static);
+        assert_eq!(kinds["d"], PredicateKind::Static);
+        assert_eq!(kinds["e"], PredicateKind::Static);
+        assert_eq!(kinds.len(), 5);
+    }
+
     #[test]
     fn test() {
         assert_eq!(
@@ -1,9 +1,8 @@
 use nom::{
     bytes::complete::tag, character::complete::char, multi::separated_list0, sequence::preceded,
-    IResult,
 };
 
-use crate::shared::{named, spaced};
+use crate::shared::{named, spaced, IResult};
 
 pub type Requirements = Vec<String>;
 
@@ -1,21 +1,42 @@
+pub mod hierarchy;
+
 use nom::{
     branch::alt,
     bytes::complete::tag,
     character::complete::{char, multispace0, multispace1},
     multi::{many1, separated_list1},
     sequence::{delimited, preceded, separated_pair},
-    IResult,
 };
 
-use crate::shared::{named, spaced};
+use std::fmt::{self, Write};
+
+use crate::{
+    shared::{named, spaced, IResult},
+    writer::{impl_display, PddlWriter, WritePddl},
+};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Type {
     pub name: String,
     pub sub_types: Vec<String>,
 }
 pub type Types = Vec<Type>;
 
+impl WritePddl for Type {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        if self.sub_types.is_empty() {
+            return w.write_str(&self.name);
+        }
+        for sub_type in &self.sub_types {
+            write!(w, "{} ", sub_type)?;
+        }
+        write!(w, "- {}", self.name)
+    }
+}
+
+impl_display!(Type);
+
 fn parse_without_subtypes(input: &str) -> IResult<&str, Type> {
     let (remainder, name) = spaced(named)(input)?;
     Ok((
@@ -0,0 +1,358 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    domain::{parameter::Parameter, Domain},
+    problem::Problem,
+};
+
+use super::Type;
+
+/// The implicit root of every type hierarchy
+pub const OBJECT: &str = "object";
+
+/// Why `:types` do not form a tree, see [TypeHierarchy::new]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TypeError {
+    /// The type is a subtype more than once, possibly of different types
+    DeclaredTwice(String),
+    /// The type has subtypes, but is neither [OBJECT] nor a subtype itself
+    UndeclaredSupertype(String),
+    /// The type is among its own ancestors
+    Cycle(String),
+    /// [OBJECT] is declared as a subtype
+    ObjectSubtype,
+}
+
+impl TypeError {
+    /// The type the error is about
+    pub fn type_name(&self) -> &str {
+        match self {
+            TypeError::DeclaredTwice(name)
+            | TypeError::UndeclaredSupertype(name)
+            | TypeError::Cycle(name) => name,
+            TypeError::ObjectSubtype => OBJECT,
+        }
+    }
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeError::DeclaredTwice(name) => write!(f, "type '{}' is declared twice", name),
+            TypeError::UndeclaredSupertype(name) => write!(
+                f,
+                "type '{}' is used as a supertype, but is not declared",
+                name
+            ),
+            TypeError::Cycle(name) => write!(f, "type '{}' is its own supertype", name),
+            TypeError::ObjectSubtype => write!(f, "type '{}' cannot have a supertype", OBJECT),
+        }
+    }
+}
+
+/// `types` with only the first declaration of each subtype, and the subtypes whose later declarations were dropped
+///
+/// Lets validation carry on with a hierarchy for domains declaring a type twice, which planners commonly accept
+pub(crate) fn first_declarations(types: &[Type]) -> (Vec<Type>, Vec<&str>) {
+    let mut declared = HashSet::new();
+    let mut duplicates = Vec::new();
+    let types = types
+        .iter()
+        .map(|t| Type {
+            name: t.name.clone(),
+            sub_types: t
+                .sub_types
+                .iter()
+                .filter(|sub_type| match declared.insert(sub_type.as_str()) {
+                    true => true,
+                    false => {
+                        duplicates.push(sub_type.as_str());
+                        false
+                    }
+                })
+                .cloned()
+                .collect(),
+        })
+        .collect();
+    (types, duplicates)
+}
+
+/// The resolved tree of the `:types` of a domain, rooted in [OBJECT]
+///
+/// Types declared without a supertype, such as `(:types room ball)`, are subtypes of [OBJECT]
+///
+/// ## Example
+/// ```rust
+/// use spingus::domain::{parse_domain, types::hierarchy::TypeHierarchy};
+/// let domain = "(define (domain d)
+///     (:types car truck - vehicle vehicle - object)
+///     (:predicates (p))
+///     (:action a :parameters () :effect (p)))";
+/// let hierarchy = TypeHierarchy::from_domain(&parse_domain(domain).unwrap()).unwrap();
+/// assert!(hierarchy.is_subtype("car", "vehicle"));
+/// assert!(!hierarchy.is_subtype("vehicle", "car"));
+/// assert_eq!(hierarchy.ancestors("truck"), vec!["vehicle", "object"]);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TypeHierarchy {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+    parents: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+}
+
+impl TypeHierarchy {
+    /// Resolves `types`, failing on types declared twice, supertypes which are not declared, and cycles
+    pub fn new(types: &[Type]) -> Result<Self, TypeError> {
+        let mut parents: HashMap<&str, &str> = HashMap::new();
+        for t in types {
+            for sub_type in &t.sub_types {
+                if sub_type == OBJECT {
+                    return Err(TypeError::ObjectSubtype);
+                }
+                if parents.insert(sub_type, &t.name).is_some() {
+                    return Err(TypeError::DeclaredTwice(sub_type.clone()));
+                }
+            }
+        }
+
+        let mut hierarchy = TypeHierarchy {
+            names: Vec::new(),
+            indices: HashMap::new(),
+            parents: Vec::new(),
+            children: Vec::new(),
+        };
+        hierarchy.insert(OBJECT);
+        for t in types {
+            if t.name != OBJECT && !parents.contains_key(t.name.as_str()) {
+                if !t.sub_types.is_empty() {
+                    return Err(TypeError::UndeclaredSupertype(t.name.clone()));
+                }
+                parents.insert(&t.name, OBJECT);
+            }
+            hierarchy.insert(&t.name);
+            for sub_type in &t.sub_types {
+                hierarchy.insert(sub_type);
+            }
+        }
+
+        for (index, name) in hierarchy.names.iter().enumerate().skip(1) {
+            let parent = hierarchy.indices[parents[name.as_str()]];
+            hierarchy.parents[index] = Some(parent);
+            hierarchy.children[parent].push(index);
+        }
+        for index in 0..hierarchy.names.len() {
+            let mut current = index;
+            for _ in 0..hierarchy.names.len() {
+                match hierarchy.parents[current] {
+                    Some(parent) => current = parent,
+                    None => break,
+                }
+            }
+            if current != 0 {
+                return Err(TypeError::Cycle(hierarchy.names[index].clone()));
+            }
+        }
+        Ok(hierarchy)
+    }
+
+    /// The hierarchy of the types of `domain`, which is only [OBJECT] if it declares none
+    pub fn from_domain(domain: &Domain) -> Result<Self, TypeError> {
+        Self::new(domain.types.as_deref().unwrap_or_default())
+    }
+
+    fn insert(&mut self, name: &str) {
+        if !self.indices.contains_key(name) {
+            self.indices.insert(name.to_owned(), self.names.len());
+            self.names.push(name.to_owned());
+            self.parents.push(None);
+            self.children.push(Vec::new());
+        }
+    }
+
+    /// Whether `name` is declared, or is [OBJECT]
+    pub fn contains(&self, name: &str) -> bool {
+        self.indices.contains_key(name)
+    }
+
+    /// Every type, starting with [OBJECT]
+    pub fn types(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// The direct supertype of `name`, which is `None` for [OBJECT] and undeclared types
+    pub fn parent(&self, name: &str) -> Option<&str> {
+        let parent = self.parents[*self.indices.get(name)?]?;
+        Some(&self.names[parent])
+    }
+
+    /// Whether `sub_type` is `super_type`, or a descendant of it
+    ///
+    /// Every type is a subtype of [OBJECT], while undeclared types are subtypes of nothing
+    pub fn is_subtype(&self, sub_type: &str, super_type: &str) -> bool {
+        let (Some(mut current), Some(super_type)) =
+            (self.indices.get(sub_type), self.indices.get(super_type))
+        else {
+            return false;
+        };
+        loop {
+            if current == super_type {
+                return true;
+            }
+            match &self.parents[*current] {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// The supertypes of `name`, from its parent up to [OBJECT]
+    pub fn ancestors(&self, name: &str) -> Vec<&str> {
+        let mut ancestors = Vec::new();
+        let mut current = name;
+        while let Some(parent) = self.parent(current) {
+            ancestors.push(parent);
+            current = parent;
+        }
+        ancestors
+    }
+
+    /// The subtypes of `name`, direct or not, excluding `name` itself
+    pub fn descendants(&self, name: &str) -> Vec<&str> {
+        let mut descendants = Vec::new();
+        let Some(index) = self.indices.get(name) else {
+            return descendants;
+        };
+        let mut queue = self.children[*index].clone();
+        while let Some(child) = queue.pop() {
+            descendants.push(self.names[child].as_str());
+            queue.extend(&self.children[child]);
+        }
+        descendants
+    }
+
+    /// The objects of `problem` which are of type `name`, directly or through a subtype
+    ///
+    /// Untyped objects are of type [OBJECT]
+    pub fn objects_of_type<'a>(&self, problem: &Problem<'a>, name: &str) -> Vec<&'a str> {
+        self.objects_of_types(problem, &[name])
+    }
+
+    /// The objects of `problem` which are of any of the types `names`, as for `(either ...)`
+    pub fn objects_of_types<'a>(&self, problem: &Problem<'a>, names: &[&str]) -> Vec<&'a str> {
+        problem
+            .objects
+            .iter()
+            .flatten()
+            .filter(|object| {
+                let type_name = object.type_name.unwrap_or(OBJECT);
+                names.iter().any(|name| self.is_subtype(type_name, name))
+            })
+            .map(|object| object.name)
+            .collect()
+    }
+
+    /// The objects of `problem` which `parameter` can be instantiated with
+    pub fn objects_of_parameter<'a>(
+        &self,
+        problem: &Problem<'a>,
+        parameter: &Parameter,
+    ) -> Vec<&'a str> {
+        match parameter {
+            Parameter::Untyped { .. } => self.objects_of_type(problem, OBJECT),
+            Parameter::Typed { type_name, .. } => self.objects_of_type(problem, type_name),
+            Parameter::Either { type_names, .. } => self.objects_of_types(
+                problem,
+                &type_names.iter().map(String::as_str).collect::<Vec<_>>(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TypeError, TypeHierarchy};
+    use crate::{
+        domain::{parameter::Parameter, types::Type},
+        problem,
+    };
+
+    fn types(declarations: &[(&str, &[&str])]) -> Vec<Type> {
+        declarations
+            .iter()
+            .map(|(name, sub_types)| Type {
+                name: name.to_string(),
+                sub_types: sub_types.iter().map(|t| t.to_string()).collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn queries() {
+        let hierarchy = TypeHierarchy::new(&types(&[
+            ("object", &["vehicle", "place"]),
+            ("vehicle", &["truck", "plane"]),
+            ("truck", &["van"]),
+            ("depot", &[]),
+        ]))
+        .unwrap();
+        assert!(hierarchy.is_subtype("van", "vehicle"));
+        assert!(hierarchy.is_subtype("van", "van"));
+        assert!(hierarchy.is_subtype("depot", "object"));
+        assert!(!hierarchy.is_subtype("plane", "truck"));
+        assert!(!hierarchy.is_subtype("boat", "object"));
+        assert_eq!(
+            hierarchy.ancestors("van"),
+            vec!["truck", "vehicle", "object"]
+        );
+        assert_eq!(hierarchy.ancestors("object"), Vec::<&str>::new());
+        let mut descendants = hierarchy.descendants("vehicle");
+        descendants.sort();
+        assert_eq!(descendants, vec!["plane", "truck", "van"]);
+        assert_eq!(hierarchy.parent("depot"), Some("object"));
+
+        let problem = problem::parse(
+            "(define (problem p) (:objects t1 - truck v1 - van p1 - plane l1 - place x))",
+        );
+        assert_eq!(
+            hierarchy.objects_of_type(&problem, "truck"),
+            vec!["t1", "v1"]
+        );
+        assert_eq!(hierarchy.objects_of_type(&problem, "object").len(), 5);
+        let either = Parameter::Either {
+            name: "?v".to_owned(),
+            type_names: vec!["van".to_owned(), "plane".to_owned()],
+        };
+        assert_eq!(
+            hierarchy.objects_of_parameter(&problem, &either),
+            vec!["v1", "p1"]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            TypeHierarchy::new(&types(&[
+                ("a", &["c"]),
+                ("b", &["c"]),
+                ("object", &["a", "b"])
+            ])),
+            Err(TypeError::DeclaredTwice("c".to_owned()))
+        );
+        assert_eq!(
+            TypeHierarchy::new(&types(&[("vehicle", &["car"])])),
+            Err(TypeError::UndeclaredSupertype("vehicle".to_owned()))
+        );
+        assert_eq!(
+            TypeHierarchy::new(&types(&[("a", &["b"]), ("b", &["a"])])),
+            Err(TypeError::Cycle("a".to_owned()))
+        );
+        assert_eq!(
+            TypeHierarchy::new(&types(&[("a", &["object"])])),
+            Err(TypeError::ObjectSubtype)
+        );
+    }
+}
@@ -0,0 +1,660 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    ops::Range,
+};
+
+use annotate_snippets::{Annotation, AnnotationType, Renderer, Slice, Snippet, SourceAnnotation};
+
+use crate::{
+    formula::{Atom, Formula},
+    term::Term,
+    visit::{walk_effect, walk_formula, Visitor},
+};
+
+use super::{
+    action::{effect::Effect, Action},
+    parameter::Parameter,
+    types::{
+        hierarchy::{first_declarations, TypeHierarchy, OBJECT},
+        Type,
+    },
+    Domain,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// The domain is meaningless, e.g. an action uses an undeclared predicate
+    Error,
+    /// The domain is likely to be rejected by some planners, e.g. it uses a feature without requiring it
+    Warning,
+}
+
+/// The part of a domain or problem a [Diagnostic] is about
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Scope {
+    /// The whole domain
+    Domain,
+    Types,
+    Predicate(String),
+    Action(String),
+    /// The whole problem
+    Problem,
+    Objects,
+    Init,
+    Goal,
+}
+
+/// A problem found by [Domain::validate] or [crate::problem::validate::validate_problem]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub scope: Scope,
+    /// What the diagnostic points at in the source, such as a predicate, a variable or a fact like `(at a)`
+    pub subject: String,
+    pub message: String,
+    /// The code of the [crate::lint::Lint] which reported the diagnostic
+    pub code: Option<&'static str>,
+    /// The atom containing the subject, such as `(at ?x)`, and how many identical atoms come before it in the scope
+    pub atom: Option<(String, usize)>,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(severity: Severity, scope: Scope, subject: &str, message: String) -> Self {
+        Diagnostic {
+            severity,
+            scope,
+            subject: subject.to_owned(),
+            message,
+            code: None,
+            atom: None,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Characters which can be part of a name, so that searching for `?x` does not match `?xs`
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '?'
+}
+
+/// `c` lowercased as the parser does, or kept if its lowercase would take a different number of bytes
+fn lowercase(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) if l.len_utf8() == c.len_utf8() => l,
+        _ => c,
+    }
+}
+
+/// `source` lowercased as the parser does where that keeps every offset, see [lowercase], with comments blanked out
+fn searchable(source: &str) -> String {
+    let mut text = String::with_capacity(source.len());
+    let mut in_comment = false;
+    for c in source.chars() {
+        match c {
+            '\n' => in_comment = false,
+            ';' => in_comment = true,
+            _ => {}
+        }
+        match in_comment {
+            true => text.extend(std::iter::repeat_n(' ', c.len_utf8())),
+            false => text.push(lowercase(c)),
+        }
+    }
+    text
+}
+
+/// The first occurrence of `word` within `range` of `text`, which is not part of a longer name
+fn find_word(text: &str, range: Range<usize>, word: &str) -> Option<Range<usize>> {
+    let section = &text[range.clone()];
+    section
+        .match_indices(word)
+        .map(|(start, _)| range.start + start..range.start + start + word.len())
+        .find(|found| {
+            !text[..found.start].ends_with(is_name_char)
+                && !text[found.end..].starts_with(is_name_char)
+        })
+}
+
+/// The first occurrence of the parenthesised `atom`, such as `(at a b)`, within `range` of `text`, allowing any whitespace between its names
+fn find_atom(text: &str, range: Range<usize>, atom: &str) -> Option<Range<usize>> {
+    let names: Vec<&str> = atom
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split_whitespace()
+        .collect();
+    text[range.clone()]
+        .match_indices('(')
+        .map(|(start, _)| range.start + start)
+        .find_map(|start| {
+            let mut rest = &text[start + 1..range.end];
+            for name in &names {
+                rest = rest.trim_start().strip_prefix(name)?;
+                if rest.starts_with(is_name_char) {
+                    return None;
+                }
+            }
+            rest = rest.trim_start();
+            rest.starts_with(')')
+                .then(|| start..range.end - rest.len() + 1)
+        })
+}
+
+/// The parenthesised group starting with `keyword`, followed by `name` if given, such as `(:action name ...)`
+fn find_group(
+    text: &str,
+    range: Range<usize>,
+    keyword: &str,
+    name: Option<&str>,
+) -> Option<Range<usize>> {
+    let mut from = range.start;
+    while let Some(keyword_span) = find_word(text, from..range.end, keyword) {
+        from = keyword_span.end;
+        let rest = text[keyword_span.end..].trim_start();
+        let named = name.is_none_or(|name| {
+            rest.starts_with(name) && !rest[name.len()..].starts_with(is_name_char)
+        });
+        if !named {
+            continue;
+        }
+        let start = text[..keyword_span.start].rfind('(')?;
+        let mut depth = 0;
+        for (offset, c) in text[start..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 1 => return Some(start..start + offset + 1),
+                ')' => depth -= 1,
+                _ => {}
+            }
+        }
+        return Some(start..text.len());
+    }
+    None
+}
+
+impl Diagnostic {
+    /// The span of [Diagnostic::subject] in `source`, the text the domain or problem was parsed from
+    ///
+    /// The span is found by searching `source`, within [Diagnostic::atom] if set, and is `None` if it is not found, e.g. for domains built in code.
+    /// Spans are best-effort: the search is case-insensitive, but misses names with characters whose lowercase takes a different number of bytes, such as `İ`
+    pub fn span(&self, source: &str) -> Option<Range<usize>> {
+        let text = searchable(source);
+        let section = match &self.scope {
+            Scope::Domain => 0..text.len(),
+            Scope::Types => find_group(&text, 0..text.len(), ":types", None)?,
+            Scope::Predicate(name) => {
+                let predicates = find_group(&text, 0..text.len(), ":predicates", None)?;
+                find_group(&text, predicates, name, None)?
+            }
+            Scope::Action(name) => find_group(&text, 0..text.len(), ":action", Some(name))?,
+            Scope::Problem => 0..text.len(),
+            Scope::Objects => find_group(&text, 0..text.len(), ":objects", None)?,
+            Scope::Init => find_group(&text, 0..text.len(), ":init", None)?,
+            Scope::Goal => find_group(&text, 0..text.len(), ":goal", None)?,
+        };
+        let section = match &self.atom {
+            Some((atom, index)) => {
+                let atom = atom.to_lowercase();
+                let mut found = find_atom(&text, section.clone(), &atom)?;
+                for _ in 0..*index {
+                    found = find_atom(&text, found.start + 1..section.end, &atom)?;
+                }
+                found
+            }
+            None => section,
+        };
+        let subject = self.subject.to_lowercase();
+        match subject.starts_with('(') {
+            true => find_atom(&text, section, &subject),
+            false => find_word(&text, section, &subject),
+        }
+    }
+
+    /// Renders the diagnostic as an annotated snippet of `source`
+    pub fn render(&self, source: &str) -> String {
+        let annotation_type = match self.severity {
+            Severity::Error => AnnotationType::Error,
+            Severity::Warning => AnnotationType::Warning,
+        };
+        let snippet = match self.span(source) {
+            Some(span) => Snippet {
+                title: self.code.map(|code| Annotation {
+                    id: Some(code),
+                    label: Some(&self.message),
+                    annotation_type,
+                }),
+                footer: vec![],
+                slices: vec![Slice {
+                    source,
+                    line_start: 1,
+                    origin: None,
+                    annotations: vec![SourceAnnotation {
+                        range: (span.start, span.end),
+                        label: &self.message,
+                        annotation_type,
+                    }],
+                    fold: true,
+                }],
+            },
+            None => Snippet {
+                title: Some(Annotation {
+                    id: self.code,
+                    label: Some(&self.message),
+                    annotation_type,
+                }),
+                footer: vec![],
+                slices: vec![],
+            },
+        };
+        Renderer::plain().render(snippet).to_string()
+    }
+}
+
+/// Requirements implied by `:adl`
+const ADL: [&str; 7] = [
+    "strips",
+    "typing",
+    "negative-preconditions",
+    "disjunctive-preconditions",
+    "equality",
+    "quantified-preconditions",
+    "conditional-effects",
+];
+
+/// Requirements allowing numeric effects
+const NUMERIC: [&str; 3] = ["numeric-fluents", "fluents", "action-costs"];
+
+struct Validator<'a> {
+    diagnostics: Vec<Diagnostic>,
+    requirements: BTreeSet<&'a str>,
+    declared_types: BTreeSet<&'a str>,
+    constants: BTreeSet<&'a str>,
+    arities: BTreeMap<&'a str, usize>,
+}
+
+impl<'a> Validator<'a> {
+    fn report(&mut self, severity: Severity, scope: &Scope, subject: &str, message: String) {
+        self.report_in(severity, scope, subject, message, None);
+    }
+
+    /// Reports a diagnostic about `subject` within `atom`, keeping only the first of identical diagnostics in different atoms
+    fn report_in(
+        &mut self,
+        severity: Severity,
+        scope: &Scope,
+        subject: &str,
+        message: String,
+        atom: Option<(String, usize)>,
+    ) {
+        let message = match scope {
+            Scope::Predicate(name) => format!("predicate '{}': {}", name, message),
+            Scope::Action(name) => format!("action '{}': {}", name, message),
+            _ => message,
+        };
+        let diagnostic = Diagnostic {
+            atom,
+            ..Diagnostic::new(severity, scope.clone(), subject, message)
+        };
+        let reported = self.diagnostics.iter().any(|d| {
+            (d.severity, &d.scope, &d.subject, &d.message)
+                == (severity, scope, &diagnostic.subject, &diagnostic.message)
+        });
+        if !reported {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn requires(&mut self, scope: &Scope, subject: &str, requirements: &[&str]) {
+        if !requirements.iter().any(|r| self.requirements.contains(r)) {
+            let message = format!("'{}' requires :{}", subject, requirements[0]);
+            self.report(Severity::Warning, scope, subject, message);
+        }
+    }
+
+    /// Reports names declared twice, which are only a warning for the parameters of predicates as only their number matters
+    fn check_unique(
+        &mut self,
+        severity: Severity,
+        kind: &str,
+        scope: &Scope,
+        names: impl Iterator<Item = &'a str>,
+    ) {
+        let mut seen = BTreeSet::new();
+        for name in names {
+            if !seen.insert(name) {
+                let message = format!("{} '{}' is declared twice", kind, name);
+                self.report(severity, scope, name, message);
+            }
+        }
+    }
+
+    fn check_types(&mut self, scope: &Scope, parameters: &'a [Parameter]) {
+        for parameter in parameters {
+            let type_names: Vec<&String> = match parameter {
+                Parameter::Untyped { .. } => continue,
+                Parameter::Typed { type_name, .. } => vec![type_name],
+                Parameter::Either { type_names, .. } => type_names.iter().collect(),
+            };
+            for type_name in type_names {
+                if !self.declared_types.contains(type_name.as_str()) {
+                    let message = format!("type '{}' is not declared", type_name);
+                    self.report(Severity::Error, scope, type_name, message);
+                }
+            }
+        }
+    }
+
+    fn check_hierarchy(&mut self, types: &'a [Type]) {
+        let (types, duplicates) = first_declarations(types);
+        for sub_type in duplicates {
+            let message = format!("type '{}' is declared twice", sub_type);
+            self.report(Severity::Warning, &Scope::Domain, sub_type, message);
+        }
+        if let Err(err) = TypeHierarchy::new(&types) {
+            self.report(
+                Severity::Error,
+                &Scope::Domain,
+                err.type_name(),
+                err.to_string(),
+            );
+        }
+    }
+
+    fn check_action(&mut self, action: &'a Action) {
+        let scope = Scope::Action(action.name.clone());
+        self.check_unique(
+            Severity::Error,
+            "parameter",
+            &scope,
+            action.parameters.iter().map(Parameter::name),
+        );
+        self.check_types(&scope, &action.parameters);
+        let mut check = ActionCheck {
+            validator: self,
+            scope: &scope,
+            bound: action.parameters.iter().map(Parameter::name).collect(),
+            seen: BTreeMap::new(),
+        };
+        if let Some(precondition) = &action.precondition {
+            check.visit_formula(precondition);
+        }
+        check.visit_effect(&action.effect);
+    }
+}
+
+/// Checks the atoms and used features of an action
+struct ActionCheck<'v, 'a> {
+    validator: &'v mut Validator<'a>,
+    scope: &'v Scope,
+    bound: BTreeSet<&'a str>,
+    /// How many times each atom has been visited, to tell identical atoms apart in the source
+    seen: BTreeMap<String, usize>,
+}
+
+impl<'a> ActionCheck<'_, 'a> {
+    /// `term` with how many identical atoms were visited before it
+    fn occurrence(&mut self, term: &Term) -> (String, usize) {
+        let atom = term.to_string();
+        let count = self.seen.entry(atom.clone()).or_default();
+        *count += 1;
+        (atom, *count - 1)
+    }
+
+    fn arguments(&mut self, atom: &(String, usize), arguments: &[String]) {
+        for argument in arguments {
+            let message = match argument.starts_with('?') {
+                true if !self.bound.contains(argument.as_str()) => {
+                    format!("variable '{}' is not bound", argument)
+                }
+                false if !self.validator.constants.contains(argument.as_str()) => {
+                    format!("constant '{}' is not declared", argument)
+                }
+                _ => continue,
+            };
+            self.validator.report_in(
+                Severity::Error,
+                self.scope,
+                argument,
+                message,
+                Some(atom.clone()),
+            );
+        }
+    }
+}
+
+impl<'a> Visitor<'a, Term> for ActionCheck<'_, 'a> {
+    fn visit_formula(&mut self, formula: &'a Formula<Term>) {
+        let requirements: Option<(&str, &[&str])> = match formula {
+            Formula::Not(_) => Some(("not", &["negative-preconditions", "adl"])),
+            Formula::Or(_) => Some(("or", &["disjunctive-preconditions", "adl"])),
+            Formula::Imply(..) => Some(("imply", &["disjunctive-preconditions", "adl"])),
+            Formula::Forall { .. } => Some((
+                "forall",
+                &["universal-preconditions", "quantified-preconditions", "adl"],
+            )),
+            Formula::Exists { .. } => Some((
+                "exists",
+                &[
+                    "existential-preconditions",
+                    "quantified-preconditions",
+                    "adl",
+                ],
+            )),
+            Formula::Atom(_) | Formula::And(_) => None,
+        };
+        if let Some((subject, requirements)) = requirements {
+            self.validator.requires(self.scope, subject, requirements);
+        }
+        match formula {
+            Formula::Forall { parameters, .. } | Formula::Exists { parameters, .. } => {
+                self.validator.check_types(self.scope, parameters);
+                let outer = self.bound.clone();
+                self.bound.extend(parameters.iter().map(Parameter::name));
+                walk_formula(self, formula);
+                self.bound = outer;
+            }
+            _ => walk_formula(self, formula),
+        }
+    }
+
+    fn visit_effect(&mut self, effect: &'a Effect) {
+        match effect {
+            Effect::When { .. } => {
+                self.validator
+                    .requires(self.scope, "when", &["conditional-effects", "adl"]);
+                walk_effect(self, effect);
+            }
+            Effect::Forall { parameters, .. } => {
+                self.validator
+                    .requires(self.scope, "forall", &["conditional-effects", "adl"]);
+                self.validator.check_types(self.scope, parameters);
+                let outer = self.bound.clone();
+                self.bound.extend(parameters.iter().map(Parameter::name));
+                walk_effect(self, effect);
+                self.bound = outer;
+            }
+            Effect::Numeric {
+                operator, fluent, ..
+            } => {
+                self.validator
+                    .requires(self.scope, operator.keyword(), &NUMERIC);
+                let atom = self.occurrence(fluent);
+                self.arguments(&atom, &fluent.parameters);
+            }
+            _ => walk_effect(self, effect),
+        }
+    }
+
+    fn visit_atom(&mut self, term: &'a Term) {
+        let atom = self.occurrence(term);
+        if term.is_equality() {
+            self.validator
+                .requires(self.scope, &term.name, &["equality", "adl"]);
+        } else {
+            match self.validator.arities.get(term.name.as_str()) {
+                None => {
+                    let message = format!("predicate '{}' is not declared", term.name);
+                    self.validator.report_in(
+                        Severity::Error,
+                        self.scope,
+                        &term.name,
+                        message,
+                        Some(atom),
+                    );
+                    return;
+                }
+                Some(&arity) if arity != term.parameters.len() => {
+                    let message = format!(
+                        "predicate '{}' takes {} arguments, but is given {}",
+                        term.name,
+                        arity,
+                        term.parameters.len()
+                    );
+                    self.validator.report_in(
+                        Severity::Error,
+                        self.scope,
+                        &term.name,
+                        message,
+                        Some(atom),
+                    );
+                    return;
+                }
+                Some(_) => {}
+            }
+        }
+        self.arguments(&atom, &term.parameters);
+    }
+}
+
+impl Domain {
+    /// Checks that the domain is meaningful, beyond being syntactically correct
+    ///
+    /// Errors are reported for names declared twice, undeclared types, predicates and constants, predicates used with the wrong arity, unbound variables, and `:types` which do not form a tree.
+    /// Warnings are reported for features used without their requirement and types declared twice, see [crate::lint] for more.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spingus::domain::{parse_domain, validate::Severity};
+    /// let input = "(define (domain d)
+    ///     (:predicates (at ?x))
+    ///     (:action go :parameters (?x) :effect (and (at ?y))))";
+    /// let diagnostics = parse_domain(input).unwrap().validate();
+    /// assert_eq!(diagnostics[0].severity, Severity::Error);
+    /// assert_eq!(diagnostics[0].message, "action 'go': variable '?y' is not bound");
+    /// assert_eq!(diagnostics[0].span(input), Some(95..97));
+    /// ```
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut declared_types: BTreeSet<&str> = self
+            .types
+            .iter()
+            .flatten()
+            .flat_map(|t| {
+                std::iter::once(t.name.as_str()).chain(t.sub_types.iter().map(String::as_str))
+            })
+            .collect();
+        declared_types.insert(OBJECT);
+        let mut validator = Validator {
+            diagnostics: Vec::new(),
+            requirements: self
+                .requirements
+                .iter()
+                .flatten()
+                .map(String::as_str)
+                .collect(),
+            declared_types,
+            constants: self
+                .constants
+                .iter()
+                .flatten()
+                .map(Parameter::name)
+                .collect(),
+            arities: self
+                .predicates
+                .iter()
+                .map(|predicate| (predicate.name.as_str(), predicate.parameters.len()))
+                .collect(),
+        };
+        if validator.requirements.contains("adl") {
+            validator.requirements.extend(ADL);
+        }
+
+        if let Some(types) = &self.types {
+            validator.requires(&Scope::Domain, ":types", &["typing"]);
+            validator.check_hierarchy(types);
+        }
+        if let Some(constants) = &self.constants {
+            validator.check_unique(
+                Severity::Error,
+                "constant",
+                &Scope::Domain,
+                constants.iter().map(Parameter::name),
+            );
+            validator.check_types(&Scope::Domain, constants);
+        }
+        validator.check_unique(
+            Severity::Error,
+            "predicate",
+            &Scope::Domain,
+            self.predicates.iter().map(|p| p.name.as_str()),
+        );
+        validator.check_unique(
+            Severity::Error,
+            "action",
+            &Scope::Domain,
+            self.actions.iter().map(|a| a.name.as_str()),
+        );
+        for predicate in &self.predicates {
+            let scope = Scope::Predicate(predicate.name.clone());
+            validator.check_unique(
+                Severity::Warning,
+                "parameter",
+                &scope,
+                predicate.parameters.iter().map(Parameter::name),
+            );
+            validator.check_types(&scope, &predicate.parameters);
+        }
+        for action in &self.actions {
+            validator.check_action(action);
+        }
+        validator.diagnostics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_atom, find_group, find_word, searchable};
+
+    #[test]
+    fn search() {
+        let text =
+            searchable("(define ; (:action Move\n (:action MOVE :effect (p)) (:action move-all))");
+        assert!(!text.contains(';'));
+        assert_eq!(text.len(), 71);
+        let all = 0..text.len();
+        assert_eq!(
+            find_group(&text, all.clone(), ":action", Some("move")),
+            Some(25..51)
+        );
+        assert_eq!(
+            find_group(&text, all.clone(), ":action", Some("move-all")),
+            Some(52..70)
+        );
+        assert_eq!(find_group(&text, all.clone(), ":action", Some("mov")), None);
+        assert_eq!(find_atom(&text, all.clone(), "(p)"), Some(47..50));
+        assert_eq!(find_atom(&text, all, "(p q)"), None);
+
+        // Lowercasing keeps offsets, so 'İ', whose lowercase is longer, stays as it is
+        let text = searchable("(ÄB İ)");
+        assert_eq!(text, "(äb İ)");
+        assert_eq!(
+            find_word(&text, 0..text.len(), &"ÄB".to_lowercase()),
+            Some(1..4)
+        );
+    }
+}
@@ -0,0 +1,690 @@
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+    iter::Peekable,
+};
+
+use crate::{
+    shared::Result,
+    writer::{Indent, PddlWriter, WriterConfig},
+};
+
+/// How the facts of an `:init` section are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InitOrder {
+    #[default]
+    Preserve,
+    Sorted,
+}
+
+/// Options controlling the canonical layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub indent: Indent,
+    pub init_order: InitOrder,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: WriterConfig::default().indent,
+            init_order: InitOrder::default(),
+        }
+    }
+}
+
+/// Heads that are lowercased when formatting, in addition to every `:keyword`
+const KEYWORDS: [&str; 17] = [
+    "define",
+    "domain",
+    "problem",
+    "and",
+    "or",
+    "not",
+    "imply",
+    "either",
+    "forall",
+    "exists",
+    "when",
+    "increase",
+    "decrease",
+    "assign",
+    "scale-up",
+    "scale-down",
+    "preference",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+enum Node<'a> {
+    Atom(&'a str),
+    List(List<'a>),
+}
+
+#[derive(Debug, PartialEq, Eq, Default)]
+struct List<'a> {
+    elements: Vec<Element<'a>>,
+    /// Comments after the last element, before the closing parenthesis
+    dangling: Vec<&'a str>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Element<'a> {
+    leading: Vec<&'a str>,
+    node: Node<'a>,
+    trailing: Option<&'a str>,
+}
+
+impl<'a> Element<'a> {
+    fn atom(&self) -> Option<&'a str> {
+        match self.node {
+            Node::Atom(atom) => Some(atom),
+            Node::List(_) => None,
+        }
+    }
+
+    fn has_comments(&self) -> bool {
+        !self.leading.is_empty() || self.trailing.is_some()
+    }
+
+    fn is_flat(&self) -> bool {
+        !self.has_comments() && self.node.is_flat()
+    }
+}
+
+impl Node<'_> {
+    fn is_flat(&self) -> bool {
+        match self {
+            Node::Atom(_) => true,
+            Node::List(list) => list.is_flat(),
+        }
+    }
+}
+
+impl<'a> List<'a> {
+    /// Whether the list can be written on a single line
+    fn is_flat(&self) -> bool {
+        self.dangling.is_empty()
+            && !self.is_block_formula()
+            && self.elements.iter().all(Element::is_flat)
+    }
+
+    fn head(&self) -> Option<String> {
+        self.elements
+            .first()
+            .and_then(Element::atom)
+            .map(|head| head.to_lowercase())
+    }
+
+    /// Conjunctions and disjunctions with more than one child get a line per child
+    fn is_block_formula(&self) -> bool {
+        matches!(self.head().as_deref(), Some("and" | "or")) && self.elements.len() > 2
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) -> bool {
+        let mut newline = false;
+        while let Some(c) = self.input[self.pos..].chars().next() {
+            if !c.is_whitespace() {
+                break;
+            }
+            newline |= c == '\n';
+            self.pos += c.len_utf8();
+        }
+        newline
+    }
+
+    fn comment(&mut self) -> &'a str {
+        let rest = &self.input[self.pos..];
+        let end = rest.find('\n').unwrap_or(rest.len());
+        self.pos += end;
+        rest[..end].trim_end()
+    }
+
+    fn atom(&mut self) -> &'a str {
+        let rest = &self.input[self.pos..];
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ';'))
+            .unwrap_or(rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+
+    /// Parses elements until a closing parenthesis, or end of input if `top_level`
+    fn list(&mut self, top_level: bool) -> Result<List<'a>> {
+        let start = self.pos;
+        let mut list = List::default();
+        let mut leading = Vec::new();
+        let mut same_line = false;
+        loop {
+            same_line &= !self.skip_whitespace();
+            let node = match self.input[self.pos..].chars().next() {
+                None if top_level => break,
+                None => {
+                    return Err((
+                        "unclosed parenthesis".to_owned(),
+                        start.saturating_sub(1)..start,
+                    ))
+                }
+                Some(')') if top_level => {
+                    return Err((
+                        "unexpected closing parenthesis".to_owned(),
+                        self.pos..self.pos + 1,
+                    ))
+                }
+                Some(')') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(';') => {
+                    let comment = self.comment();
+                    match list.elements.last_mut() {
+                        Some(last) if same_line && last.trailing.is_none() => {
+                            last.trailing = Some(comment)
+                        }
+                        _ => leading.push(comment),
+                    }
+                    same_line = false;
+                    continue;
+                }
+                Some('(') => {
+                    self.pos += 1;
+                    Node::List(self.list(false)?)
+                }
+                Some(_) => Node::Atom(self.atom()),
+            };
+            list.elements.push(Element {
+                leading: std::mem::take(&mut leading),
+                node,
+                trailing: None,
+            });
+            same_line = true;
+        }
+        list.dangling = leading;
+        Ok(list)
+    }
+}
+
+fn normalise(atom: &str, head: bool) -> Cow<'_, str> {
+    let lower = atom.to_lowercase();
+    if atom.starts_with(':') || (head && KEYWORDS.contains(&lower.as_str())) {
+        lower.into()
+    } else {
+        atom.into()
+    }
+}
+
+struct Formatter<'o> {
+    options: &'o FormatOptions,
+}
+
+impl Formatter<'_> {
+    /// Writes each top level element on a line of its own
+    fn document(&self, w: &mut PddlWriter, document: &List) -> fmt::Result {
+        for (i, element) in document.elements.iter().enumerate() {
+            if i > 0 {
+                w.newline()?;
+            }
+            self.comments(w, &element.leading)?;
+            self.node(w, &element.node)?;
+            self.trailing(w, element)?;
+        }
+        for (i, comment) in document.dangling.iter().enumerate() {
+            if i > 0 || !document.elements.is_empty() {
+                w.newline()?;
+            }
+            w.write_str(comment)?;
+        }
+        Ok(())
+    }
+
+    fn comments(&self, w: &mut PddlWriter, comments: &[&str]) -> fmt::Result {
+        for comment in comments {
+            w.write_str(comment)?;
+            w.newline()?;
+        }
+        Ok(())
+    }
+
+    fn trailing(&self, w: &mut PddlWriter, element: &Element) -> fmt::Result {
+        match element.trailing {
+            Some(comment) => write!(w, " {}", comment),
+            None => Ok(()),
+        }
+    }
+
+    fn head(&self, w: &mut PddlWriter, head: &Element) -> fmt::Result {
+        self.comments(w, &head.leading)?;
+        match head.node {
+            Node::Atom(atom) => w.write_str(&normalise(atom, true))?,
+            ref node => self.node(w, node)?,
+        }
+        self.trailing(w, head)
+    }
+
+    /// Writes an element on a line of its own, preceded by its comments
+    fn line(&self, w: &mut PddlWriter, element: &Element) -> fmt::Result {
+        w.newline()?;
+        self.comments(w, &element.leading)?;
+        self.node(w, &element.node)?;
+        self.trailing(w, element)
+    }
+
+    /// Writes the dangling comments of a list and its closing parenthesis on a line of its own
+    fn close(&self, w: &mut PddlWriter, list: &List) -> fmt::Result {
+        w.indented(|w| {
+            for comment in &list.dangling {
+                w.newline()?;
+                w.write_str(comment)?;
+            }
+            Ok(())
+        })?;
+        w.newline()?;
+        w.write_char(')')
+    }
+
+    fn node(&self, w: &mut PddlWriter, node: &Node) -> fmt::Result {
+        match node {
+            Node::Atom(atom) => w.write_str(&normalise(atom, false)),
+            Node::List(list) => self.list(w, list),
+        }
+    }
+
+    fn list(&self, w: &mut PddlWriter, list: &List) -> fmt::Result {
+        match list.head().as_deref() {
+            Some("define") => self.define(w, list),
+            Some(":action" | ":durative-action" | ":derived") => self.structure(w, list),
+            Some(":types" | ":objects" | ":constants") => self.typed_section(w, list),
+            Some(":predicates" | ":functions" | ":init") => self.section(w, list),
+            Some(":requirements") if list.is_flat() => self.flat(w, list),
+            Some("and" | "or") if list.is_block_formula() || !list.dangling.is_empty() => {
+                self.section(w, list)
+            }
+            _ if list.is_flat() => self.flat(w, list),
+            _ => self.broken(w, list),
+        }
+    }
+
+    /// Writes a list on a single line
+    fn flat(&self, w: &mut PddlWriter, list: &List) -> fmt::Result {
+        w.write_char('(')?;
+        for (i, element) in list.elements.iter().enumerate() {
+            if i > 0 {
+                w.write_char(' ')?;
+            }
+            match &element.node {
+                Node::Atom(atom) => w.write_str(&normalise(atom, i == 0))?,
+                Node::List(list) => self.flat(w, list)?,
+            }
+        }
+        w.write_char(')')
+    }
+
+    /// Writes the elements separated by spaces, only breaking lines around comments
+    fn broken(&self, w: &mut PddlWriter, list: &List) -> fmt::Result {
+        let (head, rest) = match list.elements.split_first() {
+            Some(split) => split,
+            None => return self.close(w, list),
+        };
+        w.write_char('(')?;
+        self.head(w, head)?;
+        let mut newline = head.trailing.is_some();
+        for element in rest {
+            if newline || !element.leading.is_empty() {
+                w.indented(|w| self.line(w, element))?;
+            } else {
+                w.write_char(' ')?;
+                self.node(w, &element.node)?;
+                self.trailing(w, element)?;
+            }
+            newline = element.trailing.is_some();
+        }
+        if newline || !list.dangling.is_empty() {
+            self.close(w, list)
+        } else {
+            w.write_char(')')
+        }
+    }
+
+    /// Writes the head, followed by each later element on a line of its own
+    fn section(&self, w: &mut PddlWriter, list: &List) -> fmt::Result {
+        let (head, rest) = match list.elements.split_first() {
+            Some(split) => split,
+            None => return self.close(w, list),
+        };
+        w.write_char('(')?;
+        self.head(w, head)?;
+        let mut rest: Vec<&Element> = rest.iter().collect();
+        if list.head().as_deref() == Some(":init") && self.options.init_order == InitOrder::Sorted {
+            rest.sort_by_cached_key(|element| self.sort_key(element));
+        }
+        w.indented(|w| {
+            for element in rest {
+                self.line(w, element)?;
+            }
+            Ok(())
+        })?;
+        self.close(w, list)
+    }
+
+    fn sort_key(&self, element: &Element) -> String {
+        let mut key = String::new();
+        let config = WriterConfig::default();
+        let _ = self.node(&mut PddlWriter::new(&mut key, &config), &element.node);
+        key
+    }
+
+    /// Writes the head of a list followed by its name on the same line, unless comments are in the way
+    fn name<'e, 'a: 'e, I>(&self, w: &mut PddlWriter, elements: &mut Peekable<I>) -> fmt::Result
+    where
+        I: Iterator<Item = &'e Element<'a>>,
+    {
+        let head = match elements.next() {
+            Some(head) => head,
+            None => return Ok(()),
+        };
+        self.head(w, head)?;
+        if head.trailing.is_some() {
+            return Ok(());
+        }
+        if let Some(name) = elements.next_if(|name| name.leading.is_empty()) {
+            w.write_char(' ')?;
+            self.node(w, &name.node)?;
+            self.trailing(w, name)?;
+        }
+        Ok(())
+    }
+
+    /// Writes `(define (domain name)`, followed by each section on a line of its own
+    ///
+    /// Actions are separated by blank lines
+    fn define(&self, w: &mut PddlWriter, list: &List) -> fmt::Result {
+        let mut elements = list.elements.iter().peekable();
+        w.write_char('(')?;
+        self.name(w, &mut elements)?;
+        w.indented(|w| {
+            for element in elements {
+                if let Node::List(section) = &element.node {
+                    if matches!(
+                        section.head().as_deref(),
+                        Some(":action" | ":durative-action" | ":derived")
+                    ) {
+                        w.blank_line()?;
+                    }
+                }
+                self.line(w, element)?;
+            }
+            Ok(())
+        })?;
+        self.close(w, list)
+    }
+
+    /// Writes `(:action name`, followed by each `:keyword value` pair on a line of its own
+    ///
+    /// Parameters of several types get one line per type, as in [Formatter::typed_section]
+    fn structure(&self, w: &mut PddlWriter, list: &List) -> fmt::Result {
+        let mut elements = list.elements.iter().peekable();
+        w.write_char('(')?;
+        self.name(w, &mut elements)?;
+        w.indented(|w| {
+            while let Some(key) = elements.next() {
+                self.line(w, key)?;
+                let is_key = key.atom().is_some_and(|atom| atom.starts_with(':'));
+                if !is_key || key.trailing.is_some() {
+                    continue;
+                }
+                if let Some(value) = elements.next_if(|value| value.leading.is_empty()) {
+                    w.write_char(' ')?;
+                    match &value.node {
+                        Node::List(parameters)
+                            if key
+                                .atom()
+                                .is_some_and(|atom| atom.eq_ignore_ascii_case(":parameters"))
+                                && typed_groups(&parameters.elements).len() > 1 =>
+                        {
+                            w.write_char('(')?;
+                            self.typed_lines(w, &parameters.elements)?;
+                            self.close(w, parameters)?;
+                        }
+                        node => self.node(w, node)?,
+                    }
+                    self.trailing(w, value)?;
+                }
+            }
+            Ok(())
+        })?;
+        self.close(w, list)
+    }
+
+    /// Writes a section of typed names with one line per type, aligning the type separators
+    fn typed_section(&self, w: &mut PddlWriter, list: &List) -> fmt::Result {
+        let (head, rest) = match list.elements.split_first() {
+            Some(split) => split,
+            None => return self.close(w, list),
+        };
+        w.write_char('(')?;
+        self.head(w, head)?;
+        self.typed_lines(w, rest)?;
+        self.close(w, list)
+    }
+
+    /// Writes typed names indented with one line per type, aligning the type separators
+    fn typed_lines(&self, w: &mut PddlWriter, elements: &[Element]) -> fmt::Result {
+        let groups = typed_groups(elements);
+        let width = groups
+            .iter()
+            .filter(|group| group.type_name.is_some())
+            .filter_map(TypedGroup::width)
+            .max()
+            .unwrap_or(0);
+        w.indented(|w| {
+            for group in &groups {
+                w.newline()?;
+                let mut newline = false;
+                for (i, name) in group.names.iter().enumerate() {
+                    if i > 0 {
+                        match newline || !name.leading.is_empty() {
+                            true => w.newline()?,
+                            false => w.write_char(' ')?,
+                        }
+                    }
+                    self.comments(w, &name.leading)?;
+                    self.node(w, &name.node)?;
+                    self.trailing(w, name)?;
+                    newline = name.trailing.is_some();
+                }
+                if let Some((separator, type_name)) = group.type_name {
+                    match newline || separator.has_comments() || !type_name.leading.is_empty() {
+                        true => w.newline()?,
+                        false => {
+                            for _ in group.width().unwrap_or(width)..width {
+                                w.write_char(' ')?;
+                            }
+                            w.write_char(' ')?;
+                        }
+                    }
+                    self.comments(w, &separator.leading)?;
+                    w.write_str("- ")?;
+                    self.trailing(w, separator)?;
+                    if separator.trailing.is_some() {
+                        w.newline()?;
+                    }
+                    self.comments(w, &type_name.leading)?;
+                    self.node(w, &type_name.node)?;
+                    self.trailing(w, type_name)?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Names sharing a type in a typed list, such as `a b - type`
+struct TypedGroup<'e, 'a> {
+    names: Vec<&'e Element<'a>>,
+    /// The separator `-` and the type
+    type_name: Option<(&'e Element<'a>, &'e Element<'a>)>,
+}
+
+impl TypedGroup<'_, '_> {
+    /// Width of the names when written on one line, if they can be
+    fn width(&self) -> Option<usize> {
+        let mut width = self.names.len().saturating_sub(1);
+        for name in &self.names {
+            if name.has_comments() {
+                return None;
+            }
+            width += name.atom()?.len();
+        }
+        Some(width)
+    }
+}
+
+fn typed_groups<'e, 'a>(elements: &'e [Element<'a>]) -> Vec<TypedGroup<'e, 'a>> {
+    let mut groups = Vec::new();
+    let mut names = Vec::new();
+    let mut elements = elements.iter();
+    while let Some(element) = elements.next() {
+        if element.atom() != Some("-") {
+            names.push(element);
+            continue;
+        }
+        let type_name = match elements.next() {
+            Some(type_name) => type_name,
+            None => {
+                // A dangling separator is kept as a name, so nothing is lost
+                names.push(element);
+                break;
+            }
+        };
+        groups.push(TypedGroup {
+            names: std::mem::take(&mut names),
+            type_name: Some((element, type_name)),
+        });
+    }
+    if !names.is_empty() {
+        groups.push(TypedGroup {
+            names,
+            type_name: None,
+        });
+    }
+    groups
+}
+
+/// Formats PDDL into its canonical layout
+///
+/// Comments are kept, keywords are lowercased, sections and actions are put on lines of their own and typed lists of sections and of parameters of several types get one line per type, with aligned type separators.
+/// Anything that is not PDDL, such as a plan, is formatted as a sequence of s-expressions.
+///
+/// ## Example
+/// ```rust
+/// use spingus::format::{format, FormatOptions};
+/// let input = "(DEFINE (PROBLEM p) (:DOMAIN d) ; comment
+///     (:OBJECTS a b - t c - long-type) (:INIT (p a)) (:GOAL (AND (p a) (p b))))";
+/// assert_eq!(
+///     format(input, &FormatOptions::default()),
+///     Ok("(define (problem p)
+///     (:domain d) ; comment
+///     (:objects
+///         a b - t
+///         c   - long-type
+///     )
+///     (:init
+///         (p a)
+///     )
+///     (:goal (and
+///         (p a)
+///         (p b)
+///     ))
+/// )
+/// "
+///     .to_owned())
+/// );
+/// ```
+pub fn format(input: &str, options: &FormatOptions) -> Result<String> {
+    let document = Parser { input, pos: 0 }.list(true)?;
+    let formatter = Formatter { options };
+    let config = WriterConfig {
+        indent: options.indent,
+    };
+    let mut out = String::new();
+    formatter
+        .document(&mut PddlWriter::new(&mut out, &config), &document)
+        .expect("writing to a string cannot fail");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Checks whether PDDL is already in its canonical layout
+pub fn check(input: &str, options: &FormatOptions) -> Result<bool> {
+    Ok(format(input, options)? == input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check, format, FormatOptions, InitOrder};
+    use crate::writer::Indent;
+
+    use rstest::*;
+
+    #[rstest]
+    #[case("", "")]
+    #[case("(a b)(c)", "(a b)\n(c)\n")]
+    #[case("; only a comment", "; only a comment\n")]
+    #[case("(NOT (P ?a))", "(not (P ?a))\n")]
+    #[case("(and (a) ; trailing\n(b))", "(and\n    (a) ; trailing\n    (b)\n)\n")]
+    #[case("(and (a)\n; dangling\n)", "(and\n    (a)\n    ; dangling\n)\n")]
+    #[case(
+        "(and (a)\n; leading\n(b))",
+        "(and\n    (a)\n    ; leading\n    (b)\n)\n"
+    )]
+    #[case("(not ; why\n(a))", "(not ; why\n    (a))\n")]
+    #[case(
+        "(:action A :PARAMETERS (?x   ?y - t) :effect (p ?x))",
+        "(:action A\n    :parameters (?x ?y - t)\n    :effect (p ?x)\n)\n"
+    )]
+    #[case(
+        "(:action a :parameters (?x ?yy - t ?z - u) :effect (p ?x))",
+        "(:action a\n    :parameters (\n        ?x ?yy - t\n        ?z     - u\n    )\n    :effect (p ?x)\n)\n"
+    )]
+    #[case(
+        "(:types a bb - t ccc - (either x y) ; types\nd)",
+        "(:types\n    a bb - t\n    ccc  - (either x y) ; types\n    d\n)\n"
+    )]
+    fn format_layout(#[case] input: &str, #[case] expected: &str) {
+        let options = FormatOptions::default();
+        assert_eq!(format(input, &options), Ok(expected.to_owned()));
+        assert_eq!(check(expected, &options), Ok(true));
+    }
+
+    #[test]
+    fn format_options() {
+        let input = "(:init (b) ; b\n(a) (c))";
+        let options = FormatOptions {
+            indent: Indent::Tabs,
+            init_order: InitOrder::Sorted,
+        };
+        assert_eq!(
+            format(input, &options),
+            Ok("(:init\n\t(a)\n\t(b) ; b\n\t(c)\n)\n".to_owned())
+        );
+        assert_eq!(check(input, &options), Ok(false));
+    }
+
+    #[rstest]
+    #[case("(a", "unclosed parenthesis", 0..1)]
+    #[case("(a))", "unexpected closing parenthesis", 3..4)]
+    fn format_error(#[case] input: &str, #[case] msg: &str, #[case] span: std::ops::Range<usize>) {
+        assert_eq!(
+            format(input, &FormatOptions::default()),
+            Err((msg.to_owned(), span))
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt::{self, Display, Write};
+
+use crate::{
+    domain::parameter::{write_parameters, Parameter},
+    writer::{PddlWriter, WritePddl, WriterConfig},
+};
+
+/// Normal forms of formulas, such as negation normal form and disjunctive normal form
+///
+/// The deprecated `StringExpression` and `Goal` types convert to and from [Formula] to use them
+pub mod normal;
+
+/// The predicate of equality atoms, such as `(= ?a ?b)`
+pub const EQUALITY: &str = "=";
+
+/// The atoms of a [Formula], a predicate applied to arguments
+///
+/// Implemented by [crate::term::Term] for domain formulas and by [crate::problem::init::Fact] for problem goals
+pub trait Atom {
+    type Arg;
+
+    fn predicate(&self) -> &str;
+
+    fn arguments(&self) -> &[Self::Arg];
+
+    /// Whether the atom is an equality, which is built in rather than a declared predicate
+    fn is_equality(&self) -> bool {
+        self.predicate() == EQUALITY
+    }
+}
+
+/// A logical formula over atoms of type `A`
+///
+/// Action preconditions and the conditions of their effects are `Formula<Term>` and problem goals are `Formula<Fact>`, so code evaluating either only has to be written once
+/// Equalities are atoms with the predicate [EQUALITY]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Formula<A> {
+    Atom(A),
+    And(Vec<Formula<A>>),
+    Or(Vec<Formula<A>>),
+    Not(Box<Formula<A>>),
+    Imply(Box<Formula<A>>, Box<Formula<A>>),
+    /// Holds if `formula` holds for every instantiation of `parameters`, written `(forall (parameters) formula)`
+    Forall {
+        parameters: Vec<Parameter>,
+        formula: Box<Formula<A>>,
+    },
+    /// Holds if `formula` holds for some instantiation of `parameters`, written `(exists (parameters) formula)`
+    Exists {
+        parameters: Vec<Parameter>,
+        formula: Box<Formula<A>>,
+    },
+}
+
+impl<A> Formula<A> {
+    /// Converts every atom with `atom`, keeping the structure of the formula
+    pub fn map<B, F: FnMut(A) -> B>(self, atom: &mut F) -> Formula<B> {
+        match self {
+            Formula::Atom(a) => Formula::Atom(atom(a)),
+            Formula::And(children) => {
+                Formula::And(children.into_iter().map(|child| child.map(atom)).collect())
+            }
+            Formula::Or(children) => {
+                Formula::Or(children.into_iter().map(|child| child.map(atom)).collect())
+            }
+            Formula::Not(child) => Formula::Not(Box::new(child.map(atom))),
+            Formula::Imply(antecedent, consequent) => Formula::Imply(
+                Box::new(antecedent.map(atom)),
+                Box::new(consequent.map(atom)),
+            ),
+            Formula::Forall {
+                parameters,
+                formula,
+            } => Formula::Forall {
+                parameters,
+                formula: Box::new(formula.map(atom)),
+            },
+            Formula::Exists {
+                parameters,
+                formula,
+            } => Formula::Exists {
+                parameters,
+                formula: Box::new(formula.map(atom)),
+            },
+        }
+    }
+
+    /// Converts every atom by reference with `atom`, keeping the structure of the formula
+    pub fn map_ref<'a, B, F: FnMut(&'a A) -> B>(&'a self, atom: &mut F) -> Formula<B> {
+        match self {
+            Formula::Atom(a) => Formula::Atom(atom(a)),
+            Formula::And(children) => {
+                Formula::And(children.iter().map(|child| child.map_ref(atom)).collect())
+            }
+            Formula::Or(children) => {
+                Formula::Or(children.iter().map(|child| child.map_ref(atom)).collect())
+            }
+            Formula::Not(child) => Formula::Not(Box::new(child.map_ref(atom))),
+            Formula::Imply(antecedent, consequent) => Formula::Imply(
+                Box::new(antecedent.map_ref(atom)),
+                Box::new(consequent.map_ref(atom)),
+            ),
+            Formula::Forall {
+                parameters,
+                formula,
+            } => Formula::Forall {
+                parameters: parameters.clone(),
+                formula: Box::new(formula.map_ref(atom)),
+            },
+            Formula::Exists {
+                parameters,
+                formula,
+            } => Formula::Exists {
+                parameters: parameters.clone(),
+                formula: Box::new(formula.map_ref(atom)),
+            },
+        }
+    }
+
+    /// Whether the formula holds when each atom is true exactly if `atom` returns `true` for it
+    ///
+    /// Quantified formulas cannot be evaluated without the objects they range over, so formulas containing them give `None`
+    pub fn evaluate<F: FnMut(&A) -> bool>(&self, atom: &mut F) -> Option<bool> {
+        let mut children = |children: &[Formula<A>]| -> Option<Vec<bool>> {
+            children.iter().map(|child| child.evaluate(atom)).collect()
+        };
+        Some(match self {
+            Formula::Atom(a) => atom(a),
+            Formula::And(c) => children(c)?.into_iter().all(|holds| holds),
+            Formula::Or(c) => children(c)?.into_iter().any(|holds| holds),
+            Formula::Not(child) => !child.evaluate(atom)?,
+            Formula::Imply(antecedent, consequent) => {
+                !antecedent.evaluate(atom)? || consequent.evaluate(atom)?
+            }
+            Formula::Forall { .. } | Formula::Exists { .. } => return None,
+        })
+    }
+}
+
+impl<A: WritePddl> WritePddl for Formula<A> {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        match self {
+            Formula::Atom(atom) => atom.write_pddl(w),
+            Formula::And(children) => w.block("and", children),
+            Formula::Or(children) => w.block("or", children),
+            Formula::Not(child) => {
+                w.write_str("(not ")?;
+                child.write_pddl(w)?;
+                w.write_char(')')
+            }
+            Formula::Imply(antecedent, consequent) => {
+                w.write_str("(imply ")?;
+                antecedent.write_pddl(w)?;
+                w.write_char(' ')?;
+                consequent.write_pddl(w)?;
+                w.write_char(')')
+            }
+            Formula::Forall {
+                parameters,
+                formula,
+            } => write_quantified(w, "forall", parameters, formula),
+            Formula::Exists {
+                parameters,
+                formula,
+            } => write_quantified(w, "exists", parameters, formula),
+        }
+    }
+}
+
+fn write_quantified<A: WritePddl>(
+    w: &mut PddlWriter,
+    quantifier: &str,
+    parameters: &[Parameter],
+    formula: &Formula<A>,
+) -> fmt::Result {
+    write!(w, "({} (", quantifier)?;
+    write_parameters(w, parameters)?;
+    w.write_str(") ")?;
+    formula.write_pddl(w)?;
+    w.write_char(')')
+}
+
+impl<A: WritePddl> Display for Formula<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let config = WriterConfig::default();
+        self.write_pddl(&mut PddlWriter::new(f, &config))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Formula;
+    use crate::{problem::init::Fact, term::Term};
+
+    #[test]
+    fn map() {
+        let term = |name: &str, parameters: &[&str]| {
+            Formula::Atom(Term {
+                name: name.to_owned(),
+                parameters: parameters.iter().map(|p| p.to_string()).collect(),
+            })
+        };
+        let formula = Formula::And(vec![
+            term("p", &["a"]),
+            Formula::Not(Box::new(term("=", &["a", "b"]))),
+        ]);
+        assert_eq!(formula.to_string(), "(and\n    (p a)\n    (not (= a b))\n)");
+        let names = ["a", "b", "=", "p"];
+        let mapped: Formula<Fact> = formula.map(&mut |term| Fact {
+            predicate: names.iter().find(|n| **n == term.name).unwrap(),
+            objects: term
+                .parameters
+                .iter()
+                .map(|p| *names.iter().find(|n| *n == p).unwrap())
+                .collect(),
+        });
+        assert_eq!(mapped.to_string(), "(and\n    (p a)\n    (not (= a b))\n)");
+    }
+}
@@ -0,0 +1,251 @@
+use super::Formula;
+
+impl<A> Formula<A> {
+    /// Replaces every `(imply a b)` by `(or (not a) b)`
+    pub fn eliminate_implications(self) -> Formula<A> {
+        match self {
+            Formula::Atom(atom) => Formula::Atom(atom),
+            Formula::And(children) => Formula::And(
+                children
+                    .into_iter()
+                    .map(Formula::eliminate_implications)
+                    .collect(),
+            ),
+            Formula::Or(children) => Formula::Or(
+                children
+                    .into_iter()
+                    .map(Formula::eliminate_implications)
+                    .collect(),
+            ),
+            Formula::Not(child) => Formula::Not(Box::new(child.eliminate_implications())),
+            Formula::Imply(antecedent, consequent) => Formula::Or(vec![
+                Formula::Not(Box::new(antecedent.eliminate_implications())),
+                consequent.eliminate_implications(),
+            ]),
+            Formula::Forall {
+                parameters,
+                formula,
+            } => Formula::Forall {
+                parameters,
+                formula: Box::new(formula.eliminate_implications()),
+            },
+            Formula::Exists {
+                parameters,
+                formula,
+            } => Formula::Exists {
+                parameters,
+                formula: Box::new(formula.eliminate_implications()),
+            },
+        }
+    }
+
+    /// The negation normal form of the formula, where `not` is only applied to atoms and there are no implications
+    ///
+    /// Negated quantifiers become the other quantifier, so that `(not (forall (?x) a))` becomes `(exists (?x) (not a))`
+    ///
+    /// Nested conjunctions and disjunctions are flattened, so that `(and a (and b c))` becomes `(and a b c)`
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spingus::{formula::Formula, problem};
+    /// let problem = problem::parse("(define (problem p) (:goal (not (or (a) (not (b))))))");
+    /// let nnf = problem.goal.unwrap().to_nnf();
+    /// assert_eq!(nnf.to_string(), "(and\n    (not (a))\n    (b)\n)");
+    /// ```
+    pub fn to_nnf(self) -> Formula<A> {
+        self.nnf(true)
+    }
+
+    fn nnf(self, positive: bool) -> Formula<A> {
+        let flatten = |children: Vec<Formula<A>>, conjunction: bool| {
+            let mut flat = Vec::new();
+            for child in children {
+                match (child.nnf(positive), conjunction) {
+                    (Formula::And(grandchildren), true) | (Formula::Or(grandchildren), false) => {
+                        flat.extend(grandchildren)
+                    }
+                    (child, _) => flat.push(child),
+                }
+            }
+            flat
+        };
+        match self {
+            Formula::Atom(atom) => match positive {
+                true => Formula::Atom(atom),
+                false => Formula::Not(Box::new(Formula::Atom(atom))),
+            },
+            Formula::Not(child) => child.nnf(!positive),
+            Formula::And(children) => match positive {
+                true => Formula::And(flatten(children, true)),
+                false => Formula::Or(flatten(children, false)),
+            },
+            Formula::Or(children) => match positive {
+                true => Formula::Or(flatten(children, false)),
+                false => Formula::And(flatten(children, true)),
+            },
+            Formula::Imply(antecedent, consequent) => {
+                Formula::Or(vec![Formula::Not(antecedent), *consequent]).nnf(positive)
+            }
+            Formula::Forall {
+                parameters,
+                formula,
+            } => {
+                let formula = Box::new(formula.nnf(positive));
+                match positive {
+                    true => Formula::Forall {
+                        parameters,
+                        formula,
+                    },
+                    false => Formula::Exists {
+                        parameters,
+                        formula,
+                    },
+                }
+            }
+            Formula::Exists {
+                parameters,
+                formula,
+            } => {
+                let formula = Box::new(formula.nnf(positive));
+                match positive {
+                    true => Formula::Exists {
+                        parameters,
+                        formula,
+                    },
+                    false => Formula::Forall {
+                        parameters,
+                        formula,
+                    },
+                }
+            }
+        }
+    }
+}
+
+impl<A: Clone> Formula<A> {
+    /// The clauses of the disjunctive normal form of the formula, each a conjunction of literals, i.e. atoms and negated atoms
+    ///
+    /// There are no clauses for an unsatisfiable formula such as `(or)`, and a single empty clause for a valid one such as `(and)`.
+    /// Fails if there would be more than `max_clauses` clauses, as the disjunctive normal form can be exponentially larger than the formula,
+    /// and for quantified formulas, which need objects to be expanded over first
+    pub fn dnf_clauses(self, max_clauses: usize) -> Result<Vec<Vec<Formula<A>>>, String> {
+        fn clauses<A: Clone>(
+            formula: Formula<A>,
+            max_clauses: usize,
+        ) -> Result<Vec<Vec<Formula<A>>>, String> {
+            match formula {
+                Formula::And(children) => {
+                    let mut product = vec![Vec::new()];
+                    for child in children {
+                        let child = clauses(child, max_clauses)?;
+                        if product.len() * child.len() > max_clauses {
+                            return Err(too_large(max_clauses));
+                        }
+                        product = product
+                            .iter()
+                            .flat_map(|clause| {
+                                child.iter().map(move |literals| {
+                                    let mut clause: Vec<Formula<A>> = clause.clone();
+                                    clause.extend(literals.iter().cloned());
+                                    clause
+                                })
+                            })
+                            .collect();
+                    }
+                    Ok(product)
+                }
+                Formula::Or(children) => {
+                    let mut sum = Vec::new();
+                    for child in children {
+                        sum.extend(clauses(child, max_clauses)?);
+                        if sum.len() > max_clauses {
+                            return Err(too_large(max_clauses));
+                        }
+                    }
+                    Ok(sum)
+                }
+                Formula::Forall { .. } | Formula::Exists { .. } => Err(
+                    "quantified formulas have no disjunctive normal form without objects"
+                        .to_owned(),
+                ),
+                literal => Ok(vec![vec![literal]]),
+            }
+        }
+        clauses(self.to_nnf(), max_clauses)
+    }
+
+    /// The disjunctive normal form of the formula, a disjunction of conjunctions of literals, see [Formula::dnf_clauses]
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spingus::problem;
+    /// let problem = problem::parse("(define (problem p) (:goal (and (or (a) (b)) (c))))");
+    /// let dnf = problem.goal.unwrap().to_dnf(16).unwrap();
+    /// assert_eq!(
+    ///     dnf.to_string(),
+    ///     "(or\n    (and\n        (a)\n        (c)\n    )\n    (and\n        (b)\n        (c)\n    )\n)"
+    /// );
+    /// ```
+    pub fn to_dnf(self, max_clauses: usize) -> Result<Formula<A>, String> {
+        let clauses = self.dnf_clauses(max_clauses)?;
+        Ok(Formula::Or(clauses.into_iter().map(Formula::And).collect()))
+    }
+}
+
+fn too_large(max_clauses: usize) -> String {
+    format!(
+        "the disjunctive normal form has more than {} clauses",
+        max_clauses
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{formula::Formula, term::Term};
+
+    fn atom(name: &str) -> Formula<Term> {
+        Formula::Atom(Term {
+            name: name.to_owned(),
+            parameters: vec![],
+        })
+    }
+
+    fn not(formula: Formula<Term>) -> Formula<Term> {
+        Formula::Not(Box::new(formula))
+    }
+
+    #[test]
+    fn normal_forms() {
+        let imply = Formula::Imply(Box::new(atom("a")), Box::new(atom("b")));
+        assert_eq!(
+            imply.clone().eliminate_implications(),
+            Formula::Or(vec![not(atom("a")), atom("b")])
+        );
+        assert_eq!(
+            not(imply.clone()).to_nnf(),
+            Formula::And(vec![atom("a"), not(atom("b"))])
+        );
+        assert_eq!(not(not(atom("a"))).to_nnf(), atom("a"));
+        assert_eq!(
+            Formula::And(vec![atom("a"), Formula::And(vec![atom("b"), atom("c")])]).to_nnf(),
+            Formula::And(vec![atom("a"), atom("b"), atom("c")])
+        );
+
+        let formula = Formula::And(vec![imply, Formula::Or(vec![atom("c"), atom("d")])]);
+        assert_eq!(
+            formula.clone().dnf_clauses(4),
+            Ok(vec![
+                vec![not(atom("a")), atom("c")],
+                vec![not(atom("a")), atom("d")],
+                vec![atom("b"), atom("c")],
+                vec![atom("b"), atom("d")],
+            ])
+        );
+        assert!(formula.dnf_clauses(3).is_err());
+        assert_eq!(Formula::<Term>::Or(vec![]).dnf_clauses(1), Ok(vec![]));
+        assert_eq!(
+            Formula::<Term>::And(vec![]).dnf_clauses(1),
+            Ok(vec![vec![]])
+        );
+    }
+}
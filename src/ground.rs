@@ -0,0 +1,1014 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use smallvec::{smallvec, SmallVec};
+
+use crate::{
+    datalog::{self, Arg, Program, Rule},
+    domain::{
+        action::effect::Effect,
+        parameter::Parameter,
+        types::hierarchy::{first_declarations, TypeHierarchy, OBJECT},
+        Domain,
+    },
+    formula::{Formula, EQUALITY},
+    problem::Problem,
+    symbol::{
+        ActionId, AtomId, Id, IdAction, IdArg, IdDomain, IdFact, IdParameter, IdProblem, IdTerm,
+        ObjId, PredId, SymbolTable, VarId,
+    },
+    visit::{walk_effect, Visitor},
+};
+
+/// An instantiation of an action schema, with its literals as IDs of atoms of the [GroundTask]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct GroundAction {
+    pub action: ActionId,
+    /// The objects the parameters of the schema are instantiated with, in order
+    pub arguments: SmallVec<[ObjId; 4]>,
+    pub precondition: Vec<AtomId>,
+    /// The atoms which must be false, for negated preconditions
+    pub negative_precondition: Vec<AtomId>,
+    pub add: Vec<AtomId>,
+    /// The atoms made false, excluding those which are also added
+    pub del: Vec<AtomId>,
+}
+
+/// A planning task where every action is ground, and every state is a set of atoms
+///
+/// Only atoms which actions can change are kept: literals of static predicates, see [crate::domain::predicate::PredicateKind], are evaluated in the initial state while grounding, and actions whose static preconditions do not hold are dropped.
+/// Only reachable actions and atoms are kept as well, those found by exploring the delete relaxation of the task from the initial state, where actions never delete atoms and negated preconditions are ignored.
+/// Goals which are unreachable even then are reported in [GroundTask::unreachable_goal].
+///
+/// Grounding supports preconditions and goals which are conjunctions of literals, and effects which are conjunctions of literals and universally quantified effects.
+/// Conditional and numeric effects are rejected.
+///
+/// ## Example
+/// ```rust
+/// use spingus::{domain::parse_domain, ground::GroundTask, problem};
+/// let domain = parse_domain("(define (domain d)
+///     (:types room)
+///     (:predicates (at ?r - room) (door ?from ?to - room))
+///     (:action go :parameters (?from ?to - room)
+///         :precondition (and (at ?from) (door ?from ?to))
+///         :effect (and (not (at ?from)) (at ?to))))").unwrap();
+/// let problem = problem::parse("(define (problem p)
+///     (:objects a b c - room)
+///     (:init (at a) (door a b) (door b c))
+///     (:goal (at c)))");
+/// let task = GroundTask::new(&domain, &problem).unwrap();
+/// assert_eq!(task.actions.len(), 2);
+/// assert_eq!(task.action_name(&task.actions[0]), "(go a b)");
+/// assert_eq!(task.atom_name(task.actions[0].add[0]), "(at b)");
+/// assert_eq!(task.init.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GroundTask {
+    pub symbols: SymbolTable,
+    /// Every atom, indexed by [AtomId]
+    pub atoms: Vec<IdFact>,
+    atom_ids: HashMap<IdFact, AtomId>,
+    pub actions: Vec<GroundAction>,
+    pub init: BTreeSet<AtomId>,
+    pub goal: BTreeSet<AtomId>,
+    /// The atoms which must be false in a goal state
+    pub negative_goal: BTreeSet<AtomId>,
+    /// The facts of goal literals which can never hold, so that the task is unsolvable if there are any
+    ///
+    /// These are facts which are never true for positive literals, and static facts which are true for negated literals
+    pub unreachable_goal: Vec<IdFact>,
+}
+
+impl GroundTask {
+    /// Grounds every action of `domain` over the objects of `problem` and the constants of `domain`
+    pub fn new(domain: &Domain, problem: &Problem) -> Result<Self, String> {
+        Self::with_strategy(domain, problem, Strategy::default())
+    }
+
+    /// Grounds like [GroundTask::new], exploring the delete relaxation with `strategy`
+    ///
+    /// Every strategy gives the same task
+    pub fn with_strategy(
+        domain: &Domain,
+        problem: &Problem,
+        strategy: Strategy,
+    ) -> Result<Self, String> {
+        let mut symbols = SymbolTable::default();
+        let id_domain = symbols.intern_domain(domain);
+        let problem = symbols.intern_problem(problem);
+        Grounder::new(domain, &problem, symbols)?.ground(domain, &id_domain, &problem, strategy)
+    }
+
+    /// The ID of `fact`, if it is an atom of the task
+    pub fn atom(&self, fact: &IdFact) -> Option<AtomId> {
+        self.atom_ids.get(fact).copied()
+    }
+
+    /// The atom written as PDDL, such as `(at b)`
+    pub fn atom_name(&self, atom: AtomId) -> String {
+        self.symbols.fact(&self.atoms[atom.index()]).to_string()
+    }
+
+    /// The action written as a plan step, such as `(go a b)`
+    pub fn action_name(&self, action: &GroundAction) -> String {
+        let mut name = format!("({}", self.symbols.actions.name(action.action));
+        for argument in &action.arguments {
+            name.push(' ');
+            name.push_str(self.symbols.objects.name(*argument));
+        }
+        name.push(')');
+        name
+    }
+
+    fn intern_atom(&mut self, fact: IdFact) -> AtomId {
+        if let Some(atom) = self.atom_ids.get(&fact) {
+            return *atom;
+        }
+        let atom = AtomId::new(self.atoms.len());
+        self.atoms.push(fact.clone());
+        self.atom_ids.insert(fact, atom);
+        atom
+    }
+}
+
+/// A literal of a precondition or goal, `true` when positive
+type Literal<'a, A> = (bool, &'a A);
+
+fn literals<'a, A>(
+    formula: &'a Formula<A>,
+    positive: bool,
+    out: &mut Vec<Literal<'a, A>>,
+) -> Result<(), ()> {
+    match formula {
+        Formula::Atom(atom) => out.push((positive, atom)),
+        Formula::Not(child) => literals(child, !positive, out)?,
+        Formula::And(children) if positive => {
+            for child in children {
+                literals(child, positive, out)?;
+            }
+        }
+        _ => return Err(()),
+    }
+    Ok(())
+}
+
+/// How [GroundTask::with_strategy] explores the delete relaxation
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Strategy {
+    /// Instantiates every schema again in each round, until no new atom is reached
+    Naive,
+    /// Compiles the schemas into a [crate::datalog::Program], which only joins new facts in each round
+    #[default]
+    Datalog,
+}
+
+/// A precondition literal of an action schema, checked as soon as its variables are bound
+struct Check<'a> {
+    positive: bool,
+    term: &'a IdTerm,
+    /// Whether the predicate is static, otherwise only positive literals are checked, against the reached atoms
+    is_static: bool,
+}
+
+/// An action schema prepared for grounding
+struct Schema<'a> {
+    action: &'a IdAction,
+    /// The objects each parameter can be instantiated with
+    candidates: Vec<Vec<ObjId>>,
+    /// The checks of each depth, i.e. number of bound parameters
+    checks: Vec<Vec<Check<'a>>>,
+}
+
+/// The state of compiling the effects of a schema into Datalog rules, which adds a rule deriving each added atom from the instantiations of the schema
+struct EffectRules<'g> {
+    grounder: &'g Grounder,
+    program: &'g mut Program,
+    index: usize,
+    /// The body of the rules, deriving the instantiations of the schema and its quantified variables
+    body: Vec<datalog::Atom>,
+    variables: HashMap<VarId, u32>,
+    next_variable: u32,
+    foralls: usize,
+    /// The first error, after which the remaining effects are skipped
+    result: Result<(), String>,
+}
+
+/// The facts added and deleted by an effect under a binding, where the effect is supported as by [check_effect]
+struct Instantiation<'g> {
+    grounder: &'g Grounder,
+    binding: &'g mut Vec<(VarId, ObjId)>,
+    adds: Vec<IdFact>,
+    dels: Vec<IdFact>,
+    /// The first error, after which the remaining effects are skipped
+    result: Result<(), String>,
+}
+
+/// The objects of a domain and problem, with the state of grounding
+struct Grounder {
+    task: GroundTask,
+    hierarchy: TypeHierarchy,
+    /// Every object with its type names, which are empty when untyped
+    objects: Vec<(ObjId, Vec<String>)>,
+    /// The declared predicates which actions change, any other predicate is static
+    fluents: HashSet<PredId>,
+    /// The facts of the initial state over static predicates
+    static_facts: HashSet<IdFact>,
+    /// The facts over fluent predicates which are true in the initial state, or added by a reachable action
+    reached: HashSet<IdFact>,
+    /// The reachable instantiations of schemas, by index of schema, in order of discovery
+    instances: Vec<(usize, SmallVec<[ObjId; 4]>)>,
+    known: HashSet<(usize, SmallVec<[ObjId; 4]>)>,
+    equality: PredId,
+}
+
+/// Checks that every atom of `domain` and fact of `problem` has as many arguments as its declared predicate
+fn check_arities(
+    domain: &Domain,
+    problem: &IdProblem,
+    symbols: &SymbolTable,
+) -> Result<(), String> {
+    let arities: HashMap<&str, usize> = domain
+        .predicates
+        .iter()
+        .map(|predicate| (predicate.name.as_str(), predicate.parameters.len()))
+        .collect();
+    let check = |name: &str, arity: usize| match arities.get(name) {
+        Some(expected) if *expected != arity => Err(format!(
+            "predicate '{}' takes {} arguments, but is given {}",
+            name, expected, arity
+        )),
+        _ => Ok(()),
+    };
+    for action in &domain.actions {
+        let (adds, deletes) = action.effect.possible_literals();
+        action
+            .precondition
+            .iter()
+            .flat_map(|precondition| precondition.atoms())
+            .chain(adds)
+            .chain(deletes)
+            .try_for_each(|term| check(&term.name, term.parameters.len()))
+            .map_err(|message| format!("action '{}': {}", action.name, message))?;
+    }
+    let mut facts = Facts(problem.init.iter().collect());
+    problem
+        .goal
+        .iter()
+        .for_each(|goal| facts.visit_formula(goal));
+    facts
+        .0
+        .into_iter()
+        .try_for_each(|fact| check(symbols.predicates.name(fact.predicate), fact.args.len()))
+}
+
+/// Collects the facts of a formula
+struct Facts<'f>(Vec<&'f IdFact>);
+
+impl<'f> Visitor<'f, IdFact> for Facts<'f> {
+    fn visit_atom(&mut self, fact: &'f IdFact) {
+        self.0.push(fact);
+    }
+}
+
+/// Finds the first effect which grounding does not support
+#[derive(Default)]
+struct Unsupported(Option<&'static str>);
+
+impl<'ast> Visitor<'ast, IdTerm, IdParameter> for Unsupported {
+    fn visit_effect(&mut self, effect: &'ast Effect<IdTerm, IdParameter>) {
+        let message = match effect {
+            Effect::When { .. } => "conditional effects are not supported",
+            Effect::Numeric { .. } => "numeric effects are not supported",
+            _ => return walk_effect(self, effect),
+        };
+        self.0.get_or_insert(message);
+    }
+}
+
+/// Whether grounding supports `effect`
+fn check_effect(effect: &Effect<IdTerm, IdParameter>) -> Result<(), String> {
+    let mut unsupported = Unsupported::default();
+    unsupported.visit_effect(effect);
+    unsupported
+        .0
+        .map_or(Ok(()), |message| Err(message.to_owned()))
+}
+
+impl EffectRules<'_> {
+    /// Adds a rule deriving `term` from the instantiations of the schema
+    fn add(&mut self, term: &IdTerm) -> Result<(), String> {
+        for arg in &term.args {
+            if let IdArg::Variable(variable) = arg {
+                if !self.variables.contains_key(variable) {
+                    return Err(self.grounder.unbound(*variable));
+                }
+            }
+        }
+        let head = self
+            .grounder
+            .datalog_atom(self.program, term, &self.variables);
+        self.program.add_rule(Rule {
+            head,
+            body: self.body.clone(),
+            negative: Vec::new(),
+        })
+    }
+
+    /// Binds `parameters` to new variables, restricted in the body to the objects they can be instantiated with
+    fn quantify(&mut self, parameters: &[IdParameter]) -> Result<(), String> {
+        for parameter in parameters {
+            let variable = self.next_variable;
+            self.next_variable += 1;
+            self.variables.insert(parameter.variable, variable);
+            let relation = self
+                .program
+                .relation(&format!("forall {} {}", self.index, self.foralls));
+            self.foralls += 1;
+            for object in self.grounder.candidates(parameter) {
+                self.program.add_fact(datalog::Fact {
+                    relation,
+                    args: smallvec![object],
+                })?;
+            }
+            self.body.push(datalog::Atom {
+                relation,
+                args: smallvec![Arg::Variable(variable)],
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<'ast> Visitor<'ast, IdTerm, IdParameter> for EffectRules<'_> {
+    fn visit_effect(&mut self, effect: &'ast Effect<IdTerm, IdParameter>) {
+        if self.result.is_err() {
+            return;
+        }
+        match effect {
+            Effect::Add(term) => self.result = self.add(term),
+            Effect::And(_) => walk_effect(self, effect),
+            Effect::Forall { parameters, .. } => {
+                let (variables, body) = (self.variables.clone(), self.body.len());
+                self.result = self.quantify(parameters);
+                walk_effect(self, effect);
+                self.variables = variables;
+                self.body.truncate(body);
+            }
+            Effect::Delete(_) | Effect::When { .. } | Effect::Numeric { .. } => {}
+        }
+    }
+}
+
+impl Instantiation<'_> {
+    /// Applies `effect` for every instantiation of `parameters`
+    fn forall(&mut self, parameters: &[IdParameter], effect: &Effect<IdTerm, IdParameter>) {
+        let Some((parameter, rest)) = parameters.split_first() else {
+            return self.visit_effect(effect);
+        };
+        for object in self.grounder.candidates(parameter) {
+            self.binding.push((parameter.variable, object));
+            self.forall(rest, effect);
+            self.binding.pop();
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast, IdTerm, IdParameter> for Instantiation<'_> {
+    fn visit_effect(&mut self, effect: &'ast Effect<IdTerm, IdParameter>) {
+        if self.result.is_err() {
+            return;
+        }
+        let (facts, term) = match effect {
+            Effect::Add(term) => (&mut self.adds, term),
+            Effect::Delete(term) => (&mut self.dels, term),
+            Effect::And(_) => return walk_effect(self, effect),
+            Effect::Forall { parameters, effect } => return self.forall(parameters, effect),
+            Effect::When { .. } | Effect::Numeric { .. } => return,
+        };
+        match self.grounder.instantiate(term, self.binding) {
+            Ok(fact) => facts.push(fact),
+            Err(message) => self.result = Err(message),
+        }
+    }
+}
+
+impl Grounder {
+    fn new(domain: &Domain, problem: &IdProblem, mut symbols: SymbolTable) -> Result<Self, String> {
+        check_arities(domain, problem, &symbols)?;
+        let (types, _) = first_declarations(domain.types.as_deref().unwrap_or_default());
+        let hierarchy = TypeHierarchy::new(&types).map_err(|err| err.to_string())?;
+        let equality = symbols.predicates.intern(EQUALITY);
+
+        let mut objects: Vec<(ObjId, Vec<String>)> = Vec::new();
+        let mut add_object = |object: ObjId, types: Vec<String>| match objects
+            .iter_mut()
+            .find(|(known, _)| *known == object)
+        {
+            Some((_, known_types)) => known_types.extend(types),
+            None => objects.push((object, types)),
+        };
+        for constant in domain.constants.iter().flatten() {
+            let types = match constant {
+                Parameter::Untyped { .. } => vec![],
+                Parameter::Typed { type_name, .. } => vec![type_name.clone()],
+                Parameter::Either { type_names, .. } => type_names.clone(),
+            };
+            add_object(symbols.objects.intern(constant.name()), types);
+        }
+        for object in &problem.objects {
+            let types = object
+                .types
+                .iter()
+                .map(|type_id| symbols.types.name(*type_id).to_owned())
+                .collect();
+            add_object(object.object, types);
+        }
+
+        let fluents = domain
+            .predicate_kinds()
+            .into_iter()
+            .filter(|(_, kind)| !kind.is_static())
+            .map(|(name, _)| symbols.predicates.intern(name))
+            .collect();
+
+        Ok(Grounder {
+            task: GroundTask {
+                symbols,
+                atoms: Vec::new(),
+                atom_ids: HashMap::new(),
+                actions: Vec::new(),
+                init: BTreeSet::new(),
+                goal: BTreeSet::new(),
+                negative_goal: BTreeSet::new(),
+                unreachable_goal: Vec::new(),
+            },
+            hierarchy,
+            objects,
+            fluents,
+            static_facts: HashSet::new(),
+            reached: HashSet::new(),
+            instances: Vec::new(),
+            known: HashSet::new(),
+            equality,
+        })
+    }
+
+    fn ground(
+        mut self,
+        domain: &Domain,
+        id_domain: &IdDomain,
+        problem: &IdProblem,
+        strategy: Strategy,
+    ) -> Result<GroundTask, String> {
+        for fact in problem.init.iter().cloned() {
+            match self.fluents.contains(&fact.predicate) {
+                true => {
+                    let atom = self.task.intern_atom(fact.clone());
+                    self.task.init.insert(atom);
+                    self.reached.insert(fact);
+                }
+                false => {
+                    self.static_facts.insert(fact);
+                }
+            }
+        }
+
+        let mut schemas = Vec::new();
+        for (action, named) in id_domain.actions.iter().zip(&domain.actions) {
+            let schema = self
+                .schema(action)
+                .map_err(|message| format!("action '{}': {}", named.name, message))?;
+            schemas.push(schema);
+        }
+
+        match strategy {
+            Strategy::Naive => self.explore(&schemas)?,
+            Strategy::Datalog => self.explore_datalog(&schemas)?,
+        }
+        self.instances.sort();
+        for (index, arguments) in std::mem::take(&mut self.instances) {
+            self.emit(&schemas[index], arguments)?;
+        }
+
+        if let Some(goal) = &problem.goal {
+            let mut goal_literals = Vec::new();
+            literals(goal, true, &mut goal_literals)
+                .map_err(|_| "only conjunctions of literals are supported as goals".to_owned())?;
+            for (positive, fact) in goal_literals {
+                let holds = match self.fluents.contains(&fact.predicate) {
+                    true if positive => self.reached.contains(fact),
+                    true => true,
+                    false => self.holds(fact) == positive,
+                };
+                match (holds, self.task.atom(fact)) {
+                    (false, _) => self.task.unreachable_goal.push(fact.clone()),
+                    (true, Some(atom)) if positive => {
+                        self.task.goal.insert(atom);
+                    }
+                    (true, Some(atom)) => {
+                        self.task.negative_goal.insert(atom);
+                    }
+                    (true, None) => {}
+                }
+            }
+        }
+        Ok(self.task)
+    }
+
+    /// Whether the static `fact` holds in the initial state
+    fn holds(&self, fact: &IdFact) -> bool {
+        match fact.predicate == self.equality {
+            true => fact.args.windows(2).all(|pair| pair[0] == pair[1]),
+            false => self.static_facts.contains(fact),
+        }
+    }
+
+    /// The objects which can instantiate `parameter`
+    fn candidates(&self, parameter: &IdParameter) -> Vec<ObjId> {
+        let expected: Vec<&str> = parameter
+            .types
+            .iter()
+            .map(|t| self.task.symbols.types.name(*t))
+            .collect();
+        if expected.is_empty() {
+            return self.objects.iter().map(|(object, _)| *object).collect();
+        }
+        self.objects
+            .iter()
+            .filter(|(_, types)| {
+                let is_of = |t: &str| expected.iter().any(|e| self.hierarchy.is_subtype(t, e));
+                match types.is_empty() {
+                    true => is_of(OBJECT),
+                    false => types.iter().any(|t| is_of(t)),
+                }
+            })
+            .map(|(object, _)| *object)
+            .collect()
+    }
+
+    fn schema<'a>(&self, action: &'a IdAction) -> Result<Schema<'a>, String> {
+        check_effect(&action.effect)?;
+        let mut precondition = Vec::new();
+        if let Some(formula) = &action.precondition {
+            literals(formula, true, &mut precondition).map_err(|_| {
+                "only conjunctions of literals are supported as preconditions".to_owned()
+            })?;
+        }
+
+        let depths: HashMap<VarId, usize> = action
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(index, parameter)| (parameter.variable, index + 1))
+            .collect();
+        let mut checks: Vec<Vec<Check>> =
+            (0..=action.parameters.len()).map(|_| Vec::new()).collect();
+        for (positive, term) in precondition {
+            let mut depth = 0;
+            for arg in &term.args {
+                if let IdArg::Variable(variable) = arg {
+                    let variable_depth = depths
+                        .get(variable)
+                        .ok_or_else(|| self.unbound(*variable))?;
+                    depth = depth.max(*variable_depth);
+                }
+            }
+            checks[depth].push(Check {
+                positive,
+                term,
+                is_static: !self.fluents.contains(&term.predicate),
+            });
+        }
+
+        Ok(Schema {
+            action,
+            candidates: action
+                .parameters
+                .iter()
+                .map(|parameter| self.candidates(parameter))
+                .collect(),
+            checks,
+        })
+    }
+
+    fn unbound(&self, variable: VarId) -> String {
+        format!(
+            "variable '{}' is not bound",
+            self.task.symbols.variables.name(variable)
+        )
+    }
+
+    fn instantiate(&self, term: &IdTerm, binding: &[(VarId, ObjId)]) -> Result<IdFact, String> {
+        let args = term
+            .args
+            .iter()
+            .map(|arg| match arg {
+                IdArg::Object(object) => Ok(*object),
+                IdArg::Variable(variable) => binding
+                    .iter()
+                    .rev()
+                    .find(|(bound, _)| bound == variable)
+                    .map(|(_, object)| *object)
+                    .ok_or_else(|| self.unbound(*variable)),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(IdFact {
+            predicate: term.predicate,
+            args,
+        })
+    }
+
+    /// Explores the delete relaxation until no schema has new reachable instantiations
+    fn explore(&mut self, schemas: &[Schema]) -> Result<(), String> {
+        loop {
+            let reached = self.reached.len();
+            for (index, schema) in schemas.iter().enumerate() {
+                let mut binding = Vec::with_capacity(schema.candidates.len());
+                self.assign(index, schema, &mut binding)?;
+            }
+            if self.reached.len() == reached {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Explores the delete relaxation with a Datalog program, deriving the instantiations of each schema and the atoms they add
+    fn explore_datalog(&mut self, schemas: &[Schema]) -> Result<(), String> {
+        let mut program = Program::default();
+        for fact in self.static_facts.iter().chain(&self.reached) {
+            let relation = program.relation(self.task.symbols.predicates.name(fact.predicate));
+            program.add_fact(datalog::Fact {
+                relation,
+                args: fact.args.clone(),
+            })?;
+        }
+        let equality = program.relation(EQUALITY);
+        for (object, _) in &self.objects {
+            program.add_fact(datalog::Fact {
+                relation: equality,
+                args: smallvec![*object, *object],
+            })?;
+        }
+
+        let mut applicable = Vec::new();
+        for (index, schema) in schemas.iter().enumerate() {
+            let mut variables: HashMap<VarId, u32> = HashMap::new();
+            let mut body = Vec::new();
+            for (position, (parameter, candidates)) in schema
+                .action
+                .parameters
+                .iter()
+                .zip(&schema.candidates)
+                .enumerate()
+            {
+                variables.insert(parameter.variable, position as u32);
+                let relation = program.relation(&format!("parameter {} {}", index, position));
+                for object in candidates {
+                    program.add_fact(datalog::Fact {
+                        relation,
+                        args: smallvec![*object],
+                    })?;
+                }
+                body.push(datalog::Atom {
+                    relation,
+                    args: smallvec![Arg::Variable(position as u32)],
+                });
+            }
+            let mut negative = Vec::new();
+            for check in schema.checks.iter().flatten() {
+                let atom = self.datalog_atom(&mut program, check.term, &variables);
+                match (check.positive, check.is_static) {
+                    (true, _) => body.push(atom),
+                    (false, true) => negative.push(atom),
+                    (false, false) => {}
+                }
+            }
+            let head = datalog::Atom {
+                relation: program.relation(&format!("applicable {}", index)),
+                args: (0..schema.candidates.len() as u32)
+                    .map(Arg::Variable)
+                    .collect(),
+            };
+            applicable.push(head.relation);
+            program.add_rule(Rule {
+                head: head.clone(),
+                body,
+                negative,
+            })?;
+            let mut effects = EffectRules {
+                grounder: self,
+                program: &mut program,
+                index,
+                body: vec![head],
+                variables,
+                next_variable: schema.candidates.len() as u32,
+                foralls: 0,
+                result: Ok(()),
+            };
+            effects.visit_effect(&schema.action.effect);
+            effects.result?;
+        }
+
+        let model = program.evaluate()?;
+        for (index, relation) in applicable.into_iter().enumerate() {
+            for tuple in model.tuples(relation) {
+                self.instances.push((index, tuple.clone()));
+            }
+        }
+        for predicate in &self.fluents {
+            let name = self.task.symbols.predicates.name(*predicate);
+            if let Some(relation) = program.relations.get(name) {
+                for tuple in model.tuples(relation) {
+                    self.reached.insert(IdFact {
+                        predicate: *predicate,
+                        args: tuple.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn datalog_atom(
+        &self,
+        program: &mut Program,
+        term: &IdTerm,
+        variables: &HashMap<VarId, u32>,
+    ) -> datalog::Atom {
+        datalog::Atom {
+            relation: program.relation(self.task.symbols.predicates.name(term.predicate)),
+            args: term
+                .args
+                .iter()
+                .map(|arg| match arg {
+                    IdArg::Variable(variable) => Arg::Variable(variables[variable]),
+                    IdArg::Object(object) => Arg::Constant(*object),
+                })
+                .collect(),
+        }
+    }
+
+    /// Finds the instantiations of `schema` extending `binding` which are applicable in the delete relaxation
+    fn assign(
+        &mut self,
+        index: usize,
+        schema: &Schema,
+        binding: &mut Vec<(VarId, ObjId)>,
+    ) -> Result<(), String> {
+        let depth = binding.len();
+        for check in &schema.checks[depth] {
+            let fact = self.instantiate(check.term, binding)?;
+            let holds = match check.is_static {
+                true => self.holds(&fact) == check.positive,
+                false => !check.positive || self.reached.contains(&fact),
+            };
+            if !holds {
+                return Ok(());
+            }
+        }
+        if depth == schema.candidates.len() {
+            let arguments: SmallVec<[ObjId; 4]> =
+                binding.iter().map(|(_, object)| *object).collect();
+            if self.known.insert((index, arguments.clone())) {
+                let (adds, _) = self.effect(&schema.action.effect, binding)?;
+                self.reached.extend(adds);
+                self.instances.push((index, arguments));
+            }
+            return Ok(());
+        }
+        let variable = schema.action.parameters[depth].variable;
+        for object in &schema.candidates[depth] {
+            binding.push((variable, *object));
+            self.assign(index, schema, binding)?;
+            binding.pop();
+        }
+        Ok(())
+    }
+
+    /// Adds the ground action of `schema` instantiated with `arguments`, keeping only reachable atoms
+    fn emit(&mut self, schema: &Schema, arguments: SmallVec<[ObjId; 4]>) -> Result<(), String> {
+        let mut binding: Vec<(VarId, ObjId)> = schema
+            .action
+            .parameters
+            .iter()
+            .map(|parameter| parameter.variable)
+            .zip(arguments.iter().copied())
+            .collect();
+        let mut action = GroundAction {
+            action: schema.action.action,
+            arguments,
+            precondition: Vec::new(),
+            negative_precondition: Vec::new(),
+            add: Vec::new(),
+            del: Vec::new(),
+        };
+        for check in schema.checks.iter().flatten() {
+            if check.is_static {
+                continue;
+            }
+            let fact = self.instantiate(check.term, &binding)?;
+            // An unreachable atom is never true, so its negation always holds
+            if !self.reached.contains(&fact) {
+                continue;
+            }
+            let atom = self.task.intern_atom(fact);
+            match check.positive {
+                true => action.precondition.push(atom),
+                false => action.negative_precondition.push(atom),
+            }
+        }
+        let (adds, dels) = self.effect(&schema.action.effect, &mut binding)?;
+        for fact in adds {
+            action.add.push(self.task.intern_atom(fact));
+        }
+        for fact in dels {
+            if self.reached.contains(&fact) {
+                action.del.push(self.task.intern_atom(fact));
+            }
+        }
+        for atoms in [
+            &mut action.precondition,
+            &mut action.negative_precondition,
+            &mut action.add,
+            &mut action.del,
+        ] {
+            atoms.sort();
+            atoms.dedup();
+        }
+        let add = &action.add;
+        action.del.retain(|atom| add.binary_search(atom).is_err());
+        self.task.actions.push(action);
+        Ok(())
+    }
+
+    /// The facts added and deleted by `effect` under `binding`, which is supported as by [check_effect]
+    fn effect(
+        &self,
+        effect: &Effect<IdTerm, IdParameter>,
+        binding: &mut Vec<(VarId, ObjId)>,
+    ) -> Result<(Vec<IdFact>, Vec<IdFact>), String> {
+        let mut instantiation = Instantiation {
+            grounder: self,
+            binding,
+            adds: Vec::new(),
+            dels: Vec::new(),
+            result: Ok(()),
+        };
+        instantiation.visit_effect(effect);
+        instantiation.result?;
+        Ok((instantiation.adds, instantiation.dels))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GroundTask, Strategy};
+    use crate::{domain::parse_domain, problem};
+
+    fn names(task: &GroundTask, atoms: &[crate::symbol::AtomId]) -> Vec<String> {
+        atoms.iter().map(|atom| task.atom_name(*atom)).collect()
+    }
+
+    #[test]
+    fn ground() {
+        let domain = parse_domain(
+            "(define (domain d)
+                (:types block - object)
+                (:constants table - object)
+                (:predicates (on ?x ?y) (clear ?x) (small ?x) (marked ?x))
+                (:action move
+                    :parameters (?b - block ?from ?to)
+                    :precondition (and (on ?b ?from) (clear ?to) (small ?b)
+                        (not (= ?from ?to)) (not (marked ?to)))
+                    :effect (and (not (on ?b ?from)) (on ?b ?to) (clear ?from) (not (clear ?to))))
+                (:action mark
+                    :parameters ()
+                    :effect (forall (?b - block) (marked ?b))))",
+        )
+        .unwrap();
+        let problem = problem::parse(
+            "(define (problem p)
+                (:objects A b - block)
+                (:init (on a table) (on b table) (clear a) (clear b) (small a))
+                (:goal (and (on a b) (small a) (not (marked b)))))",
+        );
+        let task = GroundTask::new(&domain, &problem).unwrap();
+        let steps: Vec<String> = task
+            .actions
+            .iter()
+            .map(|action| task.action_name(action))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                "(move a table a)",
+                "(move a table b)",
+                "(move a a table)",
+                "(move a a b)",
+                "(move a b table)",
+                "(move a b a)",
+                "(mark)"
+            ]
+        );
+        let move_a = &task.actions[1];
+        assert_eq!(
+            names(&task, &move_a.precondition),
+            vec!["(on a table)", "(clear b)"]
+        );
+        assert_eq!(
+            names(&task, &move_a.negative_precondition),
+            vec!["(marked b)"]
+        );
+        assert_eq!(names(&task, &move_a.add), vec!["(clear table)", "(on a b)"]);
+        assert_eq!(names(&task, &move_a.del), vec!["(on a table)", "(clear b)"]);
+        assert_eq!(
+            names(&task, &task.actions[6].add),
+            vec!["(marked a)", "(marked b)"]
+        );
+        assert_eq!(task.init.len(), 4);
+        assert_eq!(task.goal.len(), 1);
+        assert_eq!(task.negative_goal.len(), 1);
+
+        let conditional = parse_domain(
+            "(define (domain d)
+                (:predicates (p))
+                (:action a :parameters () :effect (when (p) (not (p)))))",
+        )
+        .unwrap();
+        assert_eq!(
+            GroundTask::new(&conditional, &problem).unwrap_err(),
+            "action 'a': conditional effects are not supported"
+        );
+    }
+
+    #[test]
+    fn arities() {
+        let domain = parse_domain(
+            "(define (domain d)
+                (:predicates (at ?x ?y))
+                (:action a :parameters (?x ?y) :precondition (at ?x ?y) :effect (at ?y)))",
+        )
+        .unwrap();
+        let problem =
+            problem::parse("(define (problem p) (:objects a) (:init (at a)) (:goal (at a a)))");
+        for strategy in [Strategy::Naive, Strategy::Datalog] {
+            assert_eq!(
+                GroundTask::with_strategy(&domain, &problem, strategy).unwrap_err(),
+                "action 'a': predicate 'at' takes 2 arguments, but is given 1"
+            );
+        }
+        let domain = parse_domain(
+            "(define (domain d)
+                (:predicates (at ?x ?y))
+                (:action a :parameters (?x ?y) :precondition (at ?x ?y) :effect (at ?y ?x)))",
+        )
+        .unwrap();
+        assert_eq!(
+            GroundTask::new(&domain, &problem).unwrap_err(),
+            "predicate 'at' takes 2 arguments, but is given 1"
+        );
+    }
+
+    #[test]
+    fn reachability() {
+        let domain = parse_domain(
+            "(define (domain d)
+                (:predicates (at ?x) (road ?x ?y) (visited ?x))
+                (:action go
+                    :parameters (?from ?to)
+                    :precondition (and (at ?from) (road ?from ?to) (not (visited ?to)))
+                    :effect (and (not (at ?from)) (at ?to) (visited ?to))))",
+        )
+        .unwrap();
+        let problem = problem::parse(
+            "(define (problem p)
+                (:objects a b c d)
+                (:init (at a) (road a b) (road b a) (road c d))
+                (:goal (and (at b) (at d) (not (road a b)))))",
+        );
+        let task = GroundTask::new(&domain, &problem).unwrap();
+        let steps: Vec<String> = task
+            .actions
+            .iter()
+            .map(|action| task.action_name(action))
+            .collect();
+        assert_eq!(steps, vec!["(go a b)", "(go b a)"]);
+        let atoms: Vec<String> = (0..task.atoms.len())
+            .map(|atom| task.atom_name(crate::symbol::AtomId(atom as u32)))
+            .collect();
+        assert_eq!(
+            atoms,
+            vec!["(at a)", "(visited b)", "(at b)", "(visited a)"]
+        );
+        assert_eq!(
+            names(&task, &task.actions[0].negative_precondition),
+            vec!["(visited b)"]
+        );
+        let unreachable: Vec<String> = task
+            .unreachable_goal
+            .iter()
+            .map(|fact| task.symbols.fact(fact).to_string())
+            .collect();
+        assert_eq!(unreachable, vec!["(at d)", "(road a b)"]);
+        assert_eq!(task.goal.len(), 1);
+    }
+}
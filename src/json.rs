@@ -0,0 +1,861 @@
+//! The format is versioned by a top level `"version"`, currently [VERSION], and a `"kind"` of either `"domain"`, `"problem"` or `"plan"`.
+//! Fields that are optional in PDDL, such as `:requirements` or `:objects`, are `null` or missing when absent.
+//! A JSON Schema of the format is found in `schema/spingus-v1.schema.json`.
+//!
+//! ## Domain
+//! ```json
+//! {
+//!   "version": 1,
+//!   "kind": "domain",
+//!   "name": "gripper",
+//!   "requirements": ["strips", "typing"],
+//!   "types": [{ "name": "object", "subtypes": ["room", "ball"] }],
+//!   "constants": [{ "name": "left", "type": "gripper" }],
+//!   "predicates": [{ "name": "at", "parameters": [{ "name": "?b" }, { "name": "?r", "type": "room" }] }],
+//!   "actions": [
+//!     {
+//!       "name": "move",
+//!       "parameters": [{ "name": "?from", "either": ["room", "hall"] }],
+//!       "precondition": { "atom": { "predicate": "at-robby", "args": ["?from"] } },
+//!       "effect": { "and": [{ "not": { "atom": { "predicate": "at-robby", "args": ["?from"] } } }] }
+//!     }
+//!   ]
+//! }
+//! ```
+//! A parameter, or constant, is untyped without `"type"` and `"either"`, and has at most one of them.
+//!
+//! ## Formulas
+//! Preconditions and goals are objects with a single key naming the connective
+//! + `{ "atom": { "predicate": "p", "args": ["?x", "o1"] } }`
+//! + `{ "equal": ["?x", "?y"] }`
+//! + `{ "and": [formula, ...] }` and `{ "or": [formula, ...] }`
+//! + `{ "not": formula }`
+//! + `{ "imply": [antecedent, consequent] }`
+//! + `{ "forall": { "parameters": [parameter, ...], "formula": formula } }` and the same for `"exists"`
+//!
+//! Goals can only contain atoms, `and`, `or`, `not`, `forall` and `exists`.
+//!
+//! ## Effects
+//! Effects are objects with a single key as well
+//! + `{ "atom": atom }` and `{ "not": { "atom": atom } }`, adding and deleting the atom
+//! + `{ "and": [effect, ...] }`
+//! + `{ "when": { "condition": formula, "effect": effect } }`
+//! + `{ "forall": { "parameters": [parameter, ...], "effect": effect } }`
+//! + `{ "increase": { "fluent": atom, "value": expression } }`, and likewise for `assign`, `decrease`, `scale-up` and `scale-down`
+//!
+//! where an expression is `{ "number": "1.5" }`, `{ "fluent": atom }`, `{ "negate": expression }` or `{ "+": [left, right] }`, and likewise for `-`, `*` and `/`.
+//!
+//! ## Problem
+//! ```json
+//! {
+//!   "version": 1,
+//!   "kind": "problem",
+//!   "name": "gripper-1",
+//!   "domain": "gripper",
+//!   "objects": [{ "name": "rooma", "type": "room" }, { "name": "ball1" }],
+//!   "init": [{ "predicate": "at-robby", "args": ["rooma"] }],
+//!   "goal": { "atom": { "predicate": "at", "args": ["ball1", "roomb"] } }
+//! }
+//! ```
+//!
+//! ## Plan
+//! ```json
+//! { "version": 1, "kind": "plan", "steps": [{ "action": "move", "args": ["rooma", "roomb"] }] }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::{
+        action::{
+            effect::{AssignOperator, BinaryOperator, Effect, NumericExpression},
+            Action,
+        },
+        parameter::Parameter,
+        predicate::Predicate,
+        types::Type,
+        Domain,
+    },
+    formula::{Atom, Formula, EQUALITY},
+    plan::Plan,
+    problem::{init::Fact, objects::Object, Problem},
+    term::Term,
+};
+
+/// The version of the format written, and the only one read
+pub const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    Domain,
+    Problem,
+    Plan,
+}
+
+/// Only reads the header, so that the version and kind can be checked before the rest
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+    kind: Kind,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonDomain {
+    version: u32,
+    kind: Kind,
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    requirements: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    types: Option<Vec<JsonType>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    constants: Option<Vec<JsonParameter>>,
+    predicates: Vec<JsonPredicate>,
+    actions: Vec<JsonAction>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonType {
+    name: String,
+    #[serde(default)]
+    subtypes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonParameter {
+    name: String,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    type_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    either: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonPredicate {
+    name: String,
+    #[serde(default)]
+    parameters: Vec<JsonParameter>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonAction {
+    name: String,
+    #[serde(default)]
+    parameters: Vec<JsonParameter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    precondition: Option<JsonFormula<String>>,
+    effect: JsonEffect,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum JsonEffect {
+    Atom(JsonAtom<String>),
+    Not(Box<JsonEffect>),
+    And(Vec<JsonEffect>),
+    When {
+        condition: JsonFormula<String>,
+        effect: Box<JsonEffect>,
+    },
+    Forall {
+        parameters: Vec<JsonParameter>,
+        effect: Box<JsonEffect>,
+    },
+    Assign(JsonNumericEffect),
+    Increase(JsonNumericEffect),
+    Decrease(JsonNumericEffect),
+    ScaleUp(JsonNumericEffect),
+    ScaleDown(JsonNumericEffect),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonNumericEffect {
+    fluent: JsonAtom<String>,
+    value: JsonNumeric,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum JsonNumeric {
+    Number(String),
+    Fluent(JsonAtom<String>),
+    #[serde(rename = "+")]
+    Add(Box<JsonNumeric>, Box<JsonNumeric>),
+    #[serde(rename = "-")]
+    Subtract(Box<JsonNumeric>, Box<JsonNumeric>),
+    #[serde(rename = "*")]
+    Multiply(Box<JsonNumeric>, Box<JsonNumeric>),
+    #[serde(rename = "/")]
+    Divide(Box<JsonNumeric>, Box<JsonNumeric>),
+    Negate(Box<JsonNumeric>),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonAtom<S> {
+    predicate: S,
+    #[serde(default = "Vec::new")]
+    args: Vec<S>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum JsonFormula<S> {
+    Atom(JsonAtom<S>),
+    Equal(Vec<S>),
+    And(Vec<JsonFormula<S>>),
+    Or(Vec<JsonFormula<S>>),
+    Not(Box<JsonFormula<S>>),
+    Imply(Box<JsonFormula<S>>, Box<JsonFormula<S>>),
+    Forall {
+        parameters: Vec<JsonParameter>,
+        formula: Box<JsonFormula<S>>,
+    },
+    Exists {
+        parameters: Vec<JsonParameter>,
+        formula: Box<JsonFormula<S>>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonProblem<'a> {
+    version: u32,
+    kind: Kind,
+    #[serde(default, borrow, skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(default, borrow, skip_serializing_if = "Option::is_none")]
+    domain: Option<&'a str>,
+    #[serde(default, borrow, skip_serializing_if = "Option::is_none")]
+    objects: Option<Vec<JsonObject<'a>>>,
+    #[serde(default, borrow, skip_serializing_if = "Option::is_none")]
+    init: Option<Vec<JsonAtom<&'a str>>>,
+    #[serde(default, borrow, skip_serializing_if = "Option::is_none")]
+    goal: Option<JsonFormula<&'a str>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonObject<'a> {
+    name: &'a str,
+    #[serde(
+        default,
+        borrow,
+        rename = "type",
+        skip_serializing_if = "Option::is_none"
+    )]
+    type_name: Option<&'a str>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonPlan<'a> {
+    version: u32,
+    kind: Kind,
+    #[serde(borrow)]
+    steps: Vec<JsonStep<'a>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonStep<'a> {
+    action: &'a str,
+    #[serde(default, borrow)]
+    args: Vec<&'a str>,
+}
+
+impl From<&Parameter> for JsonParameter {
+    fn from(parameter: &Parameter) -> Self {
+        let (type_name, either) = match parameter {
+            Parameter::Untyped { .. } => (None, None),
+            Parameter::Typed { type_name, .. } => (Some(type_name.clone()), None),
+            Parameter::Either { type_names, .. } => (None, Some(type_names.clone())),
+        };
+        Self {
+            name: parameter.name().to_owned(),
+            type_name,
+            either,
+        }
+    }
+}
+
+impl TryFrom<JsonParameter> for Parameter {
+    type Error = String;
+
+    fn try_from(parameter: JsonParameter) -> Result<Self, String> {
+        let name = lowercase(parameter.name);
+        match (
+            parameter.type_name.map(lowercase),
+            parameter
+                .either
+                .map(|names| names.into_iter().map(lowercase).collect()),
+        ) {
+            (None, None) => Ok(Parameter::Untyped { name }),
+            (Some(type_name), None) => Ok(Parameter::Typed { name, type_name }),
+            (None, Some(type_names)) => Ok(Parameter::Either { name, type_names }),
+            (Some(_), Some(_)) => Err(format!(
+                "parameter '{}' has both a type and an either",
+                name
+            )),
+        }
+    }
+}
+
+/// Domain names are lowercased as [crate::domain::parse_domain] does, while problems keep their case as [crate::problem::parse] does
+fn lowercase(name: String) -> String {
+    name.to_lowercase()
+}
+
+fn parameters_from(parameters: Vec<JsonParameter>) -> Result<Vec<Parameter>, String> {
+    parameters.into_iter().map(Parameter::try_from).collect()
+}
+
+/// The names of JSON atoms, borrowed or owned
+trait Name {
+    fn equality() -> Self;
+}
+
+impl Name for String {
+    fn equality() -> Self {
+        EQUALITY.to_owned()
+    }
+}
+
+impl Name for &str {
+    fn equality() -> Self {
+        EQUALITY
+    }
+}
+
+fn formula_to_json<A, S, F>(formula: &Formula<A>, atom: &F) -> JsonFormula<S>
+where
+    A: Atom,
+    F: Fn(&A) -> JsonAtom<S>,
+{
+    let children = |children: &[Formula<A>]| {
+        children
+            .iter()
+            .map(|child| formula_to_json(child, atom))
+            .collect()
+    };
+    match formula {
+        Formula::Atom(a) if a.is_equality() => JsonFormula::Equal(atom(a).args),
+        Formula::Atom(a) => JsonFormula::Atom(atom(a)),
+        Formula::And(c) => JsonFormula::And(children(c)),
+        Formula::Or(c) => JsonFormula::Or(children(c)),
+        Formula::Not(child) => JsonFormula::Not(Box::new(formula_to_json(child, atom))),
+        Formula::Imply(antecedent, consequent) => JsonFormula::Imply(
+            Box::new(formula_to_json(antecedent, atom)),
+            Box::new(formula_to_json(consequent, atom)),
+        ),
+        Formula::Forall {
+            parameters,
+            formula,
+        } => JsonFormula::Forall {
+            parameters: parameters.iter().map(JsonParameter::from).collect(),
+            formula: Box::new(formula_to_json(formula, atom)),
+        },
+        Formula::Exists {
+            parameters,
+            formula,
+        } => JsonFormula::Exists {
+            parameters: parameters.iter().map(JsonParameter::from).collect(),
+            formula: Box::new(formula_to_json(formula, atom)),
+        },
+    }
+}
+
+fn formula_from_json<A, S, F>(formula: JsonFormula<S>, atom: &F) -> Result<Formula<A>, String>
+where
+    S: Name,
+    F: Fn(JsonAtom<S>) -> A,
+{
+    let children = |children: Vec<JsonFormula<S>>| {
+        children
+            .into_iter()
+            .map(|child| formula_from_json(child, atom))
+            .collect::<Result<_, _>>()
+    };
+    let child = |child: Box<JsonFormula<S>>| formula_from_json(*child, atom).map(Box::new);
+    Ok(match formula {
+        JsonFormula::Atom(a) => Formula::Atom(atom(a)),
+        JsonFormula::Equal(args) => Formula::Atom(atom(JsonAtom {
+            predicate: S::equality(),
+            args,
+        })),
+        JsonFormula::And(c) => Formula::And(children(c)?),
+        JsonFormula::Or(c) => Formula::Or(children(c)?),
+        JsonFormula::Not(c) => Formula::Not(child(c)?),
+        JsonFormula::Imply(antecedent, consequent) => {
+            Formula::Imply(child(antecedent)?, child(consequent)?)
+        }
+        JsonFormula::Forall {
+            parameters,
+            formula,
+        } => Formula::Forall {
+            parameters: parameters_from(parameters)?,
+            formula: child(formula)?,
+        },
+        JsonFormula::Exists {
+            parameters,
+            formula,
+        } => Formula::Exists {
+            parameters: parameters_from(parameters)?,
+            formula: child(formula)?,
+        },
+    })
+}
+
+fn term_to_json(term: &Term) -> JsonAtom<String> {
+    JsonAtom {
+        predicate: term.name.clone(),
+        args: term.parameters.clone(),
+    }
+}
+
+fn term_from_json(atom: JsonAtom<String>) -> Term {
+    Term {
+        name: lowercase(atom.predicate),
+        parameters: atom.args.into_iter().map(lowercase).collect(),
+    }
+}
+
+fn fact_to_json<'a>(fact: &Fact<'a>) -> JsonAtom<&'a str> {
+    JsonAtom {
+        predicate: fact.predicate,
+        args: fact.objects.clone(),
+    }
+}
+
+fn fact_from_json(atom: JsonAtom<&str>) -> Fact<'_> {
+    Fact {
+        predicate: atom.predicate,
+        objects: atom.args,
+    }
+}
+
+impl From<&NumericExpression> for JsonNumeric {
+    fn from(expression: &NumericExpression) -> Self {
+        match expression {
+            NumericExpression::Number(number) => JsonNumeric::Number(number.clone()),
+            NumericExpression::Fluent(term) => JsonNumeric::Fluent(term_to_json(term)),
+            NumericExpression::Binary(operator, left, right) => {
+                let (left, right) = (
+                    Box::new(left.as_ref().into()),
+                    Box::new(right.as_ref().into()),
+                );
+                match operator {
+                    BinaryOperator::Add => JsonNumeric::Add(left, right),
+                    BinaryOperator::Subtract => JsonNumeric::Subtract(left, right),
+                    BinaryOperator::Multiply => JsonNumeric::Multiply(left, right),
+                    BinaryOperator::Divide => JsonNumeric::Divide(left, right),
+                }
+            }
+            NumericExpression::Negate(child) => {
+                JsonNumeric::Negate(Box::new(child.as_ref().into()))
+            }
+        }
+    }
+}
+
+impl From<JsonNumeric> for NumericExpression {
+    fn from(expression: JsonNumeric) -> Self {
+        let binary = |operator, left: Box<JsonNumeric>, right: Box<JsonNumeric>| {
+            NumericExpression::Binary(
+                operator,
+                Box::new((*left).into()),
+                Box::new((*right).into()),
+            )
+        };
+        match expression {
+            JsonNumeric::Number(number) => NumericExpression::Number(number),
+            JsonNumeric::Fluent(atom) => NumericExpression::Fluent(term_from_json(atom)),
+            JsonNumeric::Add(left, right) => binary(BinaryOperator::Add, left, right),
+            JsonNumeric::Subtract(left, right) => binary(BinaryOperator::Subtract, left, right),
+            JsonNumeric::Multiply(left, right) => binary(BinaryOperator::Multiply, left, right),
+            JsonNumeric::Divide(left, right) => binary(BinaryOperator::Divide, left, right),
+            JsonNumeric::Negate(child) => NumericExpression::Negate(Box::new((*child).into())),
+        }
+    }
+}
+
+impl From<&Effect> for JsonEffect {
+    fn from(effect: &Effect) -> Self {
+        match effect {
+            Effect::Add(term) => JsonEffect::Atom(term_to_json(term)),
+            Effect::Delete(term) => JsonEffect::Not(Box::new(JsonEffect::Atom(term_to_json(term)))),
+            Effect::And(children) => JsonEffect::And(children.iter().map(Self::from).collect()),
+            Effect::When { condition, effect } => JsonEffect::When {
+                condition: formula_to_json(condition, &term_to_json),
+                effect: Box::new(effect.as_ref().into()),
+            },
+            Effect::Forall { parameters, effect } => JsonEffect::Forall {
+                parameters: parameters.iter().map(JsonParameter::from).collect(),
+                effect: Box::new(effect.as_ref().into()),
+            },
+            Effect::Numeric {
+                operator,
+                fluent,
+                value,
+            } => {
+                let numeric = JsonNumericEffect {
+                    fluent: term_to_json(fluent),
+                    value: value.into(),
+                };
+                match operator {
+                    AssignOperator::Assign => JsonEffect::Assign(numeric),
+                    AssignOperator::Increase => JsonEffect::Increase(numeric),
+                    AssignOperator::Decrease => JsonEffect::Decrease(numeric),
+                    AssignOperator::ScaleUp => JsonEffect::ScaleUp(numeric),
+                    AssignOperator::ScaleDown => JsonEffect::ScaleDown(numeric),
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<JsonEffect> for Effect {
+    type Error = String;
+
+    fn try_from(effect: JsonEffect) -> Result<Self, String> {
+        let numeric = |operator, numeric: JsonNumericEffect| Effect::Numeric {
+            operator,
+            fluent: term_from_json(numeric.fluent),
+            value: numeric.value.into(),
+        };
+        Ok(match effect {
+            JsonEffect::Atom(atom) => Effect::Add(term_from_json(atom)),
+            JsonEffect::Not(child) => match *child {
+                JsonEffect::Atom(atom) => Effect::Delete(term_from_json(atom)),
+                _ => return Err("only atoms can be negated in effects".to_owned()),
+            },
+            JsonEffect::And(children) => Effect::And(
+                children
+                    .into_iter()
+                    .map(Effect::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            JsonEffect::When { condition, effect } => Effect::When {
+                condition: formula_from_json(condition, &term_from_json)?,
+                effect: Box::new((*effect).try_into()?),
+            },
+            JsonEffect::Forall { parameters, effect } => Effect::Forall {
+                parameters: parameters_from(parameters)?,
+                effect: Box::new((*effect).try_into()?),
+            },
+            JsonEffect::Assign(n) => numeric(AssignOperator::Assign, n),
+            JsonEffect::Increase(n) => numeric(AssignOperator::Increase, n),
+            JsonEffect::Decrease(n) => numeric(AssignOperator::Decrease, n),
+            JsonEffect::ScaleUp(n) => numeric(AssignOperator::ScaleUp, n),
+            JsonEffect::ScaleDown(n) => numeric(AssignOperator::ScaleDown, n),
+        })
+    }
+}
+
+/// Checks that a goal only uses the connectives a PDDL problem can express
+fn check_goal(formula: &JsonFormula<&str>) -> Result<(), String> {
+    match formula {
+        JsonFormula::Atom(_) => Ok(()),
+        JsonFormula::Not(child) => check_goal(child),
+        JsonFormula::And(children) | JsonFormula::Or(children) => {
+            children.iter().try_for_each(check_goal)
+        }
+        JsonFormula::Forall { formula, .. } | JsonFormula::Exists { formula, .. } => {
+            check_goal(formula)
+        }
+        JsonFormula::Equal(_) => Err("goals cannot contain 'equal'".to_owned()),
+        JsonFormula::Imply(..) => Err("goals cannot contain 'imply'".to_owned()),
+    }
+}
+
+fn to_json<T: Serialize>(document: T) -> String {
+    serde_json::to_string_pretty(&document).expect("documents are always serializable")
+}
+
+fn from_json<'a, T: Deserialize<'a>>(input: &'a str, kind: Kind) -> Result<T, String> {
+    let header: Header = serde_json::from_str(input).map_err(|err| err.to_string())?;
+    if header.version != VERSION {
+        return Err(format!(
+            "unsupported version {}, expected {}",
+            header.version, VERSION
+        ));
+    }
+    let expected = serde_json::to_value(&kind).expect("kinds are always serializable");
+    let found = serde_json::to_value(&header.kind).expect("kinds are always serializable");
+    if expected != found {
+        return Err(format!("expected kind {}, found {}", expected, found));
+    }
+    serde_json::from_str(input).map_err(|err| err.to_string())
+}
+
+/// Writes a domain in the interchange format
+pub fn domain_to_json(domain: &Domain) -> String {
+    to_json(JsonDomain {
+        version: VERSION,
+        kind: Kind::Domain,
+        name: domain.name.clone(),
+        requirements: domain.requirements.clone(),
+        types: domain.types.as_ref().map(|types| {
+            types
+                .iter()
+                .map(|t| JsonType {
+                    name: t.name.clone(),
+                    subtypes: t.sub_types.clone(),
+                })
+                .collect()
+        }),
+        constants: domain
+            .constants
+            .as_ref()
+            .map(|constants| constants.iter().map(JsonParameter::from).collect()),
+        predicates: domain
+            .predicates
+            .iter()
+            .map(|predicate| JsonPredicate {
+                name: predicate.name.clone(),
+                parameters: predicate
+                    .parameters
+                    .iter()
+                    .map(JsonParameter::from)
+                    .collect(),
+            })
+            .collect(),
+        actions: domain
+            .actions
+            .iter()
+            .map(|action| JsonAction {
+                name: action.name.clone(),
+                parameters: action.parameters.iter().map(JsonParameter::from).collect(),
+                precondition: action
+                    .precondition
+                    .as_ref()
+                    .map(|formula| formula_to_json(formula, &term_to_json)),
+                effect: (&action.effect).into(),
+            })
+            .collect(),
+    })
+}
+
+/// Reads a domain from the interchange format
+///
+/// Names are lowercased, as when parsing a domain
+///
+/// ## Example
+/// ```rust
+/// use spingus::{domain::parse_domain, json::{domain_from_json, domain_to_json}};
+/// let domain = parse_domain(
+///     "(define (domain d) (:predicates (p ?x)) (:action a :parameters (?x) :effect (p ?x)))",
+/// )
+/// .unwrap();
+/// assert_eq!(domain_from_json(&domain_to_json(&domain)), Ok(domain));
+/// ```
+pub fn domain_from_json(input: &str) -> Result<Domain, String> {
+    let domain: JsonDomain = from_json(input, Kind::Domain)?;
+    Ok(Domain {
+        name: lowercase(domain.name),
+        requirements: domain
+            .requirements
+            .map(|requirements| requirements.into_iter().map(lowercase).collect()),
+        types: domain.types.map(|types| {
+            types
+                .into_iter()
+                .map(|t| Type {
+                    name: lowercase(t.name),
+                    sub_types: t.subtypes.into_iter().map(lowercase).collect(),
+                })
+                .collect()
+        }),
+        constants: domain.constants.map(parameters_from).transpose()?,
+        predicates: domain
+            .predicates
+            .into_iter()
+            .map(|predicate| {
+                Ok(Predicate {
+                    name: lowercase(predicate.name),
+                    parameters: parameters_from(predicate.parameters)?,
+                })
+            })
+            .collect::<Result<_, String>>()?,
+        actions: domain
+            .actions
+            .into_iter()
+            .map(|action| {
+                Ok(Action {
+                    name: lowercase(action.name),
+                    parameters: parameters_from(action.parameters)?,
+                    precondition: action
+                        .precondition
+                        .map(|formula| formula_from_json(formula, &term_from_json))
+                        .transpose()?,
+                    effect: action.effect.try_into()?,
+                })
+            })
+            .collect::<Result<_, String>>()?,
+    })
+}
+
+/// Writes a problem in the interchange format
+pub fn problem_to_json(problem: &Problem) -> String {
+    to_json(JsonProblem {
+        version: VERSION,
+        kind: Kind::Problem,
+        name: problem.name,
+        domain: problem.domain,
+        objects: problem.objects.as_ref().map(|objects| {
+            objects
+                .iter()
+                .map(|object| JsonObject {
+                    name: object.name,
+                    type_name: object.type_name,
+                })
+                .collect()
+        }),
+        init: problem.init.as_ref().map(|init| {
+            init.iter()
+                .map(|fact| JsonAtom {
+                    predicate: fact.predicate,
+                    args: fact.objects.clone(),
+                })
+                .collect()
+        }),
+        goal: problem
+            .goal
+            .as_ref()
+            .map(|goal| formula_to_json(goal, &fact_to_json)),
+    })
+}
+
+/// Reads a problem from the interchange format
+///
+/// The problem borrows its names from `input`, so they cannot contain escape sequences, and keeps their case as when parsing a problem
+pub fn problem_from_json(input: &str) -> Result<Problem<'_>, String> {
+    let problem: JsonProblem = from_json(input, Kind::Problem)?;
+    Ok(Problem {
+        name: problem.name,
+        domain: problem.domain,
+        objects: problem.objects.map(|objects| {
+            objects
+                .into_iter()
+                .map(|object| Object {
+                    name: object.name,
+                    type_name: object.type_name,
+                })
+                .collect()
+        }),
+        init: problem.init.map(|init| {
+            init.into_iter()
+                .map(|atom| Fact {
+                    predicate: atom.predicate,
+                    objects: atom.args,
+                })
+                .collect()
+        }),
+        goal: match problem.goal {
+            Some(goal) => {
+                check_goal(&goal)?;
+                Some(formula_from_json(goal, &fact_from_json)?)
+            }
+            None => None,
+        },
+    })
+}
+
+/// Writes a plan in the interchange format
+pub fn plan_to_json(plan: &Plan) -> String {
+    to_json(JsonPlan {
+        version: VERSION,
+        kind: Kind::Plan,
+        steps: plan
+            .iter()
+            .map(|(action, args)| JsonStep {
+                action,
+                args: args.clone(),
+            })
+            .collect(),
+    })
+}
+
+/// Reads a plan from the interchange format
+///
+/// The plan borrows its names from `input`, so they cannot contain escape sequences
+pub fn plan_from_json(input: &str) -> Result<Plan<'_>, String> {
+    let plan: JsonPlan = from_json(input, Kind::Plan)?;
+    Ok(plan
+        .steps
+        .into_iter()
+        .map(|step| (step.action, step.args))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        domain_from_json, domain_to_json, plan_from_json, problem_from_json, problem_to_json,
+    };
+    use crate::{domain::parse_domain, problem};
+
+    #[test]
+    fn header() {
+        assert_eq!(
+            plan_from_json(r#"{ "version": 2, "kind": "plan", "steps": [] }"#),
+            Err("unsupported version 2, expected 1".to_owned())
+        );
+        assert_eq!(
+            domain_from_json(r#"{ "version": 1, "kind": "plan", "steps": [] }"#),
+            Err(r#"expected kind "domain", found "plan""#.to_owned())
+        );
+        assert!(
+            plan_from_json(r#"{ "version": 1, "kind": "plan", "steps": [], "x": 1 }"#).is_err()
+        );
+        assert_eq!(
+            plan_from_json(r#"{ "version": 1, "kind": "plan", "steps": [{ "action": "a" }] }"#),
+            Ok(vec![("a", vec![])])
+        );
+    }
+
+    #[test]
+    fn goal() {
+        let problem = problem::parse("(define (problem p) (:goal (or (p a) (not (q)))))");
+        let json = problem_to_json(&problem);
+        assert!(json.contains(r#""or": ["#));
+        assert_eq!(problem_from_json(&json), Ok(problem));
+        assert_eq!(
+            problem_from_json(
+                r#"{ "version": 1, "kind": "problem", "goal": { "equal": ["a", "b"] } }"#
+            ),
+            Err("goals cannot contain 'equal'".to_owned())
+        );
+    }
+
+    #[test]
+    fn case() {
+        let domain = parse_domain(
+            "(define (domain D) (:requirements :Typing) (:types Ball) (:constants Left - Ball)
+                (:predicates (At ?X - Ball)) (:action Move :parameters (?B - Ball)
+                :precondition (At ?B) :effect (not (At Left))))",
+        )
+        .unwrap();
+        let upper = domain_to_json(&domain)
+            .replace("\"at\"", "\"At\"")
+            .replace("\"?b\"", "\"?B\"")
+            .replace("\"ball\"", "\"BALL\"")
+            .replace("\"left\"", "\"Left\"")
+            .replace("\"move\"", "\"Move\"")
+            .replace("\"typing\"", "\"Typing\"");
+        assert_ne!(upper, domain_to_json(&domain));
+        assert_eq!(domain_from_json(&upper), Ok(domain));
+
+        let input =
+            "(define (problem P) (:domain D) (:objects B1 - Ball) (:init (At B1)) (:goal (At B1)))";
+        let problem = problem::parse(input);
+        assert_eq!(problem.name, Some("P"));
+        assert_eq!(problem_from_json(&problem_to_json(&problem)), Ok(problem));
+    }
+}
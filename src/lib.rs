@@ -23,6 +23,10 @@
 //! //...
 //! ```
 //!
+//! ## Cargo features
+//! + `json` - Enables the [json] interchange format
+//! + `serde` - Derives `Serialize` and `Deserialize` for all AST types. Borrowed problems and plans can be deserialized without copying, as long as their strings contain no escapes
+//!
 //! ## Benchmark
 //! Benchmarked on a i5-13600k with [Criterion](https://github.com/bheisler/criterion.rs)
 //!
@@ -33,9 +37,40 @@
 //! | Plan          | 727 MiB/s  | 378 ns |
 //!
 
+/// Compilations of domains and problems into simpler fragments of PDDL, which keep their plans
+pub mod compile;
+
+/// A Datalog engine with semi-naive bottom-up evaluation, on which [ground] is built
+pub mod datalog;
+
 /// Contains things related to PDDL domain files
 pub mod domain;
 
+/// The logical formulas shared by domains and problems
+pub mod formula;
+
+/// Formats PDDL files into a canonical layout, keeping their comments
+///
+/// Also available from the command line as `pddl fmt`
+pub mod format;
+
+/// A versioned JSON interchange format for domains, problems and plans
+///
+/// Unlike the `serde` derives, which follow the Rust types, this format is stable and documented for tools outside of Rust
+#[cfg(feature = "json")]
+pub mod json;
+
+/// Grounds the action schemas of a domain over the objects of a problem, into a task of ground actions over atoms
+pub mod ground;
+
+/// Checks for PDDL which is valid, but likely a mistake, such as unused predicates or goals which already hold
+///
+/// Every [lint::Lint] has a stable code, and can be allowed, warned about or denied with a [lint::LintConfig]
+pub mod lint;
+
+/// Owned versions of problems and plans, which outlive the input they were parsed from
+pub mod owned;
+
 /// Contains things related to PDDL plan files
 ///
 /// These files are used as a solution to PDDL domain + problem
@@ -53,5 +88,40 @@ pub mod plan;
 /// Contains things related to PDDL problem files
 pub mod problem;
 
+/// Translates ground tasks into the finite-domain representation of SAS+, with variables for groups of mutually exclusive atoms
+pub mod sas;
+
+/// Encodes ground tasks into propositional formulas for SAT solvers, and decodes their models into plans
+pub mod sat;
+
+/// Interns names into compact IDs, and views of domains and problems by IDs
+pub mod symbol;
+
+/// Predicates applied to arguments, the atoms of domain formulas
+pub mod term;
+
+/// Visitor and fold traits over formulas, and utilities built on them
+///
+/// ## Example
+/// ```rust
+/// let problem = spingus::problem::parse("(define (problem p) (:goal (and (p a) (not (q b)))))");
+/// let goal = problem.goal.unwrap();
+/// assert_eq!(goal.predicate_names().into_iter().collect::<Vec<_>>(), vec!["p", "q"]);
+/// assert_eq!(goal.depth(), 3);
+/// ```
+pub mod visit;
+
+/// Writes parsed domains, problems and plans back as PDDL
+///
+/// Every node of the ASTs implements [writer::WritePddl], and those with their own type also implement [std::fmt::Display]
+///
+/// ## Example
+/// ```rust
+/// use spingus::writer::WritePddl;
+/// let input = "(define (problem prob) (:objects o1) (:init (p o1)) (:goal (not (p o1))))";
+/// let problem = spingus::problem::parse(&input);
+/// assert_eq!(spingus::problem::parse(&problem.to_pddl()), problem);
+/// ```
+pub mod writer;
+
 mod shared;
-mod term;
@@ -0,0 +1,559 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::{
+    domain::{
+        action::{
+            effect::{Effect, NumericExpression},
+            Action,
+        },
+        parameter::Parameter,
+        types::hierarchy::{first_declarations, TypeHierarchy, OBJECT},
+        validate::{Diagnostic, Scope, Severity},
+        Domain,
+    },
+    formula::{Atom, Formula},
+    owned::OwnedFact,
+    problem::Problem,
+    symbol::lowercase_problem,
+    term::Term,
+    visit::{walk_effect, walk_formula, Visitor},
+};
+
+/// A check for something which is allowed, but likely a mistake
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum Lint {
+    /// A predicate which no action uses
+    UnusedPredicate,
+    /// A type which nothing is of, in the domain or in a problem
+    UnusedType,
+    /// An action whose effect holds whenever its precondition does
+    NoOpAction,
+    /// An action parameter which neither the precondition nor the effect uses
+    UnusedParameter,
+    /// A fact of `:init` given twice
+    DuplicateFact,
+    /// A goal which the initial state already satisfies
+    GoalAlreadyTrue,
+    /// An action with more parameters than [LintConfig::max_parameters], which is costly to ground
+    ManyParameters,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 7] = [
+        Lint::UnusedPredicate,
+        Lint::UnusedType,
+        Lint::NoOpAction,
+        Lint::UnusedParameter,
+        Lint::DuplicateFact,
+        Lint::GoalAlreadyTrue,
+        Lint::ManyParameters,
+    ];
+
+    /// The stable code of the lint, set as [Diagnostic::code]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lint::UnusedPredicate => "L001",
+            Lint::UnusedType => "L002",
+            Lint::NoOpAction => "L003",
+            Lint::UnusedParameter => "L004",
+            Lint::DuplicateFact => "L005",
+            Lint::GoalAlreadyTrue => "L006",
+            Lint::ManyParameters => "L007",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedPredicate => "unused-predicate",
+            Lint::UnusedType => "unused-type",
+            Lint::NoOpAction => "no-op-action",
+            Lint::UnusedParameter => "unused-parameter",
+            Lint::DuplicateFact => "duplicate-fact",
+            Lint::GoalAlreadyTrue => "goal-already-true",
+            Lint::ManyParameters => "many-parameters",
+        }
+    }
+
+    /// The lint with the given code or name, such as `L001` or `unused-predicate`
+    pub fn find(code_or_name: &str) -> Option<Lint> {
+        Lint::ALL
+            .into_iter()
+            .find(|lint| lint.code() == code_or_name || lint.name() == code_or_name)
+    }
+}
+
+/// How a [Lint] is reported
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Level {
+    /// Not reported
+    Allow,
+    /// Reported as a [Severity::Warning]
+    Warn,
+    /// Reported as a [Severity::Error]
+    Deny,
+}
+
+/// The [Level] of every [Lint], all [Level::Warn] by default
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LintConfig {
+    levels: BTreeMap<Lint, Level>,
+    /// Actions with more parameters are reported by [Lint::ManyParameters]
+    pub max_parameters: usize,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        LintConfig {
+            levels: BTreeMap::new(),
+            max_parameters: 6,
+        }
+    }
+}
+
+impl LintConfig {
+    /// Reports `lint` at `level`
+    pub fn set(mut self, lint: Lint, level: Level) -> Self {
+        self.levels.insert(lint, level);
+        self
+    }
+
+    pub fn level(&self, lint: Lint) -> Level {
+        self.levels.get(&lint).copied().unwrap_or(Level::Warn)
+    }
+}
+
+struct Linter<'c> {
+    config: &'c LintConfig,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, lint: Lint, scope: Scope, subject: &str, message: String) {
+        let severity = match self.config.level(lint) {
+            Level::Allow => return,
+            Level::Warn => Severity::Warning,
+            Level::Deny => Severity::Error,
+        };
+        let mut diagnostic = Diagnostic::new(severity, scope, subject, message);
+        diagnostic.code = Some(lint.code());
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn enabled(&self, lint: Lint) -> bool {
+        self.config.level(lint) != Level::Allow
+    }
+}
+
+/// The types of a parameter or constant, which is [OBJECT] when untyped
+fn types_of(parameter: &Parameter) -> Vec<&str> {
+    match parameter {
+        Parameter::Untyped { .. } => vec![OBJECT],
+        Parameter::Typed { type_name, .. } => vec![type_name],
+        Parameter::Either { type_names, .. } => type_names.iter().map(String::as_str).collect(),
+    }
+}
+
+fn expression_terms<'a>(expression: &'a NumericExpression, terms: &mut Vec<&'a Term>) {
+    match expression {
+        NumericExpression::Number(_) => {}
+        NumericExpression::Fluent(term) => terms.push(term),
+        NumericExpression::Binary(_, left, right) => {
+            expression_terms(left, terms);
+            expression_terms(right, terms);
+        }
+        NumericExpression::Negate(child) => expression_terms(child, terms),
+    }
+}
+
+/// Collects every term of formulas and effects, including conditions, fluents and equalities
+#[derive(Default)]
+struct Terms<'a> {
+    terms: Vec<&'a Term>,
+    /// The parameters of quantified formulas and effects
+    quantified: Vec<&'a Parameter>,
+}
+
+impl<'a> Visitor<'a, Term> for Terms<'a> {
+    fn visit_formula(&mut self, formula: &'a Formula<Term>) {
+        if let Formula::Forall { parameters, .. } | Formula::Exists { parameters, .. } = formula {
+            self.quantified.extend(parameters);
+        }
+        walk_formula(self, formula);
+    }
+
+    fn visit_atom(&mut self, term: &'a Term) {
+        self.terms.push(term);
+    }
+
+    fn visit_effect(&mut self, effect: &'a Effect) {
+        match effect {
+            Effect::Forall { parameters, .. } => {
+                self.quantified.extend(parameters);
+                walk_effect(self, effect);
+            }
+            Effect::Numeric { fluent, value, .. } => {
+                self.terms.push(fluent);
+                expression_terms(value, &mut self.terms);
+            }
+            _ => walk_effect(self, effect),
+        }
+    }
+}
+
+/// Every term of the precondition and effect of `action`
+fn action_terms(action: &Action) -> Vec<&Term> {
+    let mut terms = Terms::default();
+    if let Some(precondition) = &action.precondition {
+        terms.visit_formula(precondition);
+    }
+    terms.visit_effect(&action.effect);
+    terms.terms
+}
+
+/// The literals the precondition of an action requires, looking only through conjunctions
+#[derive(Default)]
+struct Required<'a> {
+    positive: Vec<&'a Term>,
+    negative: Vec<&'a Term>,
+}
+
+impl<'a> Visitor<'a, Term> for Required<'a> {
+    fn visit_formula(&mut self, formula: &'a Formula<Term>) {
+        match formula {
+            Formula::Atom(term) => self.positive.push(term),
+            Formula::Not(child) => {
+                if let Formula::Atom(term) = child.as_ref() {
+                    self.negative.push(term)
+                }
+            }
+            Formula::And(_) => walk_formula(self, formula),
+            Formula::Or(_)
+            | Formula::Imply(..)
+            | Formula::Forall { .. }
+            | Formula::Exists { .. } => {}
+        }
+    }
+}
+
+/// Finds whether an effect changes a literal which the precondition does not require, so that applying it may change the state
+struct Changes<'r, 'a> {
+    required: &'r Required<'a>,
+    changes: bool,
+}
+
+impl<'a> Visitor<'a, Term> for Changes<'_, 'a> {
+    fn visit_effect(&mut self, effect: &'a Effect) {
+        let required = match effect {
+            Effect::Add(term) => self.required.positive.contains(&term),
+            Effect::Delete(term) => self.required.negative.contains(&term),
+            Effect::And(_) => return walk_effect(self, effect),
+            Effect::When { .. } | Effect::Forall { .. } | Effect::Numeric { .. } => false,
+        };
+        self.changes |= !required;
+    }
+}
+
+fn lint_types(linter: &mut Linter, domain: &Domain) {
+    let (types, _) = first_declarations(domain.types.as_deref().unwrap_or_default());
+    let hierarchy = TypeHierarchy::new(&types).ok();
+
+    let mut parameters: Vec<&Parameter> = domain
+        .predicates
+        .iter()
+        .flat_map(|predicate| &predicate.parameters)
+        .chain(domain.constants.iter().flatten())
+        .collect();
+    for action in &domain.actions {
+        parameters.extend(&action.parameters);
+        let mut terms = Terms::default();
+        if let Some(precondition) = &action.precondition {
+            terms.visit_formula(precondition);
+        }
+        terms.visit_effect(&action.effect);
+        parameters.extend(terms.quantified);
+    }
+    let mut used: BTreeSet<&str> = parameters.into_iter().flat_map(types_of).collect();
+    if let Some(hierarchy) = &hierarchy {
+        let ancestors: Vec<&str> = used.iter().flat_map(|t| hierarchy.ancestors(t)).collect();
+        used.extend(ancestors);
+    }
+
+    let declared: BTreeSet<&str> = types
+        .iter()
+        .flat_map(|t| {
+            std::iter::once(t.name.as_str()).chain(t.sub_types.iter().map(String::as_str))
+        })
+        .filter(|&t| t != OBJECT)
+        .collect();
+    for type_name in declared.difference(&used) {
+        let message = format!("type '{}' is never used", type_name);
+        linter.report(Lint::UnusedType, Scope::Types, type_name, message);
+    }
+}
+
+fn lint_action(linter: &mut Linter, action: &Action) {
+    let scope = Scope::Action(action.name.clone());
+    let terms = action_terms(action);
+
+    let used: BTreeSet<&str> = terms
+        .iter()
+        .flat_map(|term| term.arguments())
+        .map(String::as_str)
+        .collect();
+    for parameter in &action.parameters {
+        if !used.contains(parameter.name()) {
+            let message = format!(
+                "action '{}': parameter '{}' is never used",
+                action.name,
+                parameter.name()
+            );
+            linter.report(
+                Lint::UnusedParameter,
+                scope.clone(),
+                parameter.name(),
+                message,
+            );
+        }
+    }
+
+    let mut required = Required::default();
+    if let Some(precondition) = &action.precondition {
+        required.visit_formula(precondition);
+    }
+    let mut changes = Changes {
+        required: &required,
+        changes: false,
+    };
+    changes.visit_effect(&action.effect);
+    if !changes.changes {
+        let message = format!("action '{}' never changes the state", action.name);
+        linter.report(Lint::NoOpAction, scope.clone(), &action.name, message);
+    }
+
+    if action.parameters.len() > linter.config.max_parameters {
+        let message = format!(
+            "action '{}' has {} parameters, more than {}",
+            action.name,
+            action.parameters.len(),
+            linter.config.max_parameters
+        );
+        linter.report(Lint::ManyParameters, scope, &action.name, message);
+    }
+}
+
+/// Checks `domain` for the [Lint]s about domains, reported as configured by `config`
+///
+/// Lints complement [Domain::validate], and are only meaningful for valid domains
+///
+/// ## Example
+/// ```rust
+/// use spingus::{domain::parse_domain, lint::{lint_domain, Level, Lint, LintConfig}};
+/// let domain = parse_domain("(define (domain d)
+///     (:predicates (p ?x) (q))
+///     (:action a :parameters (?x ?y) :precondition (p ?x) :effect (and (p ?x))))").unwrap();
+/// let diagnostics = lint_domain(&domain, &LintConfig::default());
+/// assert_eq!(diagnostics[0].message, "predicate 'q' is never used by an action");
+/// assert_eq!(diagnostics[0].code, Some("L001"));
+///
+/// let config = LintConfig::default().set(Lint::UnusedPredicate, Level::Allow);
+/// let diagnostics = lint_domain(&domain, &config);
+/// assert_eq!(diagnostics[0].message, "action 'a': parameter '?y' is never used");
+/// assert_eq!(diagnostics[1].message, "action 'a' never changes the state");
+/// ```
+pub fn lint_domain(domain: &Domain, config: &LintConfig) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        config,
+        diagnostics: Vec::new(),
+    };
+
+    if linter.enabled(Lint::UnusedPredicate) {
+        let used: HashSet<&str> = domain
+            .actions
+            .iter()
+            .flat_map(action_terms)
+            .map(|term| term.name.as_str())
+            .collect();
+        for predicate in &domain.predicates {
+            if !used.contains(predicate.name.as_str()) {
+                let message = format!("predicate '{}' is never used by an action", predicate.name);
+                let scope = Scope::Predicate(predicate.name.clone());
+                linter.report(Lint::UnusedPredicate, scope, &predicate.name, message);
+            }
+        }
+    }
+    if linter.enabled(Lint::UnusedType) {
+        lint_types(&mut linter, domain);
+    }
+    for action in &domain.actions {
+        lint_action(&mut linter, action);
+    }
+    linter.diagnostics
+}
+
+/// Whether `formula` holds in the state where exactly the facts of `init` are true, or `None` if it is quantified
+fn holds(formula: &Formula<OwnedFact>, init: &HashSet<&OwnedFact>) -> Option<bool> {
+    formula.evaluate(&mut |fact| match fact.is_equality() {
+        true => fact.objects.windows(2).all(|pair| pair[0] == pair[1]),
+        false => init.contains(fact),
+    })
+}
+
+/// Checks `problem` for the [Lint]s about problems, reported as configured by `config`
+///
+/// Lints complement [crate::problem::validate::validate_problem], and are only meaningful for valid problems
+///
+/// ## Example
+/// ```rust
+/// use spingus::{domain::parse_domain, lint::{lint_problem, LintConfig}, problem};
+/// let domain = parse_domain("(define (domain d)
+///     (:predicates (p ?x))
+///     (:action a :parameters (?x) :precondition (p ?x) :effect (not (p ?x))))").unwrap();
+/// let input = "(define (problem pr) (:domain d) (:objects o) (:init (p o) (p o)) (:goal (p o)))";
+/// let diagnostics = lint_problem(&domain, &problem::parse(input), &LintConfig::default());
+/// assert_eq!(diagnostics[0].message, "fact '(p o)' is declared twice");
+/// assert_eq!(diagnostics[0].span(input), Some(53..58));
+/// assert_eq!(diagnostics[1].message, "the goal is already true in the initial state");
+/// ```
+pub fn lint_problem(domain: &Domain, problem: &Problem, config: &LintConfig) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        config,
+        diagnostics: Vec::new(),
+    };
+
+    // Names are compared lowercased, as planners compare them, while diagnostics show them as written
+    let lowercased = lowercase_problem(problem);
+    let mut init = HashSet::new();
+    for (fact, key) in problem
+        .init
+        .iter()
+        .flatten()
+        .zip(lowercased.init.iter().flatten())
+    {
+        if !init.insert(key) {
+            let subject = fact.to_string();
+            let message = format!("fact '{}' is declared twice", subject);
+            linter.report(Lint::DuplicateFact, Scope::Init, &subject, message);
+        }
+    }
+
+    if let Some(goal) = &lowercased.goal {
+        if holds(goal, &init) == Some(true) {
+            let message = "the goal is already true in the initial state".to_owned();
+            linter.report(Lint::GoalAlreadyTrue, Scope::Goal, ":goal", message);
+        }
+    }
+
+    if linter.enabled(Lint::UnusedType) {
+        let (types, _) = first_declarations(domain.types.as_deref().unwrap_or_default());
+        if let Ok(hierarchy) = TypeHierarchy::new(&types) {
+            let mut object_types: Vec<String> = lowercased
+                .objects
+                .iter()
+                .flatten()
+                .map(|object| object.type_name.clone().unwrap_or(OBJECT.to_owned()))
+                .collect();
+            object_types.extend(
+                domain
+                    .constants
+                    .iter()
+                    .flatten()
+                    .flat_map(types_of)
+                    .map(str::to_owned),
+            );
+            let parameters: BTreeSet<&str> = domain
+                .actions
+                .iter()
+                .flat_map(|action| &action.parameters)
+                .flat_map(types_of)
+                .collect();
+            for type_name in parameters {
+                let empty = !object_types
+                    .iter()
+                    .any(|object_type| hierarchy.is_subtype(object_type, type_name));
+                if hierarchy.contains(type_name) && empty {
+                    let message = format!("type '{}' has no objects", type_name);
+                    linter.report(Lint::UnusedType, Scope::Objects, type_name, message);
+                }
+            }
+        }
+    }
+    linter.diagnostics
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lint_domain, lint_problem, Level, Lint, LintConfig};
+    use crate::{
+        domain::{parse_domain, validate::Severity},
+        problem,
+    };
+
+    fn messages(diagnostics: Vec<crate::domain::validate::Diagnostic>) -> Vec<String> {
+        diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn domain() {
+        let domain = parse_domain(
+            "(define (domain d)
+                (:types room ball box - object heavy - ball)
+                (:predicates (at ?b - ball ?r - room) (free ?r - room))
+                (:action move
+                    :parameters (?b - ball ?from ?to - room ?x ?y ?z ?w)
+                    :precondition (and (at ?b ?from) (free ?to) (= ?x ?y) (= ?z ?w))
+                    :effect (and (not (at ?b ?from)) (at ?b ?to)))
+                (:action stay
+                    :parameters (?b - ball ?r - room)
+                    :precondition (and (at ?b ?r) (not (free ?r)))
+                    :effect (and (at ?b ?r) (not (free ?r)))))",
+        )
+        .unwrap();
+        assert_eq!(
+            messages(lint_domain(&domain, &LintConfig::default())),
+            vec![
+                "type 'box' is never used",
+                "type 'heavy' is never used",
+                "action 'move' has 7 parameters, more than 6",
+                "action 'stay' never changes the state",
+            ]
+        );
+
+        let config = LintConfig::default()
+            .set(Lint::UnusedType, Level::Allow)
+            .set(Lint::NoOpAction, Level::Deny);
+        let diagnostics = lint_domain(&domain, &config);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(diagnostics[1].code, Some("L003"));
+        assert_eq!(Lint::find("no-op-action"), Some(Lint::NoOpAction));
+        assert_eq!(Lint::find("L003"), Some(Lint::NoOpAction));
+    }
+
+    #[test]
+    fn problem() {
+        let domain = parse_domain(
+            "(define (domain d)
+                (:types room ball)
+                (:predicates (at ?b - ball ?r - room))
+                (:action drop :parameters (?b - ball ?r - room) :effect (at ?b ?r)))",
+        )
+        .unwrap();
+        let input = "(define (problem p) (:domain d) (:objects r - room)
+            (:init (at a r) (AT a r))
+            (:goal (or (at r a) (at a r))))";
+        assert_eq!(
+            messages(lint_problem(
+                &domain,
+                &problem::parse(input),
+                &LintConfig::default()
+            )),
+            vec![
+                "fact '(AT a r)' is declared twice",
+                "the goal is already true in the initial state",
+                "type 'ball' has no objects",
+            ]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use std::fmt;
+
+use crate::{
+    formula::{Atom, Formula},
+    plan::{Plan, Step},
+    problem::{init::Fact, objects::Object, Problem},
+    writer::{impl_display, PddlWriter, WritePddl},
+};
+
+/// Converts a borrowing node into one owning its strings
+pub trait IntoOwned {
+    type Owned;
+
+    fn into_owned(self) -> Self::Owned;
+}
+
+/// Borrows an owned node as its borrowing counterpart, without copying any strings
+pub trait AsBorrowed {
+    type Borrowed<'a>
+    where
+        Self: 'a;
+
+    fn as_borrowed(&self) -> Self::Borrowed<'_>;
+}
+
+/// An owned [Problem], which does not borrow the input it was parsed from
+///
+/// ## Example
+/// ```rust
+/// use spingus::owned::{AsBorrowed, IntoOwned, OwnedProblem};
+/// fn read() -> OwnedProblem {
+///     let input = String::from("(define (problem p) (:init (a)))");
+///     spingus::problem::parse(&input).into_owned()
+/// }
+/// let problem = read();
+/// assert_eq!(problem.name.as_deref(), Some("p"));
+/// assert_eq!(problem.as_borrowed().to_string(), "(define (problem p)\n    (:init\n        (a)\n    )\n)");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedProblem {
+    pub name: Option<String>,
+    pub domain: Option<String>,
+    pub objects: Option<Vec<OwnedObject>>,
+    pub init: Option<Vec<OwnedFact>>,
+    pub goal: Option<OwnedGoal>,
+}
+
+/// An owned [Object]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedObject {
+    pub name: String,
+    pub type_name: Option<String>,
+}
+
+/// An owned [Fact]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedFact {
+    pub predicate: String,
+    pub objects: Vec<String>,
+}
+
+/// An owned goal
+pub type OwnedGoal = Formula<OwnedFact>;
+
+/// An owned [Step]
+pub type OwnedStep = (String, Vec<String>);
+
+/// An owned [Plan]
+pub type OwnedPlan = Vec<OwnedStep>;
+
+fn strings(names: Vec<&str>) -> Vec<String> {
+    names.into_iter().map(str::to_owned).collect()
+}
+
+fn strs(names: &[String]) -> Vec<&str> {
+    names.iter().map(String::as_str).collect()
+}
+
+impl IntoOwned for Problem<'_> {
+    type Owned = OwnedProblem;
+
+    fn into_owned(self) -> OwnedProblem {
+        OwnedProblem {
+            name: self.name.map(str::to_owned),
+            domain: self.domain.map(str::to_owned),
+            objects: self
+                .objects
+                .map(|objects| objects.into_iter().map(Object::into_owned).collect()),
+            init: self
+                .init
+                .map(|init| init.into_iter().map(Fact::into_owned).collect()),
+            goal: self.goal.map(IntoOwned::into_owned),
+        }
+    }
+}
+
+impl AsBorrowed for OwnedProblem {
+    type Borrowed<'a> = Problem<'a>;
+
+    fn as_borrowed(&self) -> Problem<'_> {
+        Problem {
+            name: self.name.as_deref(),
+            domain: self.domain.as_deref(),
+            objects: self
+                .objects
+                .as_ref()
+                .map(|objects| objects.iter().map(OwnedObject::as_borrowed).collect()),
+            init: self
+                .init
+                .as_ref()
+                .map(|init| init.iter().map(OwnedFact::as_borrowed).collect()),
+            goal: self.goal.as_ref().map(OwnedGoal::as_borrowed),
+        }
+    }
+}
+
+impl IntoOwned for Object<'_> {
+    type Owned = OwnedObject;
+
+    fn into_owned(self) -> OwnedObject {
+        OwnedObject {
+            name: self.name.to_owned(),
+            type_name: self.type_name.map(str::to_owned),
+        }
+    }
+}
+
+impl AsBorrowed for OwnedObject {
+    type Borrowed<'a> = Object<'a>;
+
+    fn as_borrowed(&self) -> Object<'_> {
+        Object {
+            name: &self.name,
+            type_name: self.type_name.as_deref(),
+        }
+    }
+}
+
+impl IntoOwned for Fact<'_> {
+    type Owned = OwnedFact;
+
+    fn into_owned(self) -> OwnedFact {
+        OwnedFact {
+            predicate: self.predicate.to_owned(),
+            objects: strings(self.objects),
+        }
+    }
+}
+
+impl AsBorrowed for OwnedFact {
+    type Borrowed<'a> = Fact<'a>;
+
+    fn as_borrowed(&self) -> Fact<'_> {
+        Fact {
+            predicate: &self.predicate,
+            objects: strs(&self.objects),
+        }
+    }
+}
+
+impl Atom for OwnedFact {
+    type Arg = String;
+
+    fn predicate(&self) -> &str {
+        &self.predicate
+    }
+
+    fn arguments(&self) -> &[String] {
+        &self.objects
+    }
+}
+
+impl IntoOwned for Formula<Fact<'_>> {
+    type Owned = OwnedGoal;
+
+    fn into_owned(self) -> OwnedGoal {
+        self.map(&mut Fact::into_owned)
+    }
+}
+
+impl AsBorrowed for OwnedGoal {
+    type Borrowed<'a> = Formula<Fact<'a>>;
+
+    fn as_borrowed(&self) -> Formula<Fact<'_>> {
+        self.map_ref(&mut OwnedFact::as_borrowed)
+    }
+}
+
+impl IntoOwned for Plan<'_> {
+    type Owned = OwnedPlan;
+
+    fn into_owned(self) -> OwnedPlan {
+        self.into_iter()
+            .map(|(action, objects)| (action.to_owned(), strings(objects)))
+            .collect()
+    }
+}
+
+impl AsBorrowed for OwnedPlan {
+    type Borrowed<'a> = Plan<'a>;
+
+    fn as_borrowed(&self) -> Plan<'_> {
+        self.iter()
+            .map(|(action, objects)| -> Step { (action, strs(objects)) })
+            .collect()
+    }
+}
+
+impl WritePddl for OwnedProblem {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        self.as_borrowed().write_pddl(w)
+    }
+}
+
+impl WritePddl for OwnedObject {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        self.as_borrowed().write_pddl(w)
+    }
+}
+
+impl WritePddl for OwnedFact {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        self.as_borrowed().write_pddl(w)
+    }
+}
+
+impl WritePddl for OwnedPlan {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        self.as_borrowed().write_pddl(w)
+    }
+}
+
+impl_display!(OwnedProblem, OwnedObject, OwnedFact);
+
+#[cfg(test)]
+mod test {
+    use super::{AsBorrowed, IntoOwned, OwnedPlan, OwnedProblem};
+    use crate::{plan, problem};
+
+    fn parse_owned(input: String) -> OwnedProblem {
+        problem::parse(&input).into_owned()
+    }
+
+    #[test]
+    fn problem() {
+        let input = "(define (problem p) (:domain d)
+            (:objects a b - t c)
+            (:init (p a) (q a b))
+            (:goal (and (p b) (or (not (q b a)) (p c)))))";
+        let owned = parse_owned(input.to_owned());
+        assert_eq!(owned.as_borrowed(), problem::parse(input));
+        assert_eq!(owned.to_string(), problem::parse(input).to_string());
+        let handle = std::thread::spawn(move || owned.goal.unwrap().as_borrowed().depth());
+        assert_eq!(handle.join().unwrap(), 4);
+    }
+
+    #[test]
+    fn plan() {
+        let input = String::from("(a b c)\n(d)");
+        let owned: OwnedPlan = plan::parse(&input).into_owned();
+        drop(input);
+        assert_eq!(owned[0].1, vec!["b", "c"]);
+        assert_eq!(owned.as_borrowed(), plan::parse("(a b c)\n(d)"));
+    }
+}
@@ -1,10 +1,15 @@
 mod token;
 
+use std::fmt::{self, Write};
+
 use annotate_snippets::{AnnotationType, Renderer, Slice, Snippet, SourceAnnotation};
 
 use logos::{Lexer, Logos};
 
-use crate::shared::{line_num, Result};
+use crate::{
+    shared::{line_num, Result},
+    writer::{PddlWriter, WritePddl},
+};
 
 use self::token::Token;
 
@@ -14,6 +19,28 @@ pub type Step<'a> = (&'a str, Vec<&'a str>);
 /// Denotes a sequence of steps
 pub type Plan<'a> = Vec<Step<'a>>;
 
+impl WritePddl for Step<'_> {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        write!(w, "({}", self.0)?;
+        for object in &self.1 {
+            write!(w, " {}", object)?;
+        }
+        w.write_char(')')
+    }
+}
+
+impl WritePddl for Plan<'_> {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        for (i, step) in self.iter().enumerate() {
+            if i > 0 {
+                w.newline()?;
+            }
+            step.write_pddl(w)?;
+        }
+        Ok(())
+    }
+}
+
 fn parse_step<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Step<'a>> {
     let action = match lexer.next() {
         Some(token) => match token {
@@ -56,7 +83,7 @@ fn parse_plan<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Plan<'a>> {
 /// let plan = spingus::plan::try_parse(&input);
 /// assert_eq!(plan, Ok(vec![("p1", vec!["o1", "o2", "o3"])]));
 /// ```
-pub fn try_parse(input: &str) -> Result<Plan> {
+pub fn try_parse(input: &str) -> Result<Plan<'_>> {
     let mut lexer = Token::lexer(input);
     parse_plan(&mut lexer)
 }
@@ -71,7 +98,7 @@ pub fn try_parse(input: &str) -> Result<Plan> {
 /// let plan = spingus::plan::parse(&input);
 /// assert_eq!(plan, vec![("p1", vec!["o1", "o2", "o3"])]);
 /// ```
-pub fn parse(input: &str) -> Plan {
+pub fn parse(input: &str) -> Plan<'_> {
     match try_parse(input) {
         Ok(problem) => problem,
         Err((msg, span)) => {
@@ -110,4 +137,13 @@ mod test {
             vec![("a", vec!["b"]), ("c", vec!["d"])]
         );
     }
+
+    #[test]
+    fn plan_write() {
+        use crate::writer::WritePddl;
+
+        let plan = parse("(a b)\n(c)\n; cost = 2 (unit cost)");
+        assert_eq!(plan.to_pddl(), "(a b)\n(c)");
+        assert_eq!(parse(&plan.to_pddl()), plan);
+    }
 }
@@ -0,0 +1,231 @@
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet};
+
+use crate::{
+    domain::builder::is_identifier,
+    formula::Formula,
+    owned::{AsBorrowed, OwnedFact, OwnedGoal},
+};
+
+use super::{init::Fact, objects::Object, Problem};
+
+/// Names the problem lexer reads as keywords rather than names
+const RESERVED: [&str; 5] = ["define", "problem", "and", "or", "not"];
+
+/// A goal of a [ProblemBuilder], see [fact], [and], [or] and [not]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoalBuilder(OwnedGoal);
+
+/// The fact `(predicate objects...)`
+pub fn fact<'a>(predicate: &str, objects: impl IntoIterator<Item = &'a str>) -> GoalBuilder {
+    GoalBuilder(Formula::Atom(OwnedFact {
+        predicate: predicate.to_owned(),
+        objects: objects.into_iter().map(str::to_owned).collect(),
+    }))
+}
+
+pub fn and(children: impl IntoIterator<Item = GoalBuilder>) -> GoalBuilder {
+    GoalBuilder(Formula::And(children.into_iter().map(|c| c.0).collect()))
+}
+
+pub fn or(children: impl IntoIterator<Item = GoalBuilder>) -> GoalBuilder {
+    GoalBuilder(Formula::Or(children.into_iter().map(|c| c.0).collect()))
+}
+
+pub fn not(child: GoalBuilder) -> GoalBuilder {
+    GoalBuilder(Formula::Not(Box::new(child.0)))
+}
+
+/// Builds a [Problem] from owned names, validating it on [ProblemBuilder::build]
+///
+/// As [Problem] borrows its names, the built problem borrows from the builder
+///
+/// ## Example
+/// ```rust
+/// use spingus::problem::builder::{fact, ProblemBuilder};
+/// use spingus::writer::WritePddl;
+/// let mut builder = ProblemBuilder::new("p1", "rooms").objects(["r1", "r2"], Some("room"));
+/// for room in ["r1", "r2"] {
+///     builder = builder.init("free", [room]);
+/// }
+/// let builder = builder.goal(fact("at", ["r2"]));
+/// let problem = builder.build().unwrap();
+/// assert_eq!(spingus::problem::parse(&problem.to_pddl()), problem);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemBuilder {
+    name: String,
+    domain: String,
+    objects: Vec<(String, Option<String>)>,
+    init: Vec<(String, Vec<String>)>,
+    goal: Option<OwnedGoal>,
+}
+
+impl ProblemBuilder {
+    pub fn new(name: &str, domain: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            domain: domain.to_owned(),
+            objects: Vec::new(),
+            init: Vec::new(),
+            goal: None,
+        }
+    }
+
+    pub fn object(mut self, name: &str, type_name: Option<&str>) -> Self {
+        self.objects
+            .push((name.to_owned(), type_name.map(str::to_owned)));
+        self
+    }
+
+    pub fn objects<'a>(
+        mut self,
+        names: impl IntoIterator<Item = &'a str>,
+        type_name: Option<&str>,
+    ) -> Self {
+        for name in names {
+            self = self.object(name, type_name);
+        }
+        self
+    }
+
+    /// Adds the fact `(predicate objects...)` to the initial state
+    pub fn init<'a>(mut self, predicate: &str, objects: impl IntoIterator<Item = &'a str>) -> Self {
+        self.init.push((
+            predicate.to_owned(),
+            objects.into_iter().map(str::to_owned).collect(),
+        ));
+        self
+    }
+
+    /// Sets the goal, replacing any previous one
+    pub fn goal(mut self, goal: GoalBuilder) -> Self {
+        self.goal = Some(goal.0);
+        self
+    }
+
+    /// Builds the problem, borrowing its names from the builder
+    ///
+    /// Fails if a name is not a valid identifier, if an object is declared twice, or if a predicate is used with differing arities
+    pub fn build(&self) -> Result<Problem<'_>, String> {
+        let problem = Problem {
+            name: Some(&self.name),
+            domain: Some(&self.domain),
+            objects: (!self.objects.is_empty()).then(|| {
+                self.objects
+                    .iter()
+                    .map(|(name, type_name)| Object {
+                        name,
+                        type_name: type_name.as_deref(),
+                    })
+                    .collect()
+            }),
+            init: Some(
+                self.init
+                    .iter()
+                    .map(|(predicate, objects)| Fact {
+                        predicate,
+                        objects: objects.iter().map(String::as_str).collect(),
+                    })
+                    .collect(),
+            ),
+            goal: self.goal.as_ref().map(OwnedGoal::as_borrowed),
+        };
+        check_problem(&problem)?;
+        Ok(problem)
+    }
+}
+
+fn check_name(kind: &str, name: &str) -> Result<(), String> {
+    match is_identifier(name)
+        && !name.starts_with('?')
+        && !RESERVED.contains(&name.to_lowercase().as_str())
+    {
+        true => Ok(()),
+        false => Err(format!("invalid {} name '{}'", kind, name)),
+    }
+}
+
+fn check_problem(problem: &Problem) -> Result<(), String> {
+    problem
+        .name
+        .map_or(Ok(()), |name| check_name("problem", name))?;
+    problem
+        .domain
+        .map_or(Ok(()), |name| check_name("domain", name))?;
+    let mut declared = BTreeSet::new();
+    for object in problem.objects.iter().flatten() {
+        check_name("object", object.name)?;
+        object
+            .type_name
+            .map_or(Ok(()), |name| check_name("type", name))?;
+        if !declared.insert(object.name) {
+            return Err(format!("object '{}' is declared twice", object.name));
+        }
+    }
+    let goal_facts = problem
+        .goal
+        .as_ref()
+        .map(Formula::atoms)
+        .unwrap_or_default();
+    let mut arities = BTreeMap::new();
+    for fact in problem.init.iter().flatten().chain(goal_facts) {
+        check_name("predicate", fact.predicate)?;
+        for object in &fact.objects {
+            check_name("object", object)?;
+        }
+        match arities.entry(fact.predicate) {
+            Entry::Vacant(entry) => {
+                entry.insert(fact.objects.len());
+            }
+            Entry::Occupied(entry) if *entry.get() != fact.objects.len() => {
+                return Err(format!(
+                    "predicate '{}' is used with both {} and {} arguments",
+                    fact.predicate,
+                    entry.get(),
+                    fact.objects.len()
+                ))
+            }
+            Entry::Occupied(_) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{and, fact, not, ProblemBuilder};
+    use crate::{problem, writer::WritePddl};
+
+    fn builder() -> ProblemBuilder {
+        ProblemBuilder::new("p", "Blocks")
+            .objects(["A", "b"], Some("block"))
+            .object("t", None)
+            .init("on", ["A", "b"])
+            .init("clear", ["A"])
+    }
+
+    #[test]
+    fn build() {
+        let builder = builder().goal(and([fact("on", ["b", "A"]), not(fact("clear", ["A"]))]));
+        let problem = builder.build().unwrap();
+        assert_eq!(problem.domain, Some("Blocks"));
+        assert_eq!(problem.objects.as_ref().unwrap().len(), 3);
+        assert_eq!(problem::parse(&problem.to_pddl()), problem);
+    }
+
+    #[test]
+    fn build_errors() {
+        let errors = [
+            (builder().object("b", None), "object 'b' is declared twice"),
+            (
+                builder().goal(fact("on", ["A"])),
+                "predicate 'on' is used with both 2 and 1 arguments",
+            ),
+            (builder().init("clear", ["?x"]), "invalid object name '?x'"),
+            (builder().object("and", None), "invalid object name 'and'"),
+        ];
+        for (builder, error) in errors {
+            assert_eq!(builder.build(), Err(error.to_owned()));
+        }
+    }
+}
@@ -1,22 +1,87 @@
+use std::fmt;
+
 use logos::Lexer;
 
-use crate::shared::Result;
+use crate::{
+    domain::parameter::Parameter,
+    formula::Formula,
+    shared::Result,
+    writer::{PddlWriter, WritePddl, WriterConfig},
+};
 
-use super::token::Token;
+use super::{init::Fact, token::Token};
 
+/// The goal type of problems before [Formula] was introduced
+#[deprecated(note = "use `Formula<Fact>` instead")]
+#[allow(deprecated)]
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Goal<'a> {
     Fact {
         predicate: &'a str,
+        #[cfg_attr(feature = "serde", serde(borrow))]
         objects: Vec<&'a str>,
     },
-    Not(Box<Goal<'a>>),
-    And(Vec<Goal<'a>>),
-    Or(Vec<Goal<'a>>),
+    Not(#[cfg_attr(feature = "serde", serde(borrow))] Box<Goal<'a>>),
+    And(#[cfg_attr(feature = "serde", serde(borrow))] Vec<Goal<'a>>),
+    Or(#[cfg_attr(feature = "serde", serde(borrow))] Vec<Goal<'a>>),
+}
+
+/// Written as the equivalent [Formula]
+#[allow(deprecated)]
+impl WritePddl for Goal<'_> {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        Formula::from(self.clone()).write_pddl(w)
+    }
+}
+
+#[allow(deprecated)]
+impl fmt::Display for Goal<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let config = WriterConfig::default();
+        self.write_pddl(&mut PddlWriter::new(f, &config))
+    }
+}
+
+#[allow(deprecated)]
+impl<'a> From<Goal<'a>> for Formula<Fact<'a>> {
+    fn from(goal: Goal<'a>) -> Self {
+        match goal {
+            Goal::Fact { predicate, objects } => Formula::Atom(Fact { predicate, objects }),
+            Goal::Not(child) => Formula::Not(Box::new((*child).into())),
+            Goal::And(children) => Formula::And(children.into_iter().map(Formula::from).collect()),
+            Goal::Or(children) => Formula::Or(children.into_iter().map(Formula::from).collect()),
+        }
+    }
+}
+
+/// Fails for implications and quantifiers, which [Goal] cannot represent
+#[allow(deprecated)]
+impl<'a> TryFrom<Formula<Fact<'a>>> for Goal<'a> {
+    type Error = String;
+
+    fn try_from(formula: Formula<Fact<'a>>) -> std::result::Result<Self, String> {
+        let children = |children: Vec<Formula<Fact<'a>>>| {
+            children
+                .into_iter()
+                .map(Goal::try_from)
+                .collect::<std::result::Result<_, _>>()
+        };
+        match formula {
+            Formula::Atom(Fact { predicate, objects }) => Ok(Goal::Fact { predicate, objects }),
+            Formula::Not(child) => Ok(Goal::Not(Box::new((*child).try_into()?))),
+            Formula::And(c) => Ok(Goal::And(children(c)?)),
+            Formula::Or(c) => Ok(Goal::Or(children(c)?)),
+            Formula::Imply(..) => Err("goal contains an implication".to_owned()),
+            Formula::Forall { .. } | Formula::Exists { .. } => {
+                Err("goal contains a quantifier".to_owned())
+            }
+        }
+    }
 }
 
 //  NOTE: assumes opening bracket '(' is consumed
-fn parse_expression<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Goal<'a>> {
+fn parse_expression<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Formula<Fact<'a>>> {
     let token = lexer
         .next()
         .ok_or(("unexpected end of input".to_owned(), lexer.span()))?;
@@ -26,16 +91,16 @@ fn parse_expression<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Goal<'a>> {
 
             while let Some(token) = lexer.next() {
                 match token {
-                    Ok(Token::Name(name)) => objects.push(name),
+                    Ok(Token::Name(name) | Token::Variable(name)) => objects.push(name),
                     Ok(Token::RParen) => break,
                     _ => return Err(("unexpected token".to_owned(), lexer.span())),
                 }
             }
 
-            return Ok(Goal::Fact {
+            Ok(Formula::Atom(Fact {
                 predicate: name,
                 objects,
-            });
+            }))
         }
         Ok(Token::Not) => {
             let n_token = lexer
@@ -45,39 +110,94 @@ fn parse_expression<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Goal<'a>> {
                 Ok(Token::LParen) => parse_expression(lexer),
                 _ => return Err(("unexpected token".to_owned(), lexer.span())),
             }?;
-            return Ok(Goal::Not(Box::new(expression)));
+            Ok(Formula::Not(Box::new(expression)))
         }
         Ok(Token::And) => {
             let mut expressions = Vec::new();
 
             while let Some(token) = lexer.next() {
                 match token {
-                    Ok(Token::RParen) => return Ok(Goal::And(expressions)),
+                    Ok(Token::RParen) => return Ok(Formula::And(expressions)),
                     Ok(Token::LParen) => expressions.push(parse_expression(lexer)?),
                     _ => return Err(("unexpected token".to_owned(), lexer.span())),
                 }
             }
 
-            return Err(("unexpected end of input".to_owned(), lexer.span()));
+            Err(("unexpected end of input".to_owned(), lexer.span()))
         }
         Ok(Token::Or) => {
             let mut expressions = Vec::new();
 
             while let Some(token) = lexer.next() {
                 match token {
-                    Ok(Token::RParen) => return Ok(Goal::Or(expressions)),
+                    Ok(Token::RParen) => return Ok(Formula::Or(expressions)),
                     Ok(Token::LParen) => expressions.push(parse_expression(lexer)?),
                     _ => return Err(("unexpected token".to_owned(), lexer.span())),
                 }
             }
 
-            return Err(("unexpected end of input".to_owned(), lexer.span()));
+            Err(("unexpected end of input".to_owned(), lexer.span()))
+        }
+        Ok(token @ (Token::Forall | Token::Exists)) => {
+            match lexer.next() {
+                Some(Ok(Token::LParen)) => {}
+                _ => return Err(("unexpected token".to_owned(), lexer.span())),
+            }
+            let parameters = parse_parameters(lexer)?;
+            match lexer.next() {
+                Some(Ok(Token::LParen)) => {}
+                _ => return Err(("unexpected token".to_owned(), lexer.span())),
+            }
+            let formula = Box::new(parse_expression(lexer)?);
+            match lexer.next() {
+                Some(Ok(Token::RParen)) => {}
+                _ => return Err(("unexpected token".to_owned(), lexer.span())),
+            }
+            Ok(match token {
+                Token::Forall => Formula::Forall {
+                    parameters,
+                    formula,
+                },
+                _ => Formula::Exists {
+                    parameters,
+                    formula,
+                },
+            })
+        }
+        _ => Err(("unexpected token".to_owned(), lexer.span())),
+    }
+}
+
+/// Parses the typed list of variables of a quantifier, which are lowercased like the parameters of a domain
+//  NOTE: assumes opening bracket '(' is consumed
+fn parse_parameters<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Vec<Parameter>> {
+    let mut parameters = Vec::new();
+    let mut untyped: Vec<String> = Vec::new();
+    let mut awaiting_type = false;
+    while let Some(token) = lexer.next() {
+        match token {
+            Ok(Token::Variable(name)) if !awaiting_type => untyped.push(name.to_lowercase()),
+            Ok(Token::Name(type_name)) if awaiting_type => {
+                parameters.extend(untyped.drain(..).map(|name| Parameter::Typed {
+                    name,
+                    type_name: type_name.to_lowercase(),
+                }));
+                awaiting_type = false;
+            }
+            Ok(Token::TypeSeparator) if !awaiting_type && !untyped.is_empty() => {
+                awaiting_type = true
+            }
+            Ok(Token::RParen) if !awaiting_type => {
+                parameters.extend(untyped.into_iter().map(|name| Parameter::Untyped { name }));
+                return Ok(parameters);
+            }
+            _ => return Err(("unexpected token".to_owned(), lexer.span())),
         }
-        _ => return Err(("unexpected token".to_owned(), lexer.span())),
     }
+    Err(("unexpected end of input".to_owned(), lexer.span()))
 }
 
-pub(super) fn parse_goal<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Goal<'a>> {
+pub(super) fn parse_goal<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Formula<Fact<'a>>> {
     match lexer.next() {
         Some(token) => match token {
             Ok(Token::LParen) => {}
@@ -102,33 +222,94 @@ pub(super) fn parse_goal<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Goal<'a
 mod test {
     use logos::Logos;
 
-    use crate::problem::{
-        goal::{parse_expression, parse_goal, Goal},
-        token::Token,
+    use crate::{
+        domain::parameter::Parameter,
+        formula::Formula,
+        problem::{
+            goal::{parse_expression, parse_goal},
+            init::Fact,
+            token::Token,
+        },
     };
 
     use rstest::*;
 
     #[rstest]
-    #[case("a)", Goal::Fact { predicate: "a", objects: vec![] })]
-    #[case("a b c)", Goal::Fact { predicate: "a", objects: vec!["b", "c"] })]
-    fn fact_parse(#[case] input: &str, #[case] expected: Goal) {
+    #[case("a)", Formula::Atom(Fact { predicate: "a", objects: vec![] }))]
+    #[case("a b c)", Formula::Atom(Fact { predicate: "a", objects: vec!["b", "c"] }))]
+    fn fact_parse(#[case] input: &str, #[case] expected: Formula<Fact>) {
         let mut lexer = Token::lexer(input);
         assert_eq!(parse_expression(&mut lexer), Ok(expected));
     }
 
     #[rstest]
-    #[case("not (a))", Goal::Not(Box::new(Goal::Fact { predicate: "a", objects: vec![] })))]
-    fn not_parse(#[case] input: &str, #[case] expected: Goal) {
+    #[case("not (a))", Formula::Not(Box::new(Formula::Atom(Fact { predicate: "a", objects: vec![] }))))]
+    fn not_parse(#[case] input: &str, #[case] expected: Formula<Fact>) {
         let mut lexer = Token::lexer(input);
         assert_eq!(parse_expression(&mut lexer), Ok(expected));
     }
 
     #[rstest]
-    #[case("(a))", Goal::Fact { predicate: "a", objects: vec![] })]
-    #[case("(and (a) (b)))", Goal::And(vec![Goal::Fact { predicate: "a", objects: vec![] }, Goal::Fact { predicate: "b", objects: vec![] }]))]
-    fn goal_parse(#[case] input: &str, #[case] expected: Goal) {
+    #[case("(a))", Formula::Atom(Fact { predicate: "a", objects: vec![] }))]
+    #[case("(and (a) (b)))", Formula::And(vec![Formula::Atom(Fact { predicate: "a", objects: vec![] }), Formula::Atom(Fact { predicate: "b", objects: vec![] })]))]
+    fn goal_parse(#[case] input: &str, #[case] expected: Formula<Fact>) {
         let mut lexer = Token::lexer(input);
         assert_eq!(parse_goal(&mut lexer), Ok(expected));
     }
+
+    #[test]
+    fn quantified_goal_parse() {
+        let mut lexer = Token::lexer("(forall (?X ?y - Block) (exists (?z) (on ?X ?z))))");
+        let goal = parse_goal(&mut lexer).unwrap();
+        let Formula::Forall {
+            parameters,
+            formula,
+        } = &goal
+        else {
+            panic!("expected a universal goal, got {}", goal);
+        };
+        assert_eq!(
+            parameters,
+            &vec![
+                Parameter::Typed {
+                    name: "?x".to_owned(),
+                    type_name: "block".to_owned()
+                },
+                Parameter::Typed {
+                    name: "?y".to_owned(),
+                    type_name: "block".to_owned()
+                },
+            ]
+        );
+        assert_eq!(formula.to_string(), "(exists (?z) (on ?X ?z))");
+        assert!(parse_goal(&mut Token::lexer("(forall (?x -) (a ?x)))")).is_err());
+        assert!(parse_goal(&mut Token::lexer("(exists (x) (a x)))")).is_err());
+    }
+
+    #[rstest]
+    #[case(Formula::Atom(Fact { predicate: "a", objects: vec!["b"] }), "(a b)")]
+    #[case(Formula::Not(Box::new(Formula::Atom(Fact { predicate: "a", objects: vec![] }))), "(not (a))")]
+    #[case(Formula::Or(vec![Formula::Atom(Fact { predicate: "a", objects: vec![] }), Formula::Atom(Fact { predicate: "b", objects: vec![] })]), "(or\n    (a)\n    (b)\n)")]
+    fn goal_write(#[case] goal: Formula<Fact>, #[case] expected: &str) {
+        assert_eq!(goal.to_string(), expected);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_goal() {
+        use crate::problem::goal::Goal;
+        let goal = Goal::Not(Box::new(Goal::Fact {
+            predicate: "a",
+            objects: vec!["b"],
+        }));
+        assert_eq!(goal.to_string(), "(not (a b))");
+        let formula = Formula::from(goal.clone());
+        assert_eq!(formula.to_string(), "(not (a b))");
+        assert_eq!(Goal::try_from(formula), Ok(goal));
+        let fact = Formula::Atom(Fact {
+            predicate: "a",
+            objects: vec![],
+        });
+        assert!(Goal::try_from(Formula::Imply(Box::new(fact.clone()), Box::new(fact))).is_err());
+    }
 }
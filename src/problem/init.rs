@@ -1,17 +1,86 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+};
+
 use logos::Lexer;
 
-use crate::shared::Result;
+use crate::{
+    domain::{predicate::PredicateKind, Domain},
+    formula::Atom,
+    shared::Result,
+    symbol::{PredId, SymbolTable},
+    writer::{impl_display, PddlWriter, WritePddl},
+};
 
 use super::token::Token;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Fact<'a> {
     pub predicate: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub objects: Vec<&'a str>,
 }
 
 pub type Init<'a> = Vec<Fact<'a>>;
 
+impl<'a> Atom for Fact<'a> {
+    type Arg = &'a str;
+
+    fn predicate(&self) -> &str {
+        self.predicate
+    }
+
+    fn arguments(&self) -> &[&'a str] {
+        &self.objects
+    }
+}
+
+impl WritePddl for Fact<'_> {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        write!(w, "({}", self.predicate)?;
+        for object in &self.objects {
+            write!(w, " {}", object)?;
+        }
+        w.write_char(')')
+    }
+}
+
+impl_display!(Fact<'_>);
+
+/// Splits `init` into its static facts and its fluent facts, by the [crate::domain::predicate::PredicateKind] of their predicates in `domain`
+///
+/// Facts of predicates which `domain` does not declare are static, as no action changes them
+///
+/// ## Example
+/// ```rust
+/// use spingus::{domain::parse_domain, problem::{self, init::split_init}};
+/// let domain = parse_domain("(define (domain d)
+///     (:predicates (road ?a ?b) (at ?a))
+///     (:action go :parameters (?a ?b) :effect (and (not (at ?a)) (at ?b))))").unwrap();
+/// let problem = problem::parse("(define (problem p) (:init (road a b) (at a)))");
+/// let (statics, fluents) = split_init(problem.init.as_deref().unwrap_or_default(), &domain);
+/// assert_eq!(statics[0].to_string(), "(road a b)");
+/// assert_eq!(fluents[0].to_string(), "(at a)");
+/// ```
+pub fn split_init<'i, 'a>(
+    init: &'i [Fact<'a>],
+    domain: &Domain,
+) -> (Vec<&'i Fact<'a>>, Vec<&'i Fact<'a>>) {
+    let mut symbols = SymbolTable::default();
+    let kinds: BTreeMap<PredId, PredicateKind> = domain
+        .predicate_kinds()
+        .into_iter()
+        .map(|(name, kind)| (symbols.predicates.intern(name), kind))
+        .collect();
+    init.iter().partition(|fact| {
+        kinds
+            .get(&symbols.intern_fact(fact).predicate)
+            .is_none_or(|kind| kind.is_static())
+    })
+}
+
 fn parse_fact<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Fact<'a>> {
     let predicate = match lexer.next() {
         Some(token) => match token {
@@ -1,30 +1,84 @@
+pub mod builder;
 pub mod goal;
 pub mod init;
 pub mod objects;
 mod token;
+pub mod validate;
+
+use std::fmt::{self, Write};
 
 use annotate_snippets::{AnnotationType, Renderer, Slice, Snippet, SourceAnnotation};
 
 use logos::{Lexer, Logos};
 
-use crate::shared::{line_num, Result};
+use crate::{
+    formula::Formula,
+    shared::{line_num, Result},
+    writer::{impl_display, PddlWriter, WritePddl},
+};
 
 use self::{
-    goal::{parse_goal, Goal},
-    init::{parse_init, Init},
-    objects::{parse_objects, Objects},
+    goal::parse_goal,
+    init::{parse_init, Fact, Init},
+    objects::{parse_objects, write_objects, Objects},
     token::Token,
 };
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Problem<'source> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub name: Option<&'source str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub domain: Option<&'source str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub objects: Option<Objects<'source>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub init: Option<Init<'source>>,
-    pub goal: Option<Goal<'source>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub goal: Option<Formula<Fact<'source>>>,
 }
 
+impl WritePddl for Problem<'_> {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        w.write_str("(define")?;
+        if let Some(name) = self.name {
+            write!(w, " (problem {})", name)?;
+        }
+        w.indented(|w| {
+            if let Some(domain) = self.domain {
+                w.newline()?;
+                write!(w, "(:domain {})", domain)?;
+            }
+            if let Some(objects) = &self.objects {
+                w.newline()?;
+                w.write_str("(:objects")?;
+                w.indented(|w| {
+                    w.newline()?;
+                    write_objects(w, objects)
+                })?;
+                w.newline()?;
+                w.write_char(')')?;
+            }
+            if let Some(init) = &self.init {
+                w.newline()?;
+                w.block(":init", init)?;
+            }
+            if let Some(goal) = &self.goal {
+                w.newline()?;
+                w.write_str("(:goal ")?;
+                goal.write_pddl(w)?;
+                w.write_char(')')?;
+            }
+            Ok(())
+        })?;
+        w.newline()?;
+        w.write_char(')')
+    }
+}
+
+impl_display!(Problem<'_>);
+
 fn parse_name<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<&'a str> {
     let name = match lexer.next() {
         Some(token) => match token {
@@ -95,12 +149,12 @@ fn parse_problem<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Problem<'a>> {
     })
 }
 
-pub fn try_parse(input: &str) -> Result<Problem> {
+pub fn try_parse(input: &str) -> Result<Problem<'_>> {
     let mut lexer = Token::lexer(input);
     parse_problem(&mut lexer)
 }
 
-pub fn parse(input: &str) -> Problem {
+pub fn parse(input: &str) -> Problem<'_> {
     match try_parse(input) {
         Ok(problem) => problem,
         Err((msg, span)) => {
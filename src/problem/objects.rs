@@ -1,17 +1,59 @@
+use std::fmt::{self, Write};
+
 use logos::Lexer;
 
-use crate::shared::Result;
+use crate::{
+    domain::types::hierarchy::OBJECT,
+    shared::Result,
+    writer::{impl_display, PddlWriter, WritePddl},
+};
 
 use super::token::Token;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Object<'source> {
     pub name: &'source str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub type_name: Option<&'source str>,
 }
 
 pub type Objects<'source> = Vec<Object<'source>>;
 
+impl WritePddl for Object<'_> {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        w.write_str(self.name)?;
+        match self.type_name {
+            Some(type_name) => write!(w, " - {}", type_name),
+            None => Ok(()),
+        }
+    }
+}
+
+impl_display!(Object<'_>);
+
+/// Writes objects as a typed list, where consecutive objects of the same type share it
+///
+/// E.g. `o1 o2 - type1 o3 - type2`.
+/// Untyped objects followed by typed ones are written as of type `object`, as the type after them would apply to them as well
+pub fn write_objects(w: &mut PddlWriter, objects: &[Object]) -> fmt::Result {
+    for (i, object) in objects.iter().enumerate() {
+        if i > 0 {
+            w.write_char(' ')?;
+        }
+        w.write_str(object.name)?;
+        match objects.get(i + 1) {
+            Some(next) if next.type_name == object.type_name => {}
+            next => match object.type_name {
+                Some(type_name) => write!(w, " - {}", type_name)?,
+                None if next.is_some() => write!(w, " - {}", OBJECT)?,
+                None => {}
+            },
+        }
+    }
+    Ok(())
+}
+
 pub(super) fn parse_objects<'a>(lexer: &mut Lexer<'a, Token<'a>>) -> Result<Objects<'a>> {
     let mut objects = Vec::new();
 
@@ -38,11 +38,20 @@ pub(super) enum Token<'a> {
     #[regex("(?i)not")]
     Not,
 
+    #[regex("(?i)forall")]
+    Forall,
+
+    #[regex("(?i)exists")]
+    Exists,
+
     #[token("-")]
     TypeSeparator,
 
     #[regex("[a-zA-Z][a-zA-Z0-9-_]*")]
     Name(&'a str),
+
+    #[regex(r"\?[a-zA-Z][a-zA-Z0-9-_]*")]
+    Variable(&'a str),
 }
 
 impl fmt::Display for Token<'_> {
@@ -59,8 +68,11 @@ impl fmt::Display for Token<'_> {
             Token::And => write!(f, "AND"),
             Token::Or => write!(f, "OR"),
             Token::Not => write!(f, "NOT"),
+            Token::Forall => write!(f, "FORALL"),
+            Token::Exists => write!(f, "EXISTS"),
             Token::TypeSeparator => write!(f, "TYPE_SEPARATOR"),
             Token::Name(name) => write!(f, "{}", name),
+            Token::Variable(name) => write!(f, "{}", name),
         }
     }
 }
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    domain::{
+        parameter::Parameter,
+        types::hierarchy::{first_declarations, TypeHierarchy, OBJECT},
+        validate::{Diagnostic, Scope, Severity},
+        Domain,
+    },
+    formula::{Atom, Formula},
+    symbol::{ObjId, PredId, SymbolTable},
+    visit::{walk_formula, Visitor},
+};
+
+use super::{init::Fact, Problem};
+
+/// The types of a constant or parameter, empty when untyped
+fn types_of(parameter: &Parameter) -> Vec<String> {
+    match parameter {
+        Parameter::Untyped { .. } => vec![],
+        Parameter::Typed { type_name, .. } => vec![type_name.clone()],
+        Parameter::Either { type_names, .. } => type_names.clone(),
+    }
+}
+
+fn describe(types: &[String]) -> String {
+    match types {
+        [] => OBJECT.to_owned(),
+        [type_name] => type_name.to_string(),
+        _ => format!("(either {})", types.join(" ")),
+    }
+}
+
+struct ProblemCheck<'d> {
+    diagnostics: Vec<Diagnostic>,
+    hierarchy: Option<TypeHierarchy>,
+    /// The names of the domain and problem, which interning lowercases
+    symbols: SymbolTable,
+    predicates: BTreeMap<PredId, &'d [Parameter]>,
+    /// The types of every object and constant
+    objects: BTreeMap<ObjId, Vec<String>>,
+    scope: Scope,
+}
+
+impl ProblemCheck<'_> {
+    fn report(&mut self, severity: Severity, subject: &str, message: String) {
+        let diagnostic = Diagnostic::new(severity, self.scope.clone(), subject, message);
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    /// Whether an object of `types` can be an argument of the `expected` types
+    fn fits(&self, types: &[String], expected: &[String]) -> bool {
+        let Some(hierarchy) = &self.hierarchy else {
+            return true;
+        };
+        let object = [OBJECT.to_owned()];
+        let types = match types.is_empty() {
+            true => &object[..],
+            false => types,
+        };
+        expected.is_empty()
+            || types.iter().all(|t| {
+                !hierarchy.contains(t) || expected.iter().any(|e| hierarchy.is_subtype(t, e))
+            })
+    }
+
+    fn check_fact(&mut self, fact: &Fact) {
+        let subject = fact.to_string();
+        let key = self.symbols.intern_fact(fact);
+        let mut expected = None;
+        if !fact.is_equality() {
+            match self.predicates.get(&key.predicate) {
+                None => {
+                    let message = format!("predicate '{}' is not declared", fact.predicate);
+                    return self.report(Severity::Error, &subject, message);
+                }
+                Some(parameters) if parameters.len() != fact.objects.len() => {
+                    let message = format!(
+                        "predicate '{}' takes {} arguments, but '{}' gives {}",
+                        fact.predicate,
+                        parameters.len(),
+                        subject,
+                        fact.objects.len()
+                    );
+                    return self.report(Severity::Error, &subject, message);
+                }
+                Some(parameters) => expected = Some(*parameters),
+            }
+        }
+        for (i, (object, id)) in fact.objects.iter().zip(&key.args).enumerate() {
+            let Some(types) = self.objects.get(id) else {
+                let message = format!("object '{}' in '{}' is not declared", object, subject);
+                self.report(Severity::Error, &subject, message);
+                continue;
+            };
+            let Some(parameter) = expected.map(|parameters| &parameters[i]) else {
+                continue;
+            };
+            let expected_types = types_of(parameter);
+            if !self.fits(types, &expected_types) {
+                let message = format!(
+                    "object '{}' in '{}' is of type {}, but predicate '{}' expects {}",
+                    object,
+                    subject,
+                    describe(types),
+                    fact.predicate,
+                    describe(&expected_types)
+                );
+                self.report(Severity::Error, &subject, message);
+            }
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast, Fact<'ast>> for ProblemCheck<'_> {
+    /// Quantified variables are checked as objects of their types
+    fn visit_formula(&mut self, formula: &'ast Formula<Fact<'ast>>) {
+        let (Formula::Forall { parameters, .. } | Formula::Exists { parameters, .. }) = formula
+        else {
+            return walk_formula(self, formula);
+        };
+        let outer = self.objects.clone();
+        for parameter in parameters {
+            let variable = self.symbols.objects.intern(parameter.name());
+            self.objects.insert(variable, types_of(parameter));
+        }
+        walk_formula(self, formula);
+        self.objects = outer;
+    }
+
+    fn visit_atom(&mut self, fact: &'ast Fact<'ast>) {
+        self.check_fact(fact)
+    }
+}
+
+/// Checks that `problem` fits `domain`
+///
+/// Errors are reported for a mismatched `(:domain ...)`, objects declared twice or clashing with constants, undeclared object types, and facts of `:init` or `:goal` with undeclared predicates, the wrong arity, undeclared objects or objects of the wrong type.
+/// See [crate::lint] for checks of problems which are not errors.
+///
+/// Argument types are not checked when the `:types` of the domain do not form a tree, which [Domain::validate] reports.
+///
+/// ## Example
+/// ```rust
+/// use spingus::{domain::parse_domain, problem, problem::validate::validate_problem};
+/// let domain = parse_domain("(define (domain rooms)
+///     (:types room ball)
+///     (:predicates (at ?b - ball ?r - room))
+///     (:action noop :parameters () :effect (and)))").unwrap();
+/// let input = "(define (problem p) (:domain rooms) (:objects a b - room) (:init (at a b)))";
+/// let diagnostics = validate_problem(&domain, &problem::parse(input));
+/// assert_eq!(
+///     diagnostics[0].message,
+///     "object 'a' in '(at a b)' is of type room, but predicate 'at' expects ball"
+/// );
+/// assert_eq!(diagnostics[0].span(input), Some(65..73));
+/// ```
+pub fn validate_problem(domain: &Domain, problem: &Problem) -> Vec<Diagnostic> {
+    let (types, _) = first_declarations(domain.types.as_deref().unwrap_or_default());
+    let mut symbols = SymbolTable::default();
+    let mut check = ProblemCheck {
+        diagnostics: Vec::new(),
+        hierarchy: TypeHierarchy::new(&types).ok(),
+        predicates: domain
+            .predicates
+            .iter()
+            .map(|predicate| {
+                let id = symbols.predicates.intern(&predicate.name);
+                (id, predicate.parameters.as_slice())
+            })
+            .collect(),
+        objects: domain
+            .constants
+            .iter()
+            .flatten()
+            .map(|constant| (symbols.objects.intern(constant.name()), types_of(constant)))
+            .collect(),
+        symbols,
+        scope: Scope::Problem,
+    };
+
+    if let Some(name) = problem.domain {
+        if !name.eq_ignore_ascii_case(&domain.name) {
+            let message = format!(
+                "problem is for domain '{}', but the domain is '{}'",
+                name, domain.name
+            );
+            check.report(Severity::Error, name, message);
+        }
+    }
+
+    check.scope = Scope::Objects;
+    let mut declared = BTreeSet::new();
+    for object in problem.objects.iter().flatten() {
+        let key = check.symbols.intern_object(object);
+        let types: Vec<String> = key
+            .types
+            .iter()
+            .map(|type_id| check.symbols.types.name(*type_id).to_owned())
+            .collect();
+        let constant = check.objects.contains_key(&key.object) && !declared.contains(&key.object);
+        if !declared.insert(key.object) {
+            let message = format!("object '{}' is declared twice", object.name);
+            check.report(Severity::Error, object.name, message);
+        } else if constant {
+            let message = format!(
+                "object '{}' is already a constant of the domain",
+                object.name
+            );
+            check.report(Severity::Error, object.name, message);
+        }
+        if let (Some(type_name), Some(hierarchy)) = (object.type_name, &check.hierarchy) {
+            if !types.iter().all(|t| hierarchy.contains(t)) {
+                let message = format!(
+                    "type '{}' of object '{}' is not declared",
+                    type_name, object.name
+                );
+                check.report(Severity::Error, type_name, message);
+            }
+        }
+        check.objects.insert(key.object, types);
+    }
+
+    check.scope = Scope::Init;
+    for fact in problem.init.iter().flatten() {
+        check.check_fact(fact);
+    }
+
+    check.scope = Scope::Goal;
+    if let Some(goal) = &problem.goal {
+        check.visit_formula(goal);
+    }
+    check.diagnostics
+}
@@ -0,0 +1,488 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::{
+    domain::Domain,
+    ground::{GroundAction, GroundTask},
+    problem::Problem,
+    symbol::{AtomId, Id, ObjId},
+};
+
+use self::invariant::Invariant;
+
+/// Synthesizes monotonicity invariants of lifted domains, from which the variables of a [SasTask] are built
+pub mod invariant;
+
+/// Reads and writes tasks in the `output.sas` format of Fast Downward, which its search component takes as input
+///
+/// ## Example
+/// ```rust
+/// use spingus::{domain::parse_domain, problem, sas::{output, SasTask}};
+/// let domain = parse_domain("(define (domain d)
+///     (:predicates (at ?r) (door ?from ?to))
+///     (:action go :parameters (?from ?to)
+///         :precondition (and (at ?from) (door ?from ?to))
+///         :effect (and (not (at ?from)) (at ?to))))").unwrap();
+/// let problem = problem::parse("(define (problem p) (:objects a b) (:init (at a) (door a b)) (:goal (at b)))");
+/// let task = SasTask::new(&domain, &problem).unwrap();
+/// let written = task.to_string();
+/// assert!(written.starts_with("begin_version\n3\nend_version\n"));
+/// assert_eq!(output::parse(&written), Ok(task));
+/// ```
+pub mod output;
+
+/// The value of a variable when none of its atoms are true
+pub const NONE_OF_THOSE: &str = "<none of those>";
+
+/// A variable and one of its values, by their indices
+pub type Fact = (usize, usize);
+
+/// A variable of a [SasTask], with the names of its values
+///
+/// Values are named like in Fast Downward, `Atom at(a, b)` for an atom, and `NegatedAtom at(a, b)` for its negation in binary variables
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Variable {
+    pub name: String,
+    /// The layer of the axioms deriving the variable, if it is derived
+    pub axiom_layer: Option<usize>,
+    pub values: Vec<String>,
+}
+
+/// An effect of an [Operator], setting `variable` to `value` if its conditions hold
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Effect {
+    pub conditions: Vec<Fact>,
+    pub variable: usize,
+    /// The value `variable` must have for the operator to apply, if any
+    pub precondition: Option<usize>,
+    pub value: usize,
+}
+
+/// A ground action over the variables of a [SasTask]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Operator {
+    /// The action written as a plan step without parentheses, such as `go a b`
+    pub name: String,
+    /// The preconditions on variables which the operator does not change
+    pub prevail: Vec<Fact>,
+    pub effects: Vec<Effect>,
+    pub cost: u32,
+}
+
+/// A rule setting the derived `variable` to `value` whenever its conditions hold, from its default value `precondition`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Axiom {
+    pub conditions: Vec<Fact>,
+    pub variable: usize,
+    pub precondition: usize,
+    pub value: usize,
+}
+
+/// A planning task over multi-valued variables, the finite-domain representation of SAS+
+///
+/// Atoms which are mutually exclusive share a variable, with a value for each atom, and a value [NONE_OF_THOSE] unless one of them is always true.
+/// Atoms which share no variable become binary variables.
+///
+/// ## Example
+/// ```rust
+/// use spingus::{domain::parse_domain, problem, sas::SasTask};
+/// let domain = parse_domain("(define (domain d)
+///     (:predicates (at ?r) (door ?from ?to))
+///     (:action go :parameters (?from ?to)
+///         :precondition (and (at ?from) (door ?from ?to))
+///         :effect (and (not (at ?from)) (at ?to))))").unwrap();
+/// let problem = problem::parse("(define (problem p)
+///     (:objects a b c)
+///     (:init (at a) (door a b) (door b c))
+///     (:goal (at c)))");
+/// let task = SasTask::new(&domain, &problem).unwrap();
+/// assert_eq!(task.variables.len(), 1);
+/// assert_eq!(task.variables[0].values, vec!["Atom at(a)", "Atom at(b)", "Atom at(c)"]);
+/// assert_eq!(task.init, vec![0]);
+/// assert_eq!(task.goal, vec![(0, 2)]);
+/// assert_eq!(task.operators[0].name, "go a b");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SasTask {
+    /// Whether operators have costs, or every operator costs one
+    pub metric: bool,
+    pub variables: Vec<Variable>,
+    /// Groups of facts of which at most one is true in any reachable state, besides those of a single variable
+    pub mutexes: Vec<Vec<Fact>>,
+    /// The value of every variable
+    pub init: Vec<usize>,
+    pub goal: Vec<Fact>,
+    pub operators: Vec<Operator>,
+    pub axioms: Vec<Axiom>,
+}
+
+impl SasTask {
+    /// Grounds `domain` and `problem`, and translates the ground task with the invariants synthesized for them
+    pub fn new(domain: &Domain, problem: &Problem) -> Result<Self, String> {
+        let task = GroundTask::new(domain, problem)?;
+        if !task.unreachable_goal.is_empty() {
+            return Err("the goal can never hold".to_owned());
+        }
+        let invariants = invariant::synthesize(domain, problem);
+        Ok(Self::from_ground(&task, &invariants))
+    }
+
+    /// Translates `task`, with a variable for groups of atoms which `invariants` show to be mutually exclusive
+    ///
+    /// Groups are chosen greedily, the largest first, until every atom in two or more uncovered atoms of a group is covered.
+    /// Atoms which preconditions or goals require to be false are kept as binary variables.
+    /// Operators whose preconditions require two values of one variable are dropped, as they never apply
+    pub fn from_ground(task: &GroundTask, invariants: &[Invariant]) -> Self {
+        let groups = mutex_groups(task, invariants);
+        let negated: HashSet<AtomId> = task
+            .actions
+            .iter()
+            .flat_map(|action| &action.negative_precondition)
+            .chain(&task.negative_goal)
+            .copied()
+            .collect();
+
+        let mut covered: HashSet<AtomId> = negated.clone();
+        let mut chosen: Vec<Vec<AtomId>> = Vec::new();
+        loop {
+            let best = groups
+                .iter()
+                .map(|group| -> Vec<AtomId> {
+                    group
+                        .iter()
+                        .filter(|atom| !covered.contains(atom))
+                        .copied()
+                        .collect()
+                })
+                .max_by_key(|group| group.len());
+            match best {
+                Some(group) if group.len() >= 2 => {
+                    covered.extend(&group);
+                    chosen.push(group);
+                }
+                _ => break,
+            }
+        }
+        let grouped: HashSet<AtomId> = chosen.iter().flatten().copied().collect();
+        chosen.extend(
+            (0..task.atoms.len())
+                .map(AtomId::new)
+                .filter(|atom| !grouped.contains(atom))
+                .map(|atom| vec![atom]),
+        );
+
+        let mut translation = Translation {
+            atom_facts: vec![(0, 0); task.atoms.len()],
+            none: Vec::new(),
+        };
+        let mut variables = Vec::new();
+        for (index, group) in chosen.iter().enumerate() {
+            let mut values: Vec<String> = group
+                .iter()
+                .map(|atom| value_name(task, *atom, false))
+                .collect();
+            for (value, atom) in group.iter().enumerate() {
+                translation.atom_facts[atom.index()] = (index, value);
+            }
+            let none = if group.len() == 1 {
+                values.push(value_name(task, group[0], true));
+                Some(1)
+            } else if exactly_one(task, group) {
+                None
+            } else {
+                values.push(NONE_OF_THOSE.to_owned());
+                Some(group.len())
+            };
+            translation.none.push(none);
+            variables.push(Variable {
+                name: format!("var{}", index),
+                axiom_layer: None,
+                values,
+            });
+        }
+
+        let mut init: Vec<usize> = translation
+            .none
+            .iter()
+            .map(|none| none.unwrap_or(0))
+            .collect();
+        for atom in &task.init {
+            let (variable, value) = translation.atom_facts[atom.index()];
+            init[variable] = value;
+        }
+        let mut goal: Vec<Fact> = task
+            .goal
+            .iter()
+            .map(|atom| translation.atom_facts[atom.index()])
+            .chain(
+                task.negative_goal
+                    .iter()
+                    .map(|atom| translation.negation(*atom)),
+            )
+            .collect();
+        goal.sort();
+        goal.dedup();
+
+        let operators = task
+            .actions
+            .iter()
+            .filter_map(|action| translation.operator(task, action))
+            .collect();
+
+        let mut mutexes: Vec<Vec<Fact>> = groups
+            .iter()
+            .map(|group| {
+                let mut facts: Vec<Fact> = group
+                    .iter()
+                    .map(|atom| translation.atom_facts[atom.index()])
+                    .collect();
+                facts.sort();
+                facts
+            })
+            .filter(|facts| facts.iter().any(|fact| fact.0 != facts[0].0))
+            .collect();
+        mutexes.sort();
+        mutexes.dedup();
+
+        SasTask {
+            metric: false,
+            variables,
+            mutexes,
+            init,
+            goal,
+            operators,
+            axioms: vec![],
+        }
+    }
+}
+
+/// The groups of at least two atoms of `task` with the same instantiation of an invariant, of which at most one is true in any reachable state
+pub fn mutex_groups(task: &GroundTask, invariants: &[Invariant]) -> Vec<Vec<AtomId>> {
+    let mut groups: BTreeSet<Vec<AtomId>> = BTreeSet::new();
+    for invariant in invariants {
+        let mut instantiations: BTreeMap<Vec<&ObjId>, Vec<AtomId>> = BTreeMap::new();
+        for (index, fact) in task.atoms.iter().enumerate() {
+            let predicate = task.symbols.predicates.name(fact.predicate);
+            if let Some(parameters) = invariant.parameters(predicate, &fact.args) {
+                instantiations
+                    .entry(parameters)
+                    .or_default()
+                    .push(AtomId::new(index));
+            }
+        }
+        groups.extend(
+            instantiations
+                .into_values()
+                .filter(|group| group.len() >= 2),
+        );
+    }
+    groups.into_iter().collect()
+}
+
+/// Whether exactly one atom of `group` is true in every reachable state, as one is true initially and every action which deletes one adds another
+fn exactly_one(task: &GroundTask, group: &[AtomId]) -> bool {
+    let initially = group.iter().filter(|atom| task.init.contains(atom)).count();
+    initially == 1
+        && task.actions.iter().all(|action| {
+            !action.del.iter().any(|atom| group.contains(atom))
+                || action.add.iter().any(|atom| group.contains(atom))
+        })
+}
+
+fn value_name(task: &GroundTask, atom: AtomId, negated: bool) -> String {
+    let fact = task.symbols.fact(&task.atoms[atom.index()]);
+    format!(
+        "{} {}({})",
+        if negated { "NegatedAtom" } else { "Atom" },
+        fact.predicate,
+        fact.objects.join(", ")
+    )
+}
+
+/// Where the atoms of a [GroundTask] went in a [SasTask]
+struct Translation {
+    /// The fact of every atom, indexed by [AtomId]
+    atom_facts: Vec<Fact>,
+    /// The value of every variable when none of its atoms are true, if it can be
+    none: Vec<Option<usize>>,
+}
+
+impl Translation {
+    /// The fact of `atom` being false, for atoms kept as binary variables
+    fn negation(&self, atom: AtomId) -> Fact {
+        let (variable, _) = self.atom_facts[atom.index()];
+        (variable, 1)
+    }
+
+    fn operator(&self, task: &GroundTask, action: &GroundAction) -> Option<Operator> {
+        let mut precondition: BTreeMap<usize, usize> = BTreeMap::new();
+        let facts = action
+            .precondition
+            .iter()
+            .map(|atom| self.atom_facts[atom.index()])
+            .chain(
+                action
+                    .negative_precondition
+                    .iter()
+                    .map(|atom| self.negation(*atom)),
+            );
+        for (variable, value) in facts {
+            if *precondition.entry(variable).or_insert(value) != value {
+                return None;
+            }
+        }
+
+        let mut effects: BTreeMap<usize, Effect> = BTreeMap::new();
+        for atom in &action.add {
+            let (variable, value) = self.atom_facts[atom.index()];
+            effects.entry(variable).or_insert(Effect {
+                conditions: vec![],
+                variable,
+                precondition: None,
+                value,
+            });
+        }
+        for atom in &action.del {
+            let (variable, value) = self.atom_facts[atom.index()];
+            if effects.contains_key(&variable) {
+                continue;
+            }
+            let Some(none) = self.none[variable] else {
+                continue;
+            };
+            // The atom is only made false if it is true, which the precondition may not tell
+            let conditions = match precondition.get(&variable) {
+                Some(required) if *required == value => vec![],
+                Some(_) => continue,
+                None => vec![(variable, value)],
+            };
+            effects.insert(
+                variable,
+                Effect {
+                    conditions,
+                    variable,
+                    precondition: None,
+                    value: none,
+                },
+            );
+        }
+        effects.retain(|variable, effect| {
+            precondition.get(variable) != Some(&effect.value) || !effect.conditions.is_empty()
+        });
+        for effect in effects.values_mut() {
+            if effect.conditions.is_empty() {
+                effect.precondition = precondition.remove(&effect.variable);
+            }
+        }
+
+        let name = task.action_name(action);
+        Some(Operator {
+            name: name[1..name.len() - 1].to_owned(),
+            prevail: precondition.into_iter().collect(),
+            effects: effects.into_values().collect(),
+            cost: 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{domain::parse_domain, problem};
+
+    #[test]
+    fn gripper() {
+        let domain = parse_domain(
+            "(define (domain gripper)
+                (:predicates (room ?r) (ball ?b) (at-robby ?r) (at ?b ?r) (free ?g) (carry ?b ?g))
+                (:action move :parameters (?from ?to)
+                    :precondition (and (room ?from) (room ?to) (at-robby ?from))
+                    :effect (and (at-robby ?to) (not (at-robby ?from))))
+                (:action pick :parameters (?b ?r ?g)
+                    :precondition (and (ball ?b) (at ?b ?r) (at-robby ?r) (free ?g))
+                    :effect (and (carry ?b ?g) (not (at ?b ?r)) (not (free ?g))))
+                (:action drop :parameters (?b ?r ?g)
+                    :precondition (and (ball ?b) (carry ?b ?g) (at-robby ?r))
+                    :effect (and (at ?b ?r) (free ?g) (not (carry ?b ?g)))))",
+        )
+        .unwrap();
+        let problem = problem::parse(
+            "(define (problem p)
+                (:objects a b ball left)
+                (:init (room a) (room b) (ball ball) (at-robby a) (at ball a) (free left))
+                (:goal (at ball b)))",
+        );
+        let invariants: Vec<String> = invariant::synthesize(&domain, &problem)
+            .iter()
+            .map(|invariant| invariant.to_string())
+            .collect();
+        assert_eq!(
+            invariants,
+            vec![
+                "{(at-robby *)}",
+                "{(at ?0 *), (carry ?0 *)}",
+                "{(carry * ?0), (free ?0)}"
+            ]
+        );
+
+        let task = SasTask::new(&domain, &problem).unwrap();
+        let values: Vec<&Vec<String>> = task.variables.iter().map(|v| &v.values).collect();
+        assert_eq!(
+            values,
+            vec![
+                &vec![
+                    "Atom at(ball, a)",
+                    "Atom carry(ball, left)",
+                    "Atom at(ball, b)"
+                ],
+                &vec!["Atom at-robby(a)", "Atom at-robby(b)"],
+                &vec!["Atom free(left)", "NegatedAtom free(left)"],
+            ]
+        );
+        assert_eq!(task.init, vec![0, 0, 0]);
+        assert_eq!(task.goal, vec![(0, 2)]);
+        assert_eq!(task.mutexes, vec![vec![(0, 1), (2, 0)]]);
+        let pick = task
+            .operators
+            .iter()
+            .find(|operator| operator.name == "pick ball a left")
+            .unwrap();
+        assert_eq!(pick.prevail, vec![(1, 0)]);
+        assert_eq!(
+            pick.effects,
+            vec![
+                Effect {
+                    conditions: vec![],
+                    variable: 0,
+                    precondition: Some(0),
+                    value: 1
+                },
+                Effect {
+                    conditions: vec![],
+                    variable: 2,
+                    precondition: Some(0),
+                    value: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn wrong_arity() {
+        let domain = parse_domain(
+            "(define (domain d)
+                (:predicates (at ?x ?y))
+                (:action go :parameters (?x ?from ?to)
+                    :precondition (at ?x ?from)
+                    :effect (and (not (at ?x ?from)) (at ?x ?to) (at ?to))))",
+        )
+        .unwrap();
+        let problem = problem::parse(
+            "(define (problem p) (:objects a b) (:init (at a) (at a b)) (:goal (at a a)))",
+        );
+        let invariants: Vec<String> = invariant::synthesize(&domain, &problem)
+            .iter()
+            .map(|invariant| invariant.to_string())
+            .collect();
+        assert_eq!(invariants, vec!["{(at ?0 *)}"]);
+    }
+}
@@ -0,0 +1,369 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    fmt,
+};
+
+use crate::{
+    domain::{action::effect::Effect, Domain},
+    formula::{Atom, Formula},
+    owned::OwnedFact,
+    problem::Problem,
+    symbol::lowercase_problem,
+    term::Term,
+    visit::{walk_effect, walk_formula, Visitor},
+};
+
+/// The most candidates [synthesize] checks before giving up on finding more invariants
+pub const MAX_CANDIDATES: usize = 10_000;
+
+/// The atoms of a predicate which an [Invariant] counts, with `order` the arguments bound to the parameters of the invariant
+///
+/// The argument which is not bound, if any, is counted: the invariant holds for all of its values together
+#[derive(Debug, PartialEq, Eq, Clone, Hash, PartialOrd, Ord)]
+pub struct InvariantPart {
+    pub predicate: String,
+    /// The argument bound to each parameter of the invariant, in order
+    pub order: Vec<usize>,
+    pub counted: Option<usize>,
+}
+
+/// A monotonicity invariant: for every instantiation of its parameters, at most one of the atoms matching its parts is true in any reachable state
+///
+/// Written as its parts with parameters `?0`, `?1` and so on, and `*` for counted arguments.
+/// E.g. `{(at ?0 *), (in ?0 *)}` states that every package is at most at one place or in one vehicle
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Invariant {
+    pub parts: Vec<InvariantPart>,
+}
+
+impl Invariant {
+    /// The number of parameters of the invariant
+    pub fn arity(&self) -> usize {
+        self.parts[0].order.len()
+    }
+
+    fn part(&self, predicate: &str) -> Option<&InvariantPart> {
+        self.parts.iter().find(|part| part.predicate == predicate)
+    }
+
+    /// The instantiation of the parameters of the invariant which `predicate` applied to `arguments` belongs to, if it matches a part with as many arguments
+    pub fn parameters<'a, T>(&self, predicate: &str, arguments: &'a [T]) -> Option<Vec<&'a T>> {
+        let part = self.part(predicate)?;
+        if arguments.len() != part.order.len() + usize::from(part.counted.is_some()) {
+            return None;
+        }
+        Some(part.order.iter().map(|index| &arguments[*index]).collect())
+    }
+
+    /// The invariant with its parts sorted, and its parameters renumbered in the order of the arguments of the first part, so that equal invariants are written the same
+    fn canonical(mut self) -> Self {
+        self.parts.sort();
+        let mut renumbering: Vec<usize> = (0..self.arity()).collect();
+        renumbering.sort_by_key(|parameter| self.parts[0].order[*parameter]);
+        for part in &mut self.parts {
+            part.order = renumbering.iter().map(|old| part.order[*old]).collect();
+        }
+        self
+    }
+}
+
+impl fmt::Display for Invariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, part) in self.parts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "({}", part.predicate)?;
+            let arity = part.order.len() + usize::from(part.counted.is_some());
+            for argument in 0..arity {
+                match part.order.iter().position(|index| *index == argument) {
+                    Some(parameter) => write!(f, " ?{}", parameter)?,
+                    None => write!(f, " *")?,
+                }
+            }
+            write!(f, ")")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// An action as the literals an invariant is checked against
+struct Schema<'a> {
+    precondition: Vec<&'a Term>,
+    /// Pairs of arguments the precondition requires to differ
+    distinct: Vec<(&'a str, &'a str)>,
+    add: Vec<&'a Term>,
+    del: Vec<&'a Term>,
+    /// Atoms added by quantified or conditional effects
+    other_add: Vec<&'a Term>,
+    /// Whether the effects being visited are neither quantified nor conditional
+    simple: bool,
+}
+
+impl<'a> Schema<'a> {
+    fn new(precondition: Option<&'a Formula<Term>>, effect: &'a Effect) -> Self {
+        let mut schema = Schema {
+            precondition: Vec::new(),
+            distinct: Vec::new(),
+            add: Vec::new(),
+            del: Vec::new(),
+            other_add: Vec::new(),
+            simple: true,
+        };
+        if let Some(precondition) = precondition {
+            schema.visit_formula(precondition);
+        }
+        schema.visit_effect(effect);
+        schema
+    }
+
+    /// Whether the arguments `a` and `b` can be instantiated with the same object
+    fn may_equal(&self, a: &str, b: &str) -> bool {
+        if a == b {
+            return true;
+        }
+        if !a.starts_with('?') && !b.starts_with('?') {
+            return false;
+        }
+        !self
+            .distinct
+            .iter()
+            .any(|pair| *pair == (a, b) || *pair == (b, a))
+    }
+
+    /// Whether the action keeps `invariant`, or else the invariants which could be kept instead, with a part added to balance an add effect
+    fn check(&self, invariant: &Invariant) -> Result<(), Vec<Invariant>> {
+        if self
+            .other_add
+            .iter()
+            .any(|term| invariant.part(&term.name).is_some())
+        {
+            return Err(vec![]);
+        }
+        for (i, add) in self.add.iter().enumerate() {
+            let Some(parameters) = invariant.parameters(&add.name, &add.parameters) else {
+                continue;
+            };
+            // Two atoms added for the same parameters weigh two, unless they are the same atom
+            for other in &self.add[i + 1..] {
+                if let Some(other_parameters) = invariant.parameters(&other.name, &other.parameters)
+                {
+                    let same = parameters
+                        .iter()
+                        .zip(&other_parameters)
+                        .all(|(a, b)| self.may_equal(a, b));
+                    if same && add != other {
+                        return Err(vec![]);
+                    }
+                }
+            }
+            if self.precondition.contains(add) {
+                continue;
+            }
+            let balanced = self.del.iter().any(|del| {
+                self.precondition.contains(del)
+                    && invariant
+                        .parameters(&del.name, &del.parameters)
+                        .is_some_and(|del_parameters| del_parameters == parameters)
+            });
+            if !balanced {
+                return Err(self.refinements(invariant, &parameters));
+            }
+        }
+        Ok(())
+    }
+
+    /// The invariants with a part for a deleted precondition atom, which would balance adding an atom with `parameters`
+    fn refinements(&self, invariant: &Invariant, parameters: &[&String]) -> Vec<Invariant> {
+        let mut refinements = Vec::new();
+        for del in &self.del {
+            if !self.precondition.contains(del) || invariant.part(&del.name).is_some() {
+                continue;
+            }
+            let arity = del.parameters.len();
+            if arity < parameters.len() || arity > parameters.len() + 1 {
+                continue;
+            }
+            let mut orders: Vec<Vec<usize>> = vec![vec![]];
+            for parameter in parameters {
+                let mut extended = Vec::new();
+                for order in &orders {
+                    for index in 0..arity {
+                        if !order.contains(&index) && &del.parameters[index] == *parameter {
+                            let mut order = order.clone();
+                            order.push(index);
+                            extended.push(order);
+                        }
+                    }
+                }
+                orders = extended;
+            }
+            for order in orders {
+                let counted = (0..arity).find(|index| !order.contains(index));
+                let mut parts = invariant.parts.clone();
+                parts.push(InvariantPart {
+                    predicate: del.name.clone(),
+                    order,
+                    counted,
+                });
+                refinements.push(Invariant { parts }.canonical());
+            }
+        }
+        refinements
+    }
+}
+
+impl<'a> Visitor<'a, Term> for Schema<'a> {
+    /// Collects the literals of the precondition, looking only through conjunctions
+    fn visit_formula(&mut self, formula: &'a Formula<Term>) {
+        match formula {
+            Formula::Atom(term) if !term.is_equality() => self.precondition.push(term),
+            Formula::And(_) => walk_formula(self, formula),
+            Formula::Not(child) => match &**child {
+                Formula::Atom(term) if term.is_equality() && term.parameters.len() == 2 => self
+                    .distinct
+                    .push((&term.parameters[0], &term.parameters[1])),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn visit_effect(&mut self, effect: &'a Effect) {
+        match effect {
+            Effect::Add(term) if self.simple => self.add.push(term),
+            Effect::Add(term) => self.other_add.push(term),
+            Effect::Delete(term) if self.simple => self.del.push(term),
+            Effect::And(_) => walk_effect(self, effect),
+            // Conditions are not part of the precondition
+            Effect::When { effect, .. } | Effect::Forall { effect, .. } => {
+                let simple = std::mem::replace(&mut self.simple, false);
+                self.visit_effect(effect);
+                self.simple = simple;
+            }
+            Effect::Delete(_) | Effect::Numeric { .. } => {}
+        }
+    }
+}
+
+/// Finds monotonicity invariants of `domain`, which hold in the initial state of `problem`, following Helmert's synthesis for the Fast Downward translator
+///
+/// Starts from every fluent predicate with at most one counted argument, and checks that no action can make more than one atom of an invariant true.
+/// An action adding an atom of an invariant must delete another atom of it with the same parameters, which its precondition requires to be true.
+/// If it does not, the invariant is refined with a part for such an atom the action deletes, and checked again.
+/// At most [MAX_CANDIDATES] candidates are checked, and only effects which are neither conditional nor quantified can balance others
+///
+/// ## Example
+/// ```rust
+/// use spingus::{domain::parse_domain, problem, sas::invariant::synthesize};
+/// let domain = parse_domain("(define (domain d)
+///     (:predicates (at ?p ?l) (in ?p ?t) (road ?a ?b))
+///     (:action load :parameters (?p ?t ?l)
+///         :precondition (and (at ?p ?l) (at ?t ?l))
+///         :effect (and (not (at ?p ?l)) (in ?p ?t)))
+///     (:action unload :parameters (?p ?t ?l)
+///         :precondition (and (in ?p ?t) (at ?t ?l))
+///         :effect (and (not (in ?p ?t)) (at ?p ?l)))
+///     (:action drive :parameters (?t ?from ?to)
+///         :precondition (and (at ?t ?from) (road ?from ?to))
+///         :effect (and (not (at ?t ?from)) (at ?t ?to))))").unwrap();
+/// let problem = problem::parse("(define (problem p) (:objects a b p t) (:init (at p a) (at t a) (road a b)) (:goal (at p b)))");
+/// let invariants: Vec<String> = synthesize(&domain, &problem).iter().map(|i| i.to_string()).collect();
+/// assert_eq!(invariants, vec!["{(at ?0 *), (in ?0 *)}"]);
+/// ```
+pub fn synthesize(domain: &Domain, problem: &Problem) -> Vec<Invariant> {
+    let schemas: Vec<Schema> = domain
+        .actions
+        .iter()
+        .map(|action| Schema::new(action.precondition.as_ref(), &action.effect))
+        .collect();
+    let init = lowercase_problem(problem).init.unwrap_or_default();
+
+    let mut queue: VecDeque<Invariant> = VecDeque::new();
+    for (name, kind) in domain.predicate_kinds() {
+        if kind.is_static() {
+            continue;
+        }
+        let Some(predicate) = domain.predicates.iter().find(|p| p.name == name) else {
+            continue;
+        };
+        let arity = predicate.parameters.len();
+        for counted in std::iter::once(None).chain((0..arity).map(Some)) {
+            queue.push_back(Invariant {
+                parts: vec![InvariantPart {
+                    predicate: name.to_owned(),
+                    order: (0..arity).filter(|i| Some(*i) != counted).collect(),
+                    counted,
+                }],
+            });
+        }
+    }
+
+    let mut seen: HashSet<Invariant> = queue.iter().cloned().collect();
+    let mut invariants = Vec::new();
+    let mut checked = 0;
+    while let Some(candidate) = queue.pop_front() {
+        checked += 1;
+        if checked > MAX_CANDIDATES {
+            break;
+        }
+        if !holds_initially(&candidate, &init) {
+            continue;
+        }
+        match schemas
+            .iter()
+            .try_for_each(|schema| schema.check(&candidate))
+        {
+            Ok(()) => invariants.push(candidate),
+            Err(refinements) => {
+                for refinement in refinements {
+                    if seen.insert(refinement.clone()) {
+                        queue.push_back(refinement);
+                    }
+                }
+            }
+        }
+    }
+    // An atom alone never weighs more than one, so such invariants say nothing
+    invariants
+        .retain(|invariant| invariant.parts.len() > 1 || invariant.parts[0].counted.is_some());
+    remove_weaker(invariants)
+}
+
+fn holds_initially(invariant: &Invariant, init: &[OwnedFact]) -> bool {
+    let mut weights: HashMap<Vec<&String>, usize> = HashMap::new();
+    let mut atoms = HashSet::new();
+    for fact in init {
+        if !atoms.insert(fact) {
+            continue;
+        }
+        if let Some(parameters) = invariant.parameters(&fact.predicate, &fact.objects) {
+            let weight = weights.entry(parameters).or_default();
+            *weight += 1;
+            if *weight > 1 {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// `invariants` without those whose parts are a subset of another's with the same parameters, as that one implies them
+fn remove_weaker(invariants: Vec<Invariant>) -> Vec<Invariant> {
+    let sets: Vec<BTreeSet<&InvariantPart>> = invariants
+        .iter()
+        .map(|invariant| invariant.parts.iter().collect())
+        .collect();
+    invariants
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            !sets
+                .iter()
+                .enumerate()
+                .any(|(j, other)| *i != j && sets[*i].is_subset(other) && sets[*i] != *other)
+        })
+        .map(|(_, invariant)| invariant.clone())
+        .collect()
+}
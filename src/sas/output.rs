@@ -0,0 +1,479 @@
+use std::{fmt, str::FromStr};
+
+use super::{Axiom, Effect, Fact, Operator, SasTask, Variable};
+
+/// The version of the format which is read and written
+pub const VERSION: usize = 3;
+
+fn write_fact(f: &mut fmt::Formatter<'_>, (variable, value): &Fact) -> fmt::Result {
+    writeln!(f, "{} {}", variable, value)
+}
+
+fn write_value(value: Option<usize>) -> String {
+    value.map_or_else(|| "-1".to_owned(), |value| value.to_string())
+}
+
+/// Writes the task in the `output.sas` format, with a section for each part of the task
+impl fmt::Display for SasTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "begin_version\n{}\nend_version", VERSION)?;
+        writeln!(f, "begin_metric\n{}\nend_metric", u8::from(self.metric))?;
+
+        writeln!(f, "{}", self.variables.len())?;
+        for variable in &self.variables {
+            writeln!(f, "begin_variable\n{}", variable.name)?;
+            writeln!(f, "{}", write_value(variable.axiom_layer))?;
+            writeln!(f, "{}", variable.values.len())?;
+            for value in &variable.values {
+                writeln!(f, "{}", value)?;
+            }
+            writeln!(f, "end_variable")?;
+        }
+
+        writeln!(f, "{}", self.mutexes.len())?;
+        for mutex in &self.mutexes {
+            writeln!(f, "begin_mutex_group\n{}", mutex.len())?;
+            mutex.iter().try_for_each(|fact| write_fact(f, fact))?;
+            writeln!(f, "end_mutex_group")?;
+        }
+
+        writeln!(f, "begin_state")?;
+        for value in &self.init {
+            writeln!(f, "{}", value)?;
+        }
+        writeln!(f, "end_state")?;
+
+        writeln!(f, "begin_goal\n{}", self.goal.len())?;
+        self.goal.iter().try_for_each(|fact| write_fact(f, fact))?;
+        writeln!(f, "end_goal")?;
+
+        writeln!(f, "{}", self.operators.len())?;
+        for operator in &self.operators {
+            writeln!(f, "begin_operator\n{}", operator.name)?;
+            writeln!(f, "{}", operator.prevail.len())?;
+            operator
+                .prevail
+                .iter()
+                .try_for_each(|fact| write_fact(f, fact))?;
+            writeln!(f, "{}", operator.effects.len())?;
+            for effect in &operator.effects {
+                write!(f, "{}", effect.conditions.len())?;
+                for (variable, value) in &effect.conditions {
+                    write!(f, " {} {}", variable, value)?;
+                }
+                writeln!(
+                    f,
+                    " {} {} {}",
+                    effect.variable,
+                    write_value(effect.precondition),
+                    effect.value
+                )?;
+            }
+            writeln!(f, "{}\nend_operator", operator.cost)?;
+        }
+
+        writeln!(f, "{}", self.axioms.len())?;
+        for axiom in &self.axioms {
+            writeln!(f, "begin_rule\n{}", axiom.conditions.len())?;
+            axiom
+                .conditions
+                .iter()
+                .try_for_each(|fact| write_fact(f, fact))?;
+            writeln!(
+                f,
+                "{} {} {}\nend_rule",
+                axiom.variable, axiom.precondition, axiom.value
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The lines of the input, read one at a time
+struct Reader<'a> {
+    lines: Vec<&'a str>,
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, message: impl fmt::Display) -> String {
+        format!("line {}: {}", self.position, message)
+    }
+
+    fn line(&mut self) -> Result<&'a str, String> {
+        let line = self.lines.get(self.position).copied();
+        self.position += 1;
+        line.map(|line| line.trim_end_matches('\r'))
+            .ok_or_else(|| self.error("unexpected end of input"))
+    }
+
+    fn expect(&mut self, keyword: &str) -> Result<(), String> {
+        match self.line()? {
+            line if line.trim() == keyword => Ok(()),
+            line => Err(self.error(format!("expected '{}', found '{}'", keyword, line))),
+        }
+    }
+
+    /// The numbers of `line`
+    fn split<T: FromStr>(&self, line: &str) -> Result<Vec<T>, String> {
+        line.split_whitespace()
+            .map(|number| {
+                number
+                    .parse()
+                    .map_err(|_| self.error(format!("expected a number, found '{}'", number)))
+            })
+            .collect()
+    }
+
+    fn count<T>(&self, numbers: Vec<T>, count: usize) -> Result<Vec<T>, String> {
+        if numbers.len() != count {
+            return Err(self.error(format!(
+                "expected {} numbers, found {}",
+                count,
+                numbers.len()
+            )));
+        }
+        Ok(numbers)
+    }
+
+    /// The numbers of the next line, which must have `count` of them
+    fn numbers<T: FromStr>(&mut self, count: usize) -> Result<Vec<T>, String> {
+        let line = self.line()?;
+        let numbers = self.split(line)?;
+        self.count(numbers, count)
+    }
+
+    fn number<T: FromStr>(&mut self) -> Result<T, String> {
+        Ok(self.numbers(1)?.remove(0))
+    }
+
+    /// A value, or -1 for none
+    fn value(&self, value: i64) -> Result<Option<usize>, String> {
+        match value {
+            -1 => Ok(None),
+            value => usize::try_from(value)
+                .map(Some)
+                .map_err(|_| self.error(format!("invalid value {}", value))),
+        }
+    }
+
+    fn index(&self, number: i64) -> Result<usize, String> {
+        usize::try_from(number).map_err(|_| self.error(format!("invalid index {}", number)))
+    }
+
+    fn fact(&mut self, variables: &[Variable]) -> Result<Fact, String> {
+        let numbers = self.numbers(2)?;
+        self.check(variables, (numbers[0], numbers[1]))
+    }
+
+    fn facts(&mut self, variables: &[Variable]) -> Result<Vec<Fact>, String> {
+        let count = self.number()?;
+        (0..count).map(|_| self.fact(variables)).collect()
+    }
+
+    /// `fact`, if its variable and value exist
+    fn check(&self, variables: &[Variable], fact: Fact) -> Result<Fact, String> {
+        let Some(variable) = variables.get(fact.0) else {
+            return Err(self.error(format!("variable {} does not exist", fact.0)));
+        };
+        if fact.1 >= variable.values.len() {
+            return Err(self.error(format!("variable {} has no value {}", fact.0, fact.1)));
+        }
+        Ok(fact)
+    }
+
+    fn variable(&mut self) -> Result<Variable, String> {
+        self.expect("begin_variable")?;
+        let name = self.line()?.to_owned();
+        let axiom_layer = self.number()?;
+        let axiom_layer = self.value(axiom_layer)?;
+        let count: usize = self.number()?;
+        let values = (0..count)
+            .map(|_| self.line().map(str::to_owned))
+            .collect::<Result<_, _>>()?;
+        self.expect("end_variable")?;
+        Ok(Variable {
+            name,
+            axiom_layer,
+            values,
+        })
+    }
+
+    fn operator(&mut self, variables: &[Variable]) -> Result<Operator, String> {
+        self.expect("begin_operator")?;
+        let name = self.line()?.to_owned();
+        let prevail = self.facts(variables)?;
+        let count: usize = self.number()?;
+        let mut effects = Vec::new();
+        for _ in 0..count {
+            let line = self.line()?;
+            let numbers: Vec<i64> = self.split(line)?;
+            let conditions = self.index(numbers.first().copied().unwrap_or(-1))?;
+            let length = conditions
+                .checked_mul(2)
+                .and_then(|length| length.checked_add(4))
+                .ok_or_else(|| self.error(format!("too many conditions {}", conditions)))?;
+            let numbers = self.count(numbers, length)?;
+            let conditions = numbers[1..length - 3]
+                .chunks(2)
+                .map(|pair| self.check(variables, (self.index(pair[0])?, self.index(pair[1])?)))
+                .collect::<Result<_, _>>()?;
+            let [variable, precondition, value] = numbers[numbers.len() - 3..] else {
+                unreachable!()
+            };
+            let (variable, value) =
+                self.check(variables, (self.index(variable)?, self.index(value)?))?;
+            let precondition = self.value(precondition)?;
+            if let Some(precondition) = precondition {
+                self.check(variables, (variable, precondition))?;
+            }
+            effects.push(Effect {
+                conditions,
+                variable,
+                precondition,
+                value,
+            });
+        }
+        let cost = self.number()?;
+        self.expect("end_operator")?;
+        Ok(Operator {
+            name,
+            prevail,
+            effects,
+            cost,
+        })
+    }
+
+    fn axiom(&mut self, variables: &[Variable]) -> Result<Axiom, String> {
+        self.expect("begin_rule")?;
+        let conditions = self.facts(variables)?;
+        let numbers: Vec<usize> = self.numbers(3)?;
+        let (variable, precondition) = self.check(variables, (numbers[0], numbers[1]))?;
+        let (_, value) = self.check(variables, (numbers[0], numbers[2]))?;
+        self.expect("end_rule")?;
+        Ok(Axiom {
+            conditions,
+            variable,
+            precondition,
+            value,
+        })
+    }
+}
+
+/// Parses a task in the `output.sas` format
+///
+/// Only version [VERSION] of the format is supported.
+/// Every fact must refer to a variable and value which exist, and errors tell the line they are found on
+pub fn parse(input: &str) -> Result<SasTask, String> {
+    let mut reader = Reader {
+        lines: input.lines().collect(),
+        position: 0,
+    };
+
+    reader.expect("begin_version")?;
+    let version: usize = reader.number()?;
+    if version != VERSION {
+        return Err(reader.error(format!("unsupported version {}", version)));
+    }
+    reader.expect("end_version")?;
+
+    reader.expect("begin_metric")?;
+    let metric = match reader.number::<u8>()? {
+        0 => false,
+        1 => true,
+        metric => return Err(reader.error(format!("invalid metric {}", metric))),
+    };
+    reader.expect("end_metric")?;
+
+    let count: usize = reader.number()?;
+    let variables = (0..count)
+        .map(|_| reader.variable())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let count: usize = reader.number()?;
+    let mut mutexes = Vec::new();
+    for _ in 0..count {
+        reader.expect("begin_mutex_group")?;
+        mutexes.push(reader.facts(&variables)?);
+        reader.expect("end_mutex_group")?;
+    }
+
+    reader.expect("begin_state")?;
+    let mut init = Vec::with_capacity(variables.len());
+    for variable in 0..variables.len() {
+        let value = reader.number()?;
+        init.push(reader.check(&variables, (variable, value))?.1);
+    }
+    reader.expect("end_state")?;
+
+    reader.expect("begin_goal")?;
+    let goal = reader.facts(&variables)?;
+    reader.expect("end_goal")?;
+
+    let count: usize = reader.number()?;
+    let operators = (0..count)
+        .map(|_| reader.operator(&variables))
+        .collect::<Result<_, _>>()?;
+
+    let count: usize = reader.number()?;
+    let axioms = (0..count)
+        .map(|_| reader.axiom(&variables))
+        .collect::<Result<_, _>>()?;
+
+    if let Some(line) = reader.lines[reader.position..]
+        .iter()
+        .find(|line| !line.trim().is_empty())
+    {
+        reader.position += 1;
+        return Err(reader.error(format!("unexpected '{}' after the axioms", line)));
+    }
+
+    Ok(SasTask {
+        metric,
+        variables,
+        mutexes,
+        init,
+        goal,
+        operators,
+        axioms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A task as the Fast Downward translator writes it, with a conditional effect and a derived variable
+    const TASK: &str = "begin_version
+3
+end_version
+begin_metric
+1
+end_metric
+3
+begin_variable
+var0
+-1
+3
+Atom at(ball, a)
+Atom at(ball, b)
+Atom carry(ball)
+end_variable
+begin_variable
+var1
+-1
+2
+Atom at-robby(a)
+Atom at-robby(b)
+end_variable
+begin_variable
+var2
+0
+2
+Atom new-axiom@0()
+NegatedAtom new-axiom@0()
+end_variable
+1
+begin_mutex_group
+2
+0 0
+0 2
+end_mutex_group
+begin_state
+0
+0
+1
+end_state
+begin_goal
+1
+0 1
+end_goal
+2
+begin_operator
+move a b
+0
+2
+0 1 0 1
+1 0 2 0 -1 1
+2
+end_operator
+begin_operator
+pick ball a
+1
+1 0
+1
+0 0 0 2
+1
+end_operator
+1
+begin_rule
+1
+0 2
+2 1 0
+end_rule
+";
+
+    #[test]
+    fn round_trip() {
+        let task = parse(TASK).unwrap();
+        assert!(task.metric);
+        assert_eq!(task.variables[2].axiom_layer, Some(0));
+        assert_eq!(task.variables[2].values[0], "Atom new-axiom@0()");
+        assert_eq!(task.init, vec![0, 0, 1]);
+        assert_eq!(
+            task.operators[0].effects[1],
+            Effect {
+                conditions: vec![(0, 2)],
+                variable: 0,
+                precondition: None,
+                value: 1
+            }
+        );
+        assert_eq!(
+            task.axioms,
+            vec![Axiom {
+                conditions: vec![(0, 2)],
+                variable: 2,
+                precondition: 1,
+                value: 0
+            }]
+        );
+        assert_eq!(task.to_string(), TASK);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |from: &str, to: &str| parse(&TASK.replacen(from, to, 1)).unwrap_err();
+        assert_eq!(
+            error("3\nend_version", "4\nend_version"),
+            "line 2: unsupported version 4"
+        );
+        assert_eq!(
+            error("begin_goal\n1\n0 1", "begin_goal\n1\n0 3"),
+            "line 43: variable 0 has no value 3"
+        );
+        assert_eq!(
+            error("1 0 2 0 -1 1", "1 0 2 0 -1"),
+            "line 51: expected 6 numbers, found 5"
+        );
+        assert_eq!(
+            error("end_rule\n", "end_rule\nend_rule\n"),
+            "line 68: unexpected 'end_rule' after the axioms"
+        );
+        assert_eq!(
+            error(
+                "end_variable\n1\nbegin_mutex",
+                "end_variable\n99999999999999999\nbegin_mutex"
+            ),
+            "line 36: expected 'begin_mutex_group', found 'begin_state'"
+        );
+        assert_eq!(
+            error("1 0 2 0 -1 1", "9223372036854775807 0 2 0 -1 1"),
+            "line 51: too many conditions 9223372036854775807"
+        );
+        assert_eq!(
+            parse("begin_version\n3\n").unwrap_err(),
+            "line 3: unexpected end of input"
+        );
+    }
+}
@@ -0,0 +1,436 @@
+use std::{collections::BTreeSet, fmt};
+
+use crate::{
+    ground::GroundTask,
+    plan::Plan,
+    symbol::{AtomId, Id},
+};
+
+/// Which actions may be taken in the same step of a [SatEncoding]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Semantics {
+    /// At most one action per step
+    #[default]
+    Sequential,
+    /// Any actions which do not interfere, so that they can be taken in any order within the step
+    ///
+    /// Two actions interfere if one deletes an atom which the other requires, adds an atom which the other requires to be false, or deletes an atom which the other adds
+    ForallStep,
+}
+
+/// A formula in conjunctive normal form, with variables numbered from one and literals negative when negated, like in DIMACS
+///
+/// Written in the DIMACS format with [fmt::Display]
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Cnf {
+    pub variables: usize,
+    pub clauses: Vec<Vec<i32>>,
+}
+
+impl Cnf {
+    /// A new variable
+    pub fn fresh(&mut self) -> i32 {
+        self.variables += 1;
+        literal(self.variables)
+    }
+}
+
+/// `variable` as a positive literal, failing for more variables than DIMACS literals can number
+fn literal(variable: usize) -> i32 {
+    i32::try_from(variable).expect("more than i32::MAX variables")
+}
+
+impl fmt::Display for Cnf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "p cnf {} {}", self.variables, self.clauses.len())?;
+        for clause in &self.clauses {
+            for literal in clause {
+                write!(f, "{} ", literal)?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+/// The encoding of a [GroundTask] into a [Cnf], which is satisfiable if and only if the task has a plan of at most `horizon` steps
+///
+/// There is a variable for every atom at every time from 0 to `horizon`, and for every action at every step from 0 to `horizon - 1`.
+/// Actions imply their preconditions at their step and their effects at the next, and explanatory frame axioms require an action to change an atom between times.
+/// Actions which may not share a step by the [Semantics] are excluded with mutex clauses.
+/// Steps may be empty, so plans may be shorter than the horizon
+///
+/// ## Example
+/// ```rust
+/// use spingus::{domain::parse_domain, ground::GroundTask, problem, sat::{SatEncoding, Semantics}};
+/// let domain = parse_domain("(define (domain d)
+///     (:predicates (at ?r) (door ?from ?to))
+///     (:action go :parameters (?from ?to)
+///         :precondition (and (at ?from) (door ?from ?to))
+///         :effect (and (not (at ?from)) (at ?to))))").unwrap();
+/// let problem = problem::parse("(define (problem p) (:objects a b c) (:init (at a) (door a b) (door b c)) (:goal (at c)))");
+/// let task = GroundTask::new(&domain, &problem).unwrap();
+/// let encoding = SatEncoding::new(&task, 2, Semantics::Sequential).unwrap();
+/// assert!(encoding.cnf.to_string().starts_with("p cnf 15 "));
+/// // The model a solver finds: the robot goes from a to b, then from b to c
+/// let model = [encoding.action(0, 0), encoding.action(1, 1)];
+/// assert_eq!(encoding.decode(&model), vec![("go", vec!["a", "b"]), ("go", vec!["b", "c"])]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SatEncoding<'a> {
+    task: &'a GroundTask,
+    pub horizon: usize,
+    pub semantics: Semantics,
+    pub cnf: Cnf,
+}
+
+impl<'a> SatEncoding<'a> {
+    /// Encodes `task` with `horizon` steps
+    ///
+    /// Fails if the encoding would need more variables than DIMACS literals can number, see [SatEncoding::with_mutexes]
+    pub fn new(task: &'a GroundTask, horizon: usize, semantics: Semantics) -> Result<Self, String> {
+        Self::with_mutexes(task, horizon, semantics, &[])
+    }
+
+    /// Encodes `task` like [SatEncoding::new], with clauses which keep at most one atom of each group in `mutexes` true at every time
+    ///
+    /// The clauses are implied by the others when the groups are mutually exclusive, such as those of [crate::sas::mutex_groups], but can help solvers
+    ///
+    /// Fails if the atoms and actions over the horizon, with the counters of [Semantics::Sequential], need more than `i32::MAX` variables, the most DIMACS can number
+    pub fn with_mutexes(
+        task: &'a GroundTask,
+        horizon: usize,
+        semantics: Semantics,
+        mutexes: &[Vec<AtomId>],
+    ) -> Result<Self, String> {
+        let counters = match semantics {
+            Semantics::Sequential => task.actions.len().saturating_sub(1),
+            Semantics::ForallStep => 0,
+        };
+        let count = |per_step: usize| {
+            let atoms = horizon.checked_add(1)?.checked_mul(task.atoms.len())?;
+            atoms.checked_add(horizon.checked_mul(per_step)?)
+        };
+        let too_many = || {
+            format!(
+                "encoding {} steps needs more than {} variables",
+                horizon,
+                i32::MAX
+            )
+        };
+        task.actions
+            .len()
+            .checked_add(counters)
+            .and_then(count)
+            .filter(|total| i32::try_from(*total).is_ok())
+            .ok_or_else(too_many)?;
+        let mut encoding = SatEncoding {
+            task,
+            horizon,
+            semantics,
+            cnf: Cnf {
+                variables: count(task.actions.len()).ok_or_else(too_many)?,
+                clauses: Vec::new(),
+            },
+        };
+        encoding.encode(mutexes);
+        Ok(encoding)
+    }
+
+    /// The variable of `atom` at `time`
+    pub fn atom(&self, atom: AtomId, time: usize) -> i32 {
+        literal(time * self.task.atoms.len() + atom.index() + 1)
+    }
+
+    /// The variable of the action at `index` in [GroundTask::actions] taken in `step`
+    pub fn action(&self, index: usize, step: usize) -> i32 {
+        literal(
+            (self.horizon + 1) * self.task.atoms.len() + step * self.task.actions.len() + index + 1,
+        )
+    }
+
+    fn encode(&mut self, mutexes: &[Vec<AtomId>]) {
+        let task = self.task;
+        let mut clauses = Vec::new();
+        for atom in (0..task.atoms.len()).map(AtomId::new) {
+            let literal = self.atom(atom, 0);
+            clauses.push(vec![if task.init.contains(&atom) {
+                literal
+            } else {
+                -literal
+            }]);
+        }
+        for atom in &task.goal {
+            clauses.push(vec![self.atom(*atom, self.horizon)]);
+        }
+        for atom in &task.negative_goal {
+            clauses.push(vec![-self.atom(*atom, self.horizon)]);
+        }
+
+        // The actions which add and delete every atom, for the frame axioms
+        let mut adders = vec![Vec::new(); task.atoms.len()];
+        let mut deleters = vec![Vec::new(); task.atoms.len()];
+        for (index, action) in task.actions.iter().enumerate() {
+            action
+                .add
+                .iter()
+                .for_each(|atom| adders[atom.index()].push(index));
+            action
+                .del
+                .iter()
+                .for_each(|atom| deleters[atom.index()].push(index));
+        }
+
+        for step in 0..self.horizon {
+            for (index, action) in task.actions.iter().enumerate() {
+                let taken = -self.action(index, step);
+                for atom in &action.precondition {
+                    clauses.push(vec![taken, self.atom(*atom, step)]);
+                }
+                for atom in &action.negative_precondition {
+                    clauses.push(vec![taken, -self.atom(*atom, step)]);
+                }
+                for atom in &action.add {
+                    clauses.push(vec![taken, self.atom(*atom, step + 1)]);
+                }
+                for atom in &action.del {
+                    clauses.push(vec![taken, -self.atom(*atom, step + 1)]);
+                }
+            }
+            for atom in (0..task.atoms.len()).map(AtomId::new) {
+                let (before, after) = (self.atom(atom, step), self.atom(atom, step + 1));
+                let mut added = vec![before, -after];
+                added.extend(adders[atom.index()].iter().map(|i| self.action(*i, step)));
+                clauses.push(added);
+                let mut deleted = vec![-before, after];
+                deleted.extend(deleters[atom.index()].iter().map(|i| self.action(*i, step)));
+                clauses.push(deleted);
+            }
+        }
+
+        for time in 1..=self.horizon {
+            for group in mutexes {
+                for (i, atom) in group.iter().enumerate() {
+                    for other in &group[i + 1..] {
+                        clauses.push(vec![-self.atom(*atom, time), -self.atom(*other, time)]);
+                    }
+                }
+            }
+        }
+
+        match self.semantics {
+            Semantics::Sequential => {
+                for step in 0..self.horizon {
+                    let actions: Vec<i32> = (0..task.actions.len())
+                        .map(|index| self.action(index, step))
+                        .collect();
+                    self.at_most_one(&actions, &mut clauses);
+                }
+            }
+            Semantics::ForallStep => {
+                let interfering = interfering(task, &adders, &deleters);
+                for step in 0..self.horizon {
+                    for (a, b) in &interfering {
+                        clauses.push(vec![-self.action(*a, step), -self.action(*b, step)]);
+                    }
+                }
+            }
+        }
+        self.cnf.clauses = clauses;
+    }
+
+    /// Clauses which keep at most one of `literals` true, with the sequential counter of Sinz so that they grow linearly
+    fn at_most_one(&mut self, literals: &[i32], clauses: &mut Vec<Vec<i32>>) {
+        let Some((last, literals)) = literals.split_last() else {
+            return;
+        };
+        // Whether any of the literals so far is true
+        let mut any: Option<i32> = None;
+        for literal in literals {
+            let counter = self.cnf.fresh();
+            clauses.push(vec![-literal, counter]);
+            if let Some(any) = any {
+                clauses.push(vec![-any, counter]);
+                clauses.push(vec![-literal, -any]);
+            }
+            any = Some(counter);
+        }
+        if let Some(any) = any {
+            clauses.push(vec![-last, -any]);
+        }
+    }
+
+    /// The plan of a satisfying assignment, given as the literals which are true, such as the values a SAT solver prints
+    ///
+    /// Actions taken in the same step are ordered as in [GroundTask::actions]
+    pub fn decode(&self, model: &[i32]) -> Plan<'a> {
+        let symbols = &self.task.symbols;
+        let model: BTreeSet<i32> = model.iter().copied().collect();
+        let mut plan = Vec::new();
+        for step in 0..self.horizon {
+            for (index, action) in self.task.actions.iter().enumerate() {
+                if model.contains(&self.action(index, step)) {
+                    plan.push((
+                        symbols.actions.name(action.action),
+                        action
+                            .arguments
+                            .iter()
+                            .map(|object| symbols.objects.name(*object))
+                            .collect(),
+                    ));
+                }
+            }
+        }
+        plan
+    }
+}
+
+/// The pairs of actions which interfere, by their indices with the lower first
+fn interfering(
+    task: &GroundTask,
+    adders: &[Vec<usize>],
+    deleters: &[Vec<usize>],
+) -> BTreeSet<(usize, usize)> {
+    let mut requirers = vec![Vec::new(); task.atoms.len()];
+    let mut excluders = vec![Vec::new(); task.atoms.len()];
+    for (index, action) in task.actions.iter().enumerate() {
+        action
+            .precondition
+            .iter()
+            .for_each(|atom| requirers[atom.index()].push(index));
+        action
+            .negative_precondition
+            .iter()
+            .for_each(|atom| excluders[atom.index()].push(index));
+    }
+    let mut pairs = BTreeSet::new();
+    for atom in 0..task.atoms.len() {
+        let conflicts = [
+            (&deleters[atom], &requirers[atom]),
+            (&adders[atom], &excluders[atom]),
+            (&deleters[atom], &adders[atom]),
+        ];
+        for (these, those) in conflicts {
+            for a in these {
+                for b in those {
+                    if a != b {
+                        pairs.insert((*a.min(b), *a.max(b)));
+                    }
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// Parses the output of a SAT solver into the literals which are true, or `None` if the formula is unsatisfiable
+///
+/// Takes both the output of the SAT competitions, with the lines `s SATISFIABLE` and `v 1 -2 0`, and that of MiniSat, `SAT` followed by the literals.
+/// Comment lines starting with `c` are skipped
+///
+/// ## Example
+/// ```rust
+/// use spingus::sat::parse_model;
+/// assert_eq!(parse_model("c a comment\ns SATISFIABLE\nv 1 -2\nv 3 0\n"), Ok(Some(vec![1, -2, 3])));
+/// assert_eq!(parse_model("UNSAT\n"), Ok(None));
+/// ```
+pub fn parse_model(output: &str) -> Result<Option<Vec<i32>>, String> {
+    let mut satisfiable = None;
+    let mut model = Vec::new();
+    for line in output.lines().map(str::trim) {
+        let literals = match line.split_once(' ').unwrap_or((line, "")) {
+            ("c", _) | ("", _) => continue,
+            ("s", "SATISFIABLE") | ("SAT", "") => {
+                satisfiable = Some(true);
+                continue;
+            }
+            ("s", "UNSATISFIABLE") | ("UNSAT", "") => {
+                satisfiable = Some(false);
+                continue;
+            }
+            ("v", literals) => literals,
+            _ if satisfiable == Some(true) => line,
+            _ => return Err(format!("unexpected line '{}'", line)),
+        };
+        for literal in literals.split_whitespace() {
+            match literal.parse::<i32>() {
+                Ok(0) => {}
+                Ok(literal) => model.push(literal),
+                Err(_) => return Err(format!("invalid literal '{}'", literal)),
+            }
+        }
+    }
+    match satisfiable {
+        Some(true) => Ok(Some(model)),
+        Some(false) => Ok(None),
+        None => Err("the output tells neither SAT nor UNSAT".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{domain::parse_domain, problem};
+
+    #[test]
+    fn clauses() {
+        let domain = parse_domain(
+            "(define (domain d)
+                (:predicates (p) (q) (r))
+                (:action a :parameters () :precondition (p) :effect (and (q) (not (p))))
+                (:action b :parameters () :precondition (p) :effect (r)))",
+        )
+        .unwrap();
+        let problem = problem::parse("(define (problem p) (:init (p)) (:goal (and (q) (r))))");
+        let task = GroundTask::new(&domain, &problem).unwrap();
+        assert_eq!(task.atoms.len(), 3);
+
+        let sequential = SatEncoding::new(&task, 1, Semantics::Sequential).unwrap();
+        // Atoms at two times, actions at one step, and one counter
+        assert_eq!(sequential.cnf.variables, 3 * 2 + 2 + 1);
+        let (a, b) = (sequential.action(0, 0), sequential.action(1, 0));
+        assert!(sequential.cnf.clauses.contains(&vec![-b, -9]));
+        assert!(sequential.cnf.clauses.contains(&vec![-a, 9]));
+
+        // a deletes the precondition of b, so they interfere
+        let parallel = SatEncoding::new(&task, 1, Semantics::ForallStep).unwrap();
+        assert_eq!(parallel.cnf.variables, 3 * 2 + 2);
+        assert!(parallel.cnf.clauses.contains(&vec![-a, -b]));
+
+        let dimacs = parallel.cnf.to_string();
+        assert!(dimacs.starts_with(&format!("p cnf 8 {}\n", parallel.cnf.clauses.len())));
+        assert!(dimacs.ends_with(&format!("{} {} 0\n", -a, -b)));
+
+        // 3 atoms at 2^30 + 1 times are too many
+        let horizon = 1 << 30;
+        assert_eq!(
+            SatEncoding::new(&task, horizon, Semantics::ForallStep).map(|_| ()),
+            Err(format!(
+                "encoding {} steps needs more than 2147483647 variables",
+                horizon
+            ))
+        );
+        assert!(SatEncoding::new(&task, usize::MAX, Semantics::ForallStep).is_err());
+        // 6 variables per step and 3 more at the end, as the sequential semantics adds a counter to the 2 actions
+        let horizon = (i32::MAX as usize - 3) / 6 + 1;
+        assert!(SatEncoding::new(&task, horizon, Semantics::Sequential).is_err());
+    }
+
+    #[test]
+    fn models() {
+        assert_eq!(parse_model("SAT\n1 2 -3 0\n"), Ok(Some(vec![1, 2, -3])));
+        assert_eq!(parse_model("s UNSATISFIABLE\n"), Ok(None));
+        assert_eq!(
+            parse_model("v 1 0\n"),
+            Err("the output tells neither SAT nor UNSAT".to_owned())
+        );
+        assert_eq!(
+            parse_model("1 0\n"),
+            Err("unexpected line '1 0'".to_owned())
+        );
+        assert_eq!(
+            parse_model("s SATISFIABLE\nv 1 x 0\n"),
+            Err("invalid literal 'x'".to_owned())
+        );
+    }
+}
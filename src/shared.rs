@@ -1,15 +1,75 @@
 use std::ops::Range;
 
+use nom::bytes::complete::is_not;
 use nom::bytes::complete::tag_no_case;
-use nom::{bytes::complete::is_not, IResult};
+use nom::error::ErrorKind;
 use nom::{
-    bytes::complete::tag, character::complete::multispace0, combinator::not, sequence::delimited,
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, multispace0, multispace1},
+    combinator::{not, opt, peek, recognize},
+    sequence::{delimited, pair, preceded, terminated},
 };
 
 pub type Error = (String, Range<usize>);
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The error of the domain parser, which can explain why the input is invalid
+#[derive(Debug, PartialEq)]
+pub struct ParseError<I> {
+    pub input: I,
+    pub kind: ErrorKind,
+    pub message: Option<String>,
+}
+
+impl<I> nom::error::ParseError<I> for ParseError<I> {
+    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+        Self {
+            input,
+            kind,
+            message: None,
+        }
+    }
+
+    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+pub type IResult<I, O> = nom::IResult<I, O, ParseError<I>>;
+
+/// Fails without backtracking, explaining why `input` is invalid
+pub(crate) fn invalid<I, O>(input: I, message: String) -> IResult<I, O> {
+    Err(nom::Err::Failure(ParseError {
+        input,
+        kind: ErrorKind::Verify,
+        message: Some(message),
+    }))
+}
+
+/// Renders a parser error, quoting the start of the input it failed at
+pub(crate) fn error_message(err: nom::Err<ParseError<&str>>) -> String {
+    match err {
+        nom::Err::Error(ParseError {
+            input,
+            message: Some(message),
+            ..
+        })
+        | nom::Err::Failure(ParseError {
+            input,
+            message: Some(message),
+            ..
+        }) => {
+            let excerpt = input.trim_start().lines().next().unwrap_or_default();
+            format!("{} at '{}'", message, excerpt)
+        }
+        err => err
+            .map(|err| nom::error::Error::new(err.input, err.kind))
+            .to_string(),
+    }
+}
+
 pub(crate) fn line_num(str: &str, index: usize) -> usize {
     let mut acc = 0;
     for (i, line) in str.lines().enumerate() {
@@ -37,9 +97,9 @@ pub fn remove_comments(input: &str) -> String {
     out
 }
 
-pub fn spaced<F, I, O, E>(f: F) -> impl FnMut(I) -> IResult<I, O, E>
+pub fn spaced<F, I, O, E>(f: F) -> impl FnMut(I) -> nom::IResult<I, O, E>
 where
-    F: FnMut(I) -> IResult<I, O, E>,
+    F: FnMut(I) -> nom::IResult<I, O, E>,
     I: nom::InputTakeAtPosition,
     <I as nom::InputTakeAtPosition>::Item: nom::AsChar + Clone,
     E: nom::error::ParseError<I>,
@@ -48,11 +108,12 @@ where
 }
 
 pub fn named(input: &str) -> IResult<&str, String> {
-    let (remainder, name) = is_not(" \t\r\n():")(input)?;
+    // A '?' starts a variable, so "(at?x)" is the predicate "at" applied to "?x"
+    let (remainder, name) = recognize(pair(opt(char('?')), is_not(" \t\r\n():?")))(input)?;
     not(tag("-"))(name)?;
     not(tag("="))(name)?;
     not(tag_no_case("and"))(name)?;
-    return Ok((remainder, name.to_lowercase().to_owned()));
+    Ok((remainder, name.to_lowercase().to_owned()))
 }
 
 #[test]
@@ -65,3 +126,24 @@ fn remove_comments_test() {
     assert_eq!("\n123", remove_comments(";abc\n123"));
     assert_eq!("0\n123", remove_comments("0;abc\n123"));
 }
+
+#[test]
+fn named_test() {
+    assert_eq!(named("Ab-c d"), Ok((" d", "ab-c".to_owned())));
+    assert_eq!(named("?x)"), Ok((")", "?x".to_owned())));
+    // A '?' ends a name, so that "(aircraft?a)" is an atom, as written in some IPC domains
+    assert_eq!(named("aircraft?a)"), Ok(("?a)", "aircraft".to_owned())));
+    assert!(named("and").is_err());
+    assert!(named("- t").is_err());
+}
+
+/// Matches `word`, as long as it is not just the start of a longer name
+pub(crate) fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    preceded(
+        multispace0,
+        terminated(
+            tag_no_case(word),
+            peek(alt((multispace1, tag("("), tag(")")))),
+        ),
+    )
+}
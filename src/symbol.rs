@@ -0,0 +1,545 @@
+use std::collections::HashMap;
+
+use smallvec::SmallVec;
+
+use crate::{
+    domain::{
+        action::{effect::Effect, Action},
+        parameter::Parameter,
+        predicate::Predicate,
+        types::Type,
+        Domain,
+    },
+    formula::Formula,
+    owned::{IntoOwned, OwnedObject, OwnedProblem},
+    problem::{init::Fact, objects::Object, Problem},
+    term::Term,
+};
+
+/// A compact index of an interned name, see [Interner]
+pub trait Id: Copy {
+    fn new(index: usize) -> Self;
+
+    fn index(self) -> usize;
+}
+
+macro_rules! id {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct $name(pub u32);
+
+        impl Id for $name {
+            fn new(index: usize) -> Self {
+                $name(u32::try_from(index).expect("more than u32::MAX symbols"))
+            }
+
+            fn index(self) -> usize {
+                self.0 as usize
+            }
+        }
+    };
+}
+pub(crate) use id;
+
+id!(
+    /// The ID of a predicate, including the equality predicate `=`
+    PredId
+);
+id!(
+    /// The ID of an object or constant
+    ObjId
+);
+id!(
+    /// The ID of a type
+    TypeId
+);
+id!(
+    /// The ID of an action
+    ActionId
+);
+id!(
+    /// The ID of a variable name, such as `?x`
+    VarId
+);
+id!(
+    /// The ID of a ground atom of a [crate::ground::GroundTask]
+    AtomId
+);
+
+/// Assigns each distinct name an ID of type `I`, numbered from 0 in order of first occurrence
+#[derive(Debug, Clone)]
+pub struct Interner<I> {
+    names: Vec<String>,
+    ids: HashMap<String, I>,
+}
+
+impl<I> Default for Interner<I> {
+    fn default() -> Self {
+        Interner {
+            names: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl<I: Id> Interner<I> {
+    /// The ID of `name`, which is assigned a new one if it has not been seen before
+    pub fn intern(&mut self, name: &str) -> I {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = I::new(self.names.len());
+        self.names.push(name.to_owned());
+        self.ids.insert(name.to_owned(), id);
+        id
+    }
+
+    /// The ID of `name`, if it has been interned
+    pub fn get(&self, name: &str) -> Option<I> {
+        self.ids.get(name).copied()
+    }
+
+    /// The name of `id`
+    ///
+    /// Panics if `id` was not given out by this interner
+    pub fn name(&self, id: I) -> &str {
+        &self.names[id.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Every interned name with its ID, in order of IDs
+    pub fn iter(&self) -> impl Iterator<Item = (I, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (I::new(index), name.as_str()))
+    }
+}
+
+/// The types of an object or parameter, empty when untyped and with several for `(either ...)`
+pub type TypeIds = SmallVec<[TypeId; 1]>;
+
+/// An argument of an [IdTerm], either a variable of the action or a constant
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IdArg {
+    Variable(VarId),
+    Object(ObjId),
+}
+
+/// A [Term] by IDs
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdTerm {
+    pub predicate: PredId,
+    pub args: SmallVec<[IdArg; 4]>,
+}
+
+/// A [Fact] by IDs
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdFact {
+    pub predicate: PredId,
+    pub args: SmallVec<[ObjId; 4]>,
+}
+
+/// A [Parameter] by IDs
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdParameter {
+    pub variable: VarId,
+    pub types: TypeIds,
+}
+
+/// An [Object], or a constant of a domain, by IDs
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdObject {
+    pub object: ObjId,
+    pub types: TypeIds,
+}
+
+/// A [Type] by IDs
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdType {
+    pub type_id: TypeId,
+    pub sub_types: Vec<TypeId>,
+}
+
+/// A [Predicate] by IDs
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdPredicate {
+    pub predicate: PredId,
+    pub parameters: Vec<IdParameter>,
+}
+
+/// An [Action] by IDs
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdAction {
+    pub action: ActionId,
+    pub parameters: Vec<IdParameter>,
+    pub precondition: Option<Formula<IdTerm>>,
+    pub effect: Effect<IdTerm, IdParameter>,
+}
+
+/// A [Domain] by IDs, without its name and requirements
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdDomain {
+    pub types: Vec<IdType>,
+    pub constants: Vec<IdObject>,
+    pub predicates: Vec<IdPredicate>,
+    pub actions: Vec<IdAction>,
+}
+
+/// A [Problem] by IDs, without its name and domain
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdProblem {
+    pub objects: Vec<IdObject>,
+    pub init: Vec<IdFact>,
+    pub goal: Option<Formula<IdFact>>,
+}
+
+/// Interns the names of domains and problems, with a separate [Interner] for each kind of name
+///
+/// Interning a domain and then its problems with the same table gives them shared IDs, so that constants and objects, or predicates of the domain and facts of the problem, compare as integers.
+/// Names are lowercased, so that the names of a problem match those of its domain: the domain parser lowercases the names of domains, and interning those of problems.
+///
+/// ## Example
+/// ```rust
+/// use spingus::symbol::SymbolTable;
+/// let mut table = SymbolTable::default();
+/// let problem = table
+///     .parse_problem("(define (problem p) (:objects a b) (:init (on a b) (on b a)))")
+///     .unwrap();
+/// let on = table.predicates.get("on").unwrap();
+/// assert!(problem.init.iter().all(|fact| fact.predicate == on));
+/// assert_eq!(problem.init[0].args[0], problem.init[1].args[1]);
+/// assert_eq!(table.fact(&problem.init[1]).to_string(), "(on b a)");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    pub predicates: Interner<PredId>,
+    pub objects: Interner<ObjId>,
+    pub types: Interner<TypeId>,
+    pub actions: Interner<ActionId>,
+    pub variables: Interner<VarId>,
+}
+
+impl SymbolTable {
+    /// Parses a problem with [crate::problem::try_parse] and then interns it with [SymbolTable::intern_problem], reporting errors like the former
+    ///
+    /// The parser keeps the names of problems as written, and interning lowercases them.
+    pub fn parse_problem(&mut self, input: &str) -> crate::shared::Result<IdProblem> {
+        crate::problem::try_parse(input).map(|problem| self.intern_problem(&problem))
+    }
+
+    /// Parses a domain and interns it, reporting errors like [crate::domain::parse_domain]
+    pub fn parse_domain(&mut self, input: &str) -> Result<IdDomain, String> {
+        crate::domain::parse_domain(input).map(|domain| self.intern_domain(&domain))
+    }
+
+    /// Interns the objects and facts of `problem`, see [SymbolTable::intern_object] and [SymbolTable::intern_fact]
+    ///
+    /// Variables of quantified goals are interned as objects.
+    pub fn intern_problem(&mut self, problem: &Problem) -> IdProblem {
+        IdProblem {
+            objects: problem
+                .objects
+                .iter()
+                .flatten()
+                .map(|object| self.intern_object(object))
+                .collect(),
+            init: problem
+                .init
+                .iter()
+                .flatten()
+                .map(|fact| self.intern_fact(fact))
+                .collect(),
+            goal: problem
+                .goal
+                .clone()
+                .map(|goal| goal.map(&mut |fact| self.intern_fact(&fact))),
+        }
+    }
+
+    pub fn intern_domain(&mut self, domain: &Domain) -> IdDomain {
+        IdDomain {
+            types: domain
+                .types
+                .iter()
+                .flatten()
+                .map(|t| self.intern_type(t))
+                .collect(),
+            constants: domain
+                .constants
+                .iter()
+                .flatten()
+                .map(|constant| IdObject {
+                    object: self.objects.intern(constant.name()),
+                    types: self.intern_types(constant),
+                })
+                .collect(),
+            predicates: domain
+                .predicates
+                .iter()
+                .map(|predicate| self.intern_predicate(predicate))
+                .collect(),
+            actions: domain
+                .actions
+                .iter()
+                .map(|action| self.intern_action(action))
+                .collect(),
+        }
+    }
+
+    /// Interns `object` with its name and type lowercased
+    pub fn intern_object(&mut self, object: &Object) -> IdObject {
+        IdObject {
+            object: self.objects.intern(&object.name.to_lowercase()),
+            types: object
+                .type_name
+                .map(|type_name| self.types.intern(&type_name.to_lowercase()))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Interns `fact` with its names lowercased
+    pub fn intern_fact(&mut self, fact: &Fact) -> IdFact {
+        IdFact {
+            predicate: self.predicates.intern(&fact.predicate.to_lowercase()),
+            args: fact
+                .objects
+                .iter()
+                .map(|object| self.objects.intern(&object.to_lowercase()))
+                .collect(),
+        }
+    }
+
+    pub fn intern_term(&mut self, term: &Term) -> IdTerm {
+        IdTerm {
+            predicate: self.predicates.intern(&term.name),
+            args: term
+                .parameters
+                .iter()
+                .map(|arg| match arg.starts_with('?') {
+                    true => IdArg::Variable(self.variables.intern(arg)),
+                    false => IdArg::Object(self.objects.intern(arg)),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn intern_parameter(&mut self, parameter: &Parameter) -> IdParameter {
+        IdParameter {
+            variable: self.variables.intern(parameter.name()),
+            types: self.intern_types(parameter),
+        }
+    }
+
+    fn intern_types(&mut self, parameter: &Parameter) -> TypeIds {
+        match parameter {
+            Parameter::Untyped { .. } => TypeIds::new(),
+            Parameter::Typed { type_name, .. } => [self.types.intern(type_name)].into(),
+            Parameter::Either { type_names, .. } => type_names
+                .iter()
+                .map(|type_name| self.types.intern(type_name))
+                .collect(),
+        }
+    }
+
+    fn intern_type(&mut self, t: &Type) -> IdType {
+        IdType {
+            type_id: self.types.intern(&t.name),
+            sub_types: t
+                .sub_types
+                .iter()
+                .map(|sub_type| self.types.intern(sub_type))
+                .collect(),
+        }
+    }
+
+    fn intern_predicate(&mut self, predicate: &Predicate) -> IdPredicate {
+        IdPredicate {
+            predicate: self.predicates.intern(&predicate.name),
+            parameters: predicate
+                .parameters
+                .iter()
+                .map(|parameter| self.intern_parameter(parameter))
+                .collect(),
+        }
+    }
+
+    fn intern_action(&mut self, action: &Action) -> IdAction {
+        IdAction {
+            action: self.actions.intern(&action.name),
+            parameters: action
+                .parameters
+                .iter()
+                .map(|parameter| self.intern_parameter(parameter))
+                .collect(),
+            precondition: action
+                .precondition
+                .clone()
+                .map(|precondition| precondition.map(&mut |term| self.intern_term(&term))),
+            effect: action
+                .effect
+                .clone()
+                .map(&mut |term| self.intern_term(&term), &mut |p| p)
+                .map(&mut |term| term, &mut |p| self.intern_parameter(&p)),
+        }
+    }
+
+    /// The [Fact] with the names of `fact`
+    pub fn fact(&self, fact: &IdFact) -> Fact<'_> {
+        Fact {
+            predicate: self.predicates.name(fact.predicate),
+            objects: fact
+                .args
+                .iter()
+                .map(|object| self.objects.name(*object))
+                .collect(),
+        }
+    }
+
+    /// The [Term] with the names of `term`
+    pub fn term(&self, term: &IdTerm) -> Term {
+        Term {
+            name: self.predicates.name(term.predicate).to_owned(),
+            parameters: term
+                .args
+                .iter()
+                .map(|arg| match arg {
+                    IdArg::Variable(variable) => self.variables.name(*variable).to_owned(),
+                    IdArg::Object(object) => self.objects.name(*object).to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `problem` with the names of its objects and facts lowercased by interning them, see [SymbolTable]
+///
+/// Its name and the name of its domain are kept as they are.
+pub fn lowercase_problem(problem: &Problem) -> OwnedProblem {
+    let mut table = SymbolTable::default();
+    let interned = table.intern_problem(problem);
+    OwnedProblem {
+        name: problem.name.map(str::to_owned),
+        domain: problem.domain.map(str::to_owned),
+        objects: problem.objects.as_ref().map(|_| {
+            interned
+                .objects
+                .iter()
+                .map(|object| OwnedObject {
+                    name: table.objects.name(object.object).to_owned(),
+                    type_name: object
+                        .types
+                        .first()
+                        .map(|type_id| table.types.name(*type_id).to_owned()),
+                })
+                .collect()
+        }),
+        init: problem.init.as_ref().map(|_| {
+            interned
+                .init
+                .iter()
+                .map(|fact| table.fact(fact).into_owned())
+                .collect()
+        }),
+        goal: interned
+            .goal
+            .map(|goal| goal.map(&mut |fact| table.fact(&fact).into_owned())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lowercase_problem, IdArg, SymbolTable};
+    use crate::{domain::action::effect::Effect, formula::Formula};
+
+    #[test]
+    fn shared_ids() {
+        let mut table = SymbolTable::default();
+        let domain = table
+            .parse_domain(
+                "(define (domain d)
+                    (:types block)
+                    (:constants table - block)
+                    (:predicates (on ?x ?y - block) (clear ?x))
+                    (:action stack
+                        :parameters (?x ?y - block)
+                        :precondition (and (clear ?y) (not (= ?x ?y)))
+                        :effect (and (on ?x ?y) (not (on ?x table))
+                            (forall (?z - block) (when (on ?z ?y) (not (clear ?z)))))))",
+            )
+            .unwrap();
+        let problem = table
+            .parse_problem(
+                "(define (problem p) (:objects a - block) (:init (on a table)) (:goal (clear a)))",
+            )
+            .unwrap();
+
+        let on = table.predicates.get("on").unwrap();
+        let block = table.types.get("block").unwrap();
+        assert_eq!(domain.predicates[0].predicate, on);
+        assert_eq!(problem.init[0].predicate, on);
+        assert_eq!(domain.constants[0].object, problem.init[0].args[1]);
+        assert_eq!(problem.objects[0].types.as_slice(), &[block]);
+        assert_eq!(table.predicates.len(), 3);
+
+        let action = &domain.actions[0];
+        let deleted = action.effect.del_list()[0];
+        assert_eq!(deleted.args[1], IdArg::Object(domain.constants[0].object));
+        assert_eq!(table.term(deleted).to_string(), "(on ?x table)");
+        let Effect::And(children) = &action.effect else {
+            panic!("expected a conjunction");
+        };
+        let Effect::Forall { parameters, .. } = &children[2] else {
+            panic!("expected a quantified effect");
+        };
+        assert_eq!(table.variables.name(parameters[0].variable), "?z");
+        assert_eq!(table.fact(&problem.init[0]).to_string(), "(on a table)");
+    }
+
+    #[test]
+    fn lowercased() {
+        let mut table = SymbolTable::default();
+        let domain = table
+            .parse_domain(
+                "(define (domain d) (:constants Table) (:predicates (On ?x ?y))
+                    (:action a :parameters () :effect (and)))",
+            )
+            .unwrap();
+        let input =
+            "(define (problem P) (:objects A - Block) (:init (ON a TABLE)) (:goal (on A table)))";
+        let problem = table.parse_problem(input).unwrap();
+        assert_eq!(problem.init[0].args[1], domain.constants[0].object);
+        assert_eq!(problem.objects[0].object, problem.init[0].args[0]);
+        assert_eq!(table.types.name(problem.objects[0].types[0]), "block");
+        assert_eq!(
+            Some(&Formula::Atom(problem.init[0].clone())),
+            problem.goal.as_ref()
+        );
+
+        let lowercased = lowercase_problem(&crate::problem::parse(input));
+        assert_eq!(
+            lowercased.to_string(),
+            "(define (problem P)\n    (:objects\n        a - block\n    )\n    (:init\n        (on a table)\n    )\n    (:goal (on a table))\n)"
+        );
+    }
+}
@@ -1,8 +1,15 @@
-use nom::{branch::alt, character::complete::multispace1, multi::separated_list1, IResult};
+use nom::{branch::alt, character::complete::multispace1, multi::separated_list1};
 
-use crate::shared::{named, spaced};
+use std::fmt::{self, Write};
+
+use crate::{
+    formula::Atom,
+    shared::{named, spaced, IResult},
+    writer::{impl_display, PddlWriter, WritePddl},
+};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Term {
     pub name: String,
     pub parameters: Vec<String>,
@@ -31,6 +38,30 @@ fn parse_without_parameters(input: &str) -> IResult<&str, Term> {
     ))
 }
 
+impl Atom for Term {
+    type Arg = String;
+
+    fn predicate(&self) -> &str {
+        &self.name
+    }
+
+    fn arguments(&self) -> &[String] {
+        &self.parameters
+    }
+}
+
+impl WritePddl for Term {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        write!(w, "({}", self.name)?;
+        for parameter in &self.parameters {
+            write!(w, " {}", parameter)?;
+        }
+        w.write_char(')')
+    }
+}
+
+impl_display!(Term);
+
 pub(super) fn parse_term(input: &str) -> IResult<&str, Term> {
     alt((parse_with_parameters, parse_without_parameters))(input)
 }
@@ -0,0 +1,446 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    domain::{action::effect::Effect, parameter::Parameter},
+    formula::{Atom, Formula},
+};
+
+/// Visits formulas and effects by reference
+///
+/// Every method defaults to walking the children of its node, so implementors only override the nodes they care about.
+/// Overriding methods can call [walk_formula] and [walk_effect] to keep walking.
+///
+/// Effects over atoms `A` have quantified parameters of type `P`.
+/// The added and deleted atoms of an effect and the atoms of its conditions are visited with [Visitor::visit_atom],
+/// while numeric effects are leaves, as their fluents are not atoms of the state.
+pub trait Visitor<'ast, A: 'ast, P: 'ast = Parameter> {
+    fn visit_formula(&mut self, formula: &'ast Formula<A>) {
+        walk_formula(self, formula)
+    }
+
+    fn visit_atom(&mut self, _atom: &'ast A) {}
+
+    fn visit_effect(&mut self, effect: &'ast Effect<A, P>) {
+        walk_effect(self, effect)
+    }
+}
+
+pub fn walk_formula<'ast, A, P, V>(visitor: &mut V, formula: &'ast Formula<A>)
+where
+    A: 'ast,
+    P: 'ast,
+    V: Visitor<'ast, A, P> + ?Sized,
+{
+    match formula {
+        Formula::Atom(atom) => visitor.visit_atom(atom),
+        Formula::And(children) | Formula::Or(children) => children
+            .iter()
+            .for_each(|child| visitor.visit_formula(child)),
+        Formula::Not(child) => visitor.visit_formula(child),
+        Formula::Imply(antecedent, consequent) => {
+            visitor.visit_formula(antecedent);
+            visitor.visit_formula(consequent);
+        }
+        Formula::Forall { formula, .. } | Formula::Exists { formula, .. } => {
+            visitor.visit_formula(formula)
+        }
+    }
+}
+
+pub fn walk_effect<'ast, A, P, V>(visitor: &mut V, effect: &'ast Effect<A, P>)
+where
+    A: 'ast,
+    P: 'ast,
+    V: Visitor<'ast, A, P> + ?Sized,
+{
+    match effect {
+        Effect::Add(atom) | Effect::Delete(atom) => visitor.visit_atom(atom),
+        Effect::And(children) => children
+            .iter()
+            .for_each(|child| visitor.visit_effect(child)),
+        Effect::When { condition, effect } => {
+            visitor.visit_formula(condition);
+            visitor.visit_effect(effect);
+        }
+        Effect::Forall { effect, .. } => visitor.visit_effect(effect),
+        Effect::Numeric { .. } => {}
+    }
+}
+
+/// Visits formulas and effects by mutable reference
+///
+/// Every method defaults to walking the children of its node, see [Visitor]
+pub trait VisitorMut<A, P = Parameter> {
+    fn visit_formula_mut(&mut self, formula: &mut Formula<A>) {
+        walk_formula_mut(self, formula)
+    }
+
+    fn visit_atom_mut(&mut self, _atom: &mut A) {}
+
+    fn visit_effect_mut(&mut self, effect: &mut Effect<A, P>) {
+        walk_effect_mut(self, effect)
+    }
+}
+
+pub fn walk_formula_mut<A, P, V: VisitorMut<A, P> + ?Sized>(
+    visitor: &mut V,
+    formula: &mut Formula<A>,
+) {
+    match formula {
+        Formula::Atom(atom) => visitor.visit_atom_mut(atom),
+        Formula::And(children) | Formula::Or(children) => children
+            .iter_mut()
+            .for_each(|child| visitor.visit_formula_mut(child)),
+        Formula::Not(child) => visitor.visit_formula_mut(child),
+        Formula::Imply(antecedent, consequent) => {
+            visitor.visit_formula_mut(antecedent);
+            visitor.visit_formula_mut(consequent);
+        }
+        Formula::Forall { formula, .. } | Formula::Exists { formula, .. } => {
+            visitor.visit_formula_mut(formula)
+        }
+    }
+}
+
+pub fn walk_effect_mut<A, P, V: VisitorMut<A, P> + ?Sized>(
+    visitor: &mut V,
+    effect: &mut Effect<A, P>,
+) {
+    match effect {
+        Effect::Add(atom) | Effect::Delete(atom) => visitor.visit_atom_mut(atom),
+        Effect::And(children) => children
+            .iter_mut()
+            .for_each(|child| visitor.visit_effect_mut(child)),
+        Effect::When { condition, effect } => {
+            visitor.visit_formula_mut(condition);
+            visitor.visit_effect_mut(effect);
+        }
+        Effect::Forall { effect, .. } => visitor.visit_effect_mut(effect),
+        Effect::Numeric { .. } => {}
+    }
+}
+
+/// Rebuilds formulas and effects by value, allowing nodes to be replaced by other nodes
+///
+/// Every method defaults to folding the children of its node and rebuilding it.
+/// Added and deleted atoms are kept, as replacing them by formulas would not give an effect.
+pub trait Fold<A, P = Parameter> {
+    fn fold_formula(&mut self, formula: Formula<A>) -> Formula<A> {
+        fold_children(self, formula)
+    }
+
+    fn fold_atom(&mut self, atom: A) -> Formula<A> {
+        Formula::Atom(atom)
+    }
+
+    fn fold_effect(&mut self, effect: Effect<A, P>) -> Effect<A, P> {
+        fold_effect_children(self, effect)
+    }
+}
+
+pub fn fold_children<A, P, F: Fold<A, P> + ?Sized>(
+    folder: &mut F,
+    formula: Formula<A>,
+) -> Formula<A> {
+    match formula {
+        Formula::Atom(atom) => folder.fold_atom(atom),
+        Formula::And(children) => Formula::And(
+            children
+                .into_iter()
+                .map(|child| folder.fold_formula(child))
+                .collect(),
+        ),
+        Formula::Or(children) => Formula::Or(
+            children
+                .into_iter()
+                .map(|child| folder.fold_formula(child))
+                .collect(),
+        ),
+        Formula::Not(child) => Formula::Not(Box::new(folder.fold_formula(*child))),
+        Formula::Imply(antecedent, consequent) => Formula::Imply(
+            Box::new(folder.fold_formula(*antecedent)),
+            Box::new(folder.fold_formula(*consequent)),
+        ),
+        Formula::Forall {
+            parameters,
+            formula,
+        } => Formula::Forall {
+            parameters,
+            formula: Box::new(folder.fold_formula(*formula)),
+        },
+        Formula::Exists {
+            parameters,
+            formula,
+        } => Formula::Exists {
+            parameters,
+            formula: Box::new(folder.fold_formula(*formula)),
+        },
+    }
+}
+
+pub fn fold_effect_children<A, P, F: Fold<A, P> + ?Sized>(
+    folder: &mut F,
+    effect: Effect<A, P>,
+) -> Effect<A, P> {
+    match effect {
+        Effect::And(children) => Effect::And(
+            children
+                .into_iter()
+                .map(|child| folder.fold_effect(child))
+                .collect(),
+        ),
+        Effect::When { condition, effect } => Effect::When {
+            condition: folder.fold_formula(condition),
+            effect: Box::new(folder.fold_effect(*effect)),
+        },
+        Effect::Forall { parameters, effect } => Effect::Forall {
+            parameters,
+            effect: Box::new(folder.fold_effect(*effect)),
+        },
+        literal => literal,
+    }
+}
+
+/// Collects atoms, variables and predicate names of a formula
+struct Collector<'ast, A> {
+    atoms: Vec<&'ast A>,
+    names: BTreeSet<&'ast str>,
+    variables: BTreeSet<&'ast str>,
+}
+
+impl<A> Default for Collector<'_, A> {
+    fn default() -> Self {
+        Self {
+            atoms: Vec::new(),
+            names: BTreeSet::new(),
+            variables: BTreeSet::new(),
+        }
+    }
+}
+
+impl<'ast, A: Atom> Collector<'ast, A>
+where
+    A::Arg: AsRef<str>,
+{
+    fn variables(&mut self, args: &'ast [A::Arg]) {
+        self.variables.extend(
+            args.iter()
+                .map(AsRef::as_ref)
+                .filter(|name| name.starts_with('?')),
+        );
+    }
+}
+
+impl<'ast, A: Atom> Visitor<'ast, A> for Collector<'ast, A>
+where
+    A::Arg: AsRef<str>,
+{
+    /// Variables bound by a quantifier are not free
+    fn visit_formula(&mut self, formula: &'ast Formula<A>) {
+        let (Formula::Forall { parameters, .. } | Formula::Exists { parameters, .. }) = formula
+        else {
+            return walk_formula(self, formula);
+        };
+        let outer = std::mem::take(&mut self.variables);
+        walk_formula(self, formula);
+        for parameter in parameters {
+            self.variables.remove(parameter.name());
+        }
+        self.variables.extend(outer);
+    }
+
+    fn visit_atom(&mut self, atom: &'ast A) {
+        if !atom.is_equality() {
+            self.atoms.push(atom);
+            self.names.insert(atom.predicate());
+        }
+        self.variables(atom.arguments());
+    }
+}
+
+/// Computes the nesting depth of a formula, where atoms have depth 1
+#[derive(Default)]
+struct Depth {
+    current: usize,
+    max: usize,
+}
+
+impl<'ast, A: Atom + 'ast> Visitor<'ast, A> for Depth {
+    fn visit_formula(&mut self, formula: &'ast Formula<A>) {
+        self.current += 1;
+        self.max = self.max.max(self.current);
+        walk_formula(self, formula);
+        self.current -= 1;
+    }
+}
+
+impl<A: Atom> Formula<A>
+where
+    A::Arg: AsRef<str>,
+{
+    fn collect(&self) -> Collector<'_, A> {
+        let mut collector = Collector::default();
+        collector.visit_formula(self);
+        collector
+    }
+
+    /// All atoms of the formula except equalities, in order of occurrence
+    pub fn atoms(&self) -> Vec<&A> {
+        self.collect().atoms
+    }
+
+    /// The variables, i.e. arguments starting with `?`, occurring in the formula
+    pub fn free_variables(&self) -> BTreeSet<&str> {
+        self.collect().variables
+    }
+
+    /// The names of all predicates used in the formula, excluding equality
+    pub fn predicate_names(&self) -> BTreeSet<&str> {
+        self.collect().names
+    }
+}
+
+impl<A: Atom> Formula<A> {
+    /// The nesting depth of the formula, where an atom has depth 1
+    pub fn depth(&self) -> usize {
+        let mut depth = Depth::default();
+        depth.visit_formula(self);
+        depth.max
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::{fold_effect_children, Fold, Visitor, VisitorMut};
+    use crate::{
+        domain::action::effect::Effect, formula::Formula, problem, problem::init::Fact, term::Term,
+    };
+
+    fn formula() -> Formula<Term> {
+        let domain = crate::domain::parse_domain(
+            "(define (domain d) (:predicates (p ?a) (q ?a ?b))
+                (:action a :parameters (?a ?b)
+                    :precondition (and (p ?a) (not (or (q ?a ?b) (= ?b c))))
+                    :effect (p ?b)))",
+        )
+        .unwrap();
+        domain.actions[0].precondition.clone().unwrap()
+    }
+
+    #[test]
+    fn formula_utilities() {
+        let formula = formula();
+        assert_eq!(
+            formula
+                .atoms()
+                .iter()
+                .map(|term| term.to_string())
+                .collect::<Vec<_>>(),
+            vec!["(p ?a)", "(q ?a ?b)"]
+        );
+        assert_eq!(formula.free_variables(), BTreeSet::from(["?a", "?b"]));
+        assert_eq!(formula.predicate_names(), BTreeSet::from(["p", "q"]));
+        assert_eq!(formula.depth(), 4);
+    }
+
+    #[test]
+    fn goal_utilities() {
+        let problem = problem::parse("(define (problem p) (:goal (and (p a) (not (q)))))");
+        let goal = problem.goal.unwrap();
+        assert_eq!(
+            goal.atoms(),
+            vec![
+                &Fact {
+                    predicate: "p",
+                    objects: vec!["a"]
+                },
+                &Fact {
+                    predicate: "q",
+                    objects: vec![]
+                }
+            ]
+        );
+        assert_eq!(goal.predicate_names(), BTreeSet::from(["p", "q"]));
+        assert_eq!(goal.depth(), 3);
+    }
+
+    #[test]
+    fn visitor_mut() {
+        struct Rename;
+        impl VisitorMut<Term> for Rename {
+            fn visit_atom_mut(&mut self, term: &mut Term) {
+                term.name = term.name.to_uppercase();
+            }
+        }
+        let mut formula = formula();
+        Rename.visit_formula_mut(&mut formula);
+        assert_eq!(formula.predicate_names(), BTreeSet::from(["P", "Q"]));
+    }
+
+    #[test]
+    fn fold() {
+        /// Replaces every atom `(q ...)` by its negation
+        struct NegateQ;
+        impl<'a> Fold<Fact<'a>> for NegateQ {
+            fn fold_atom(&mut self, fact: Fact<'a>) -> Formula<Fact<'a>> {
+                match fact.predicate {
+                    "q" => Formula::Not(Box::new(Formula::Atom(fact))),
+                    _ => Formula::Atom(fact),
+                }
+            }
+        }
+        let problem = problem::parse("(define (problem p) (:goal (and (p a) (or (q) (p b)))))");
+        let goal = NegateQ.fold_formula(problem.goal.unwrap());
+        assert_eq!(goal.depth(), 4);
+    }
+
+    #[test]
+    fn effects() {
+        let domain = crate::domain::parse_domain(
+            "(define (domain d) (:predicates (p ?a) (q ?a))
+                (:action a :parameters (?a)
+                    :effect (and (not (p ?a))
+                        (forall (?b) (when (q ?b) (and (p ?b) (increase (total-cost) 1)))))))",
+        )
+        .unwrap();
+        let mut effect = domain.actions[0].effect.clone();
+
+        #[derive(Default)]
+        struct Atoms(Vec<String>);
+        impl Visitor<'_, Term> for Atoms {
+            fn visit_atom(&mut self, term: &Term) {
+                self.0.push(term.to_string());
+            }
+        }
+        let mut atoms = Atoms::default();
+        atoms.visit_effect(&effect);
+        assert_eq!(atoms.0, vec!["(p ?a)", "(q ?b)", "(p ?b)"]);
+
+        struct Rename;
+        impl VisitorMut<Term> for Rename {
+            fn visit_atom_mut(&mut self, term: &mut Term) {
+                term.name = term.name.to_uppercase();
+            }
+        }
+        Rename.visit_effect_mut(&mut effect);
+        assert_eq!(effect.possible_literals().0[0].to_string(), "(P ?b)");
+
+        /// Removes the conditions of conditional effects
+        struct Unconditional;
+        impl Fold<Term> for Unconditional {
+            fn fold_effect(&mut self, effect: Effect) -> Effect {
+                match effect {
+                    Effect::When { effect, .. } => self.fold_effect(*effect),
+                    effect => fold_effect_children(self, effect),
+                }
+            }
+        }
+        let effect = Unconditional.fold_effect(effect);
+        assert!(!effect.to_string().contains("when"));
+        let mut atoms = Atoms::default();
+        atoms.visit_effect(&effect);
+        assert_eq!(atoms.0, vec!["(P ?a)", "(P ?b)"]);
+    }
+}
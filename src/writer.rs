@@ -0,0 +1,187 @@
+use std::fmt::{self, Write};
+
+/// How a single level of indentation is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    Spaces(usize),
+    Tabs,
+}
+
+/// Options controlling the layout of written PDDL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriterConfig {
+    pub indent: Indent,
+}
+
+impl Default for WriterConfig {
+    fn default() -> Self {
+        Self {
+            indent: Indent::Spaces(4),
+        }
+    }
+}
+
+/// Writes PDDL to an underlying [fmt::Write], keeping track of the current indentation depth
+pub struct PddlWriter<'a> {
+    out: &'a mut dyn Write,
+    config: &'a WriterConfig,
+    depth: usize,
+}
+
+impl<'a> PddlWriter<'a> {
+    pub fn new(out: &'a mut dyn Write, config: &'a WriterConfig) -> Self {
+        Self {
+            out,
+            config,
+            depth: 0,
+        }
+    }
+
+    pub fn config(&self) -> &WriterConfig {
+        self.config
+    }
+
+    /// Starts a new line, indented to the current depth
+    pub fn newline(&mut self) -> fmt::Result {
+        self.out.write_char('\n')?;
+        for _ in 0..self.depth {
+            match self.config.indent {
+                Indent::Spaces(width) => {
+                    for _ in 0..width {
+                        self.out.write_char(' ')?;
+                    }
+                }
+                Indent::Tabs => self.out.write_char('\t')?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes an empty line, without trailing indentation
+    pub fn blank_line(&mut self) -> fmt::Result {
+        self.out.write_char('\n')
+    }
+
+    /// Runs `f` one indentation level deeper than the current one
+    pub fn indented<F>(&mut self, f: F) -> fmt::Result
+    where
+        F: FnOnce(&mut Self) -> fmt::Result,
+    {
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Writes `(head`, then each item on its own indented line, and finally `)` on a line of its own
+    pub fn block<T: WritePddl>(&mut self, head: &str, items: &[T]) -> fmt::Result {
+        write!(self, "({}", head)?;
+        self.indented(|w| {
+            for item in items {
+                w.newline()?;
+                item.write_pddl(w)?;
+            }
+            Ok(())
+        })?;
+        self.newline()?;
+        self.write_char(')')
+    }
+}
+
+impl Write for PddlWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_str(s)
+    }
+}
+
+/// Implemented by everything that can be written as PDDL
+pub trait WritePddl {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result;
+
+    /// Writes the node as PDDL with the default [WriterConfig]
+    fn to_pddl(&self) -> String {
+        self.to_pddl_with(&WriterConfig::default())
+    }
+
+    /// Writes the node as PDDL with the given [WriterConfig]
+    fn to_pddl_with(&self, config: &WriterConfig) -> String {
+        let mut out = String::new();
+        self.write_pddl(&mut PddlWriter::new(&mut out, config))
+            .expect("writing to a string cannot fail");
+        out
+    }
+}
+
+impl WritePddl for &str {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        w.write_str(self)
+    }
+}
+
+impl WritePddl for String {
+    fn write_pddl(&self, w: &mut PddlWriter) -> fmt::Result {
+        w.write_str(self)
+    }
+}
+
+/// Writes `node` as PDDL to `out`
+///
+/// ## Example
+/// ```rust
+/// use spingus::writer::{write_pddl, Indent, WriterConfig};
+/// let domain = spingus::domain::parse_domain(
+///     "(define (domain d) (:predicates (p)) (:action a :parameters () :effect (p)))",
+/// )
+/// .unwrap();
+/// let mut out = String::new();
+/// let config = WriterConfig { indent: Indent::Spaces(2) };
+/// write_pddl(&mut out, &domain, &config).unwrap();
+/// assert!(out.starts_with("(define (domain d)\n  (:predicates"));
+/// ```
+pub fn write_pddl<T: WritePddl + ?Sized>(
+    out: &mut dyn Write,
+    node: &T,
+    config: &WriterConfig,
+) -> fmt::Result {
+    node.write_pddl(&mut PddlWriter::new(out, config))
+}
+
+/// Implements [fmt::Display] in terms of [WritePddl] with the default [WriterConfig]
+macro_rules! impl_display {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl std::fmt::Display for $t {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    let config = $crate::writer::WriterConfig::default();
+                    $crate::writer::WritePddl::write_pddl(
+                        self,
+                        &mut $crate::writer::PddlWriter::new(f, &config),
+                    )
+                }
+            }
+        )*
+    };
+}
+pub(crate) use impl_display;
+
+#[cfg(test)]
+mod test {
+    use super::{Indent, WritePddl, WriterConfig};
+
+    #[test]
+    fn block() {
+        struct Block;
+        impl WritePddl for Block {
+            fn write_pddl(&self, w: &mut super::PddlWriter) -> std::fmt::Result {
+                w.block(":init", &["(a)", "(b)"])
+            }
+        }
+        assert_eq!(Block.to_pddl(), "(:init\n    (a)\n    (b)\n)");
+        assert_eq!(
+            Block.to_pddl_with(&WriterConfig {
+                indent: Indent::Tabs
+            }),
+            "(:init\n\t(a)\n\t(b)\n)"
+        );
+    }
+}
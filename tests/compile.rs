@@ -0,0 +1,178 @@
+use std::fs;
+
+use spingus::{
+    compile::{inference, types, Compiled},
+    domain::{self, types::hierarchy::TypeHierarchy},
+    ground::GroundTask,
+    owned::{AsBorrowed, IntoOwned},
+    problem,
+};
+
+use rstest::*;
+
+#[rstest]
+#[case("barman-agile")]
+#[case("barman-mco14-strips")]
+#[case("barman-satisficing")]
+#[case("blocks-typed")]
+#[case("blocks-untyped")]
+#[case("childsnack")]
+#[case("child-snack-agile")]
+#[case("child-snack-satisficing")]
+#[case("driverlog-automatic")]
+#[case("driverlog-hand-coded")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("ferry")]
+#[case("floortile")]
+#[case("freecell-typed")]
+#[case("freecell-untyped")]
+#[case("grid")]
+#[case("gripper")]
+#[case("hiking-sequential-agile")]
+#[case("logistics")]
+#[case("logistics-typed")]
+#[case("logistics-untyped")]
+#[case("miconic")]
+#[case("movie")]
+#[case("mystery")]
+#[case("rovers")]
+#[case("satellite")]
+#[case("sokoban")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+#[case("zenotravel")]
+fn types_data(#[case] domain_name: &str) {
+    let domain_content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    let problem = problem::parse(&problem_content);
+    let untyped = types::compile(&domain, &problem).unwrap();
+    assert_eq!(untyped.domain.types, None);
+    assert_eq!(
+        domain::parse_domain(&untyped.domain.to_string()),
+        Ok(untyped.domain.clone())
+    );
+
+    let task = GroundTask::new(&domain, &problem).unwrap();
+    let untyped_task = GroundTask::new(&untyped.domain, &untyped.problem.as_borrowed()).unwrap();
+    let steps = |task: &GroundTask| {
+        let mut steps: Vec<String> = task
+            .actions
+            .iter()
+            .map(|action| task.action_name(action))
+            .collect();
+        steps.sort();
+        steps
+    };
+    assert_eq!(steps(&untyped_task), steps(&task), "{}", domain_name);
+    assert_eq!(
+        untyped_task.atoms.len(),
+        task.atoms.len(),
+        "{}",
+        domain_name
+    );
+    assert_eq!(untyped_task.init.len(), task.init.len(), "{}", domain_name);
+
+    // Types with objects are recovered from the facts of their predicates
+    let inferred = TypeHierarchy::new(&types::infer_types(
+        &untyped.domain,
+        &untyped.problem.as_borrowed(),
+    ))
+    .unwrap();
+    // Storage declares a type twice, so it has no hierarchy to compare with
+    let Ok(hierarchy) = TypeHierarchy::from_domain(&domain) else {
+        return;
+    };
+    for sub_type in inferred.types() {
+        for super_type in inferred.types() {
+            if hierarchy.contains(sub_type) && hierarchy.contains(super_type) {
+                assert_eq!(
+                    inferred.is_subtype(sub_type, super_type),
+                    hierarchy.is_subtype(sub_type, super_type),
+                    "{}: {} - {}",
+                    domain_name,
+                    sub_type,
+                    super_type
+                );
+            }
+        }
+    }
+}
+
+#[rstest]
+#[case("barman-agile")]
+#[case("barman-mco14-strips")]
+#[case("barman-satisficing")]
+#[case("blocks-typed")]
+#[case("blocks-untyped")]
+#[case("childsnack")]
+#[case("child-snack-agile")]
+#[case("child-snack-satisficing")]
+#[case("driverlog-automatic")]
+#[case("driverlog-hand-coded")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("ferry")]
+#[case("floortile")]
+#[case("freecell-typed")]
+#[case("freecell-untyped")]
+#[case("grid")]
+#[case("gripper")]
+#[case("hiking-sequential-agile")]
+#[case("logistics")]
+#[case("logistics-typed")]
+#[case("logistics-untyped")]
+#[case("miconic")]
+#[case("movie")]
+#[case("mystery")]
+#[case("rovers")]
+#[case("satellite")]
+#[case("sokoban")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+#[case("zenotravel")]
+fn inference_data(#[case] domain_name: &str) {
+    let domain_content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    let problem = problem::parse(&problem_content);
+    // Typed domains have their types removed first, to infer them again
+    let untyped = match domain.types {
+        Some(_) => types::compile(&domain, &problem).unwrap(),
+        None => Compiled {
+            domain: domain.clone(),
+            problem: problem.clone().into_owned(),
+        },
+    };
+    let typed = inference::infer(&untyped.domain, &untyped.problem.as_borrowed()).unwrap();
+    assert!(typed.domain.types.is_some());
+    assert_eq!(
+        domain::parse_domain(&typed.domain.to_string()),
+        Ok(typed.domain.clone())
+    );
+    assert_eq!(
+        inference::infer(&typed.domain, &typed.problem.as_borrowed()).map(|_| ()),
+        Err("the domain is already typed".to_owned())
+    );
+
+    let task = GroundTask::new(&domain, &problem).unwrap();
+    let typed_task = GroundTask::new(&typed.domain, &typed.problem.as_borrowed()).unwrap();
+    let steps = |task: &GroundTask| {
+        let mut steps: Vec<String> = task
+            .actions
+            .iter()
+            .map(|action| task.action_name(action))
+            .collect();
+        steps.sort();
+        steps
+    };
+    assert_eq!(steps(&typed_task), steps(&task), "{}", domain_name);
+    assert_eq!(typed_task.atoms.len(), task.atoms.len(), "{}", domain_name);
+}
@@ -0,0 +1,98 @@
+use std::fs;
+
+use spingus::{
+    domain,
+    format::{check, format, FormatOptions, InitOrder},
+    problem,
+};
+
+use rstest::*;
+
+#[rstest]
+#[case("barman-agile")]
+#[case("barman-mco14-strips")]
+#[case("barman-satisficing")]
+#[case("blocks-typed")]
+#[case("blocks-untyped")]
+#[case("childsnack")]
+#[case("child-snack-agile")]
+#[case("child-snack-satisficing")]
+#[case("driverlog-automatic")]
+#[case("driverlog-hand-coded")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("ferry")]
+#[case("floortile")]
+#[case("freecell-typed")]
+#[case("freecell-untyped")]
+#[case("grid")]
+#[case("gripper")]
+#[case("hiking-sequential-agile")]
+#[case("logistics")]
+#[case("logistics-typed")]
+#[case("logistics-untyped")]
+#[case("miconic")]
+#[case("movie")]
+#[case("mystery")]
+#[case("rovers")]
+#[case("satellite")]
+#[case("sokoban")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+#[case("zenotravel")]
+fn format_data(#[case] domain_name: &str) {
+    let options = FormatOptions::default();
+
+    let domain_content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let formatted = format(&domain_content, &options).unwrap();
+    assert_eq!(check(&formatted, &options), Ok(true));
+    assert_eq!(
+        domain::parse_domain(&formatted),
+        domain::parse_domain(&domain_content)
+    );
+
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let formatted = format(&problem_content, &options).unwrap();
+    assert_eq!(check(&formatted, &options), Ok(true));
+    assert_eq!(
+        problem::try_parse(&formatted),
+        problem::try_parse(&problem_content)
+    );
+
+    let options = FormatOptions {
+        init_order: InitOrder::Sorted,
+        ..options
+    };
+    let formatted = format(&problem_content, &options).unwrap();
+    assert_eq!(check(&formatted, &options), Ok(true));
+    let mut expected = problem::parse(&problem_content);
+    let mut actual = problem::parse(&formatted);
+    if let Some(init) = expected.init.as_mut() {
+        init.sort_by_key(|fact| fact.to_string());
+    }
+    assert_eq!(actual.init.as_deref(), expected.init.as_deref());
+    actual.init = None;
+    expected.init = None;
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn format_requirements_comments() {
+    let options = FormatOptions::default();
+    let input = "(define (domain d) (:requirements :strips ; needed\n :typing))";
+    let formatted = format(input, &options).unwrap();
+    assert_eq!(
+        formatted,
+        "(define (domain d)\n    (:requirements :strips ; needed\n        :typing)\n)\n"
+    );
+    assert_eq!(check(&formatted, &options), Ok(true));
+    assert!(format(
+        "(define (domain d) (:requirements :strips :typing))",
+        &options
+    )
+    .unwrap()
+    .contains("(:requirements :strips :typing)"));
+}
@@ -0,0 +1,64 @@
+use std::fs;
+
+use spingus::{
+    domain,
+    ground::{GroundTask, Strategy},
+    problem,
+};
+
+use rstest::*;
+
+#[rstest]
+#[case("barman-agile")]
+#[case("barman-mco14-strips")]
+#[case("barman-satisficing")]
+#[case("blocks-typed")]
+#[case("blocks-untyped")]
+#[case("childsnack")]
+#[case("child-snack-agile")]
+#[case("child-snack-satisficing")]
+#[case("driverlog-automatic")]
+#[case("driverlog-hand-coded")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("ferry")]
+#[case("floortile")]
+#[case("freecell-typed")]
+#[case("freecell-untyped")]
+#[case("grid")]
+#[case("gripper")]
+#[case("hiking-sequential-agile")]
+#[case("logistics")]
+#[case("logistics-typed")]
+#[case("logistics-untyped")]
+#[case("miconic")]
+#[case("movie")]
+#[case("mystery")]
+#[case("rovers")]
+#[case("satellite")]
+#[case("sokoban")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+#[case("zenotravel")]
+fn ground_data(#[case] domain_name: &str) {
+    let domain_content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    let problem = problem::parse(&problem_content);
+    let task = GroundTask::new(&domain, &problem).unwrap();
+    assert!(!task.actions.is_empty(), "{}", domain_name);
+    assert!(!task.goal.is_empty(), "{}", domain_name);
+    assert_eq!(task.unreachable_goal, vec![], "{}", domain_name);
+    for action in &task.actions {
+        assert!(action.del.iter().all(|atom| !action.add.contains(atom)));
+    }
+
+    let naive = GroundTask::with_strategy(&domain, &problem, Strategy::Naive).unwrap();
+    assert_eq!(naive.atoms, task.atoms, "{}", domain_name);
+    assert_eq!(naive.actions, task.actions, "{}", domain_name);
+    assert_eq!(naive.init, task.init, "{}", domain_name);
+    assert_eq!(naive.goal, task.goal, "{}", domain_name);
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "json")]
+
+use std::fs;
+
+use spingus::{
+    domain,
+    json::{
+        domain_from_json, domain_to_json, plan_from_json, plan_to_json, problem_from_json,
+        problem_to_json,
+    },
+    plan, problem,
+};
+
+use rstest::*;
+
+#[rstest]
+#[case("barman-agile")]
+#[case("barman-mco14-strips")]
+#[case("barman-satisficing")]
+#[case("blocks-typed")]
+#[case("blocks-untyped")]
+#[case("childsnack")]
+#[case("child-snack-agile")]
+#[case("child-snack-satisficing")]
+#[case("driverlog-automatic")]
+#[case("driverlog-hand-coded")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("ferry")]
+#[case("floortile")]
+#[case("freecell-typed")]
+#[case("freecell-untyped")]
+#[case("grid")]
+#[case("gripper")]
+#[case("hiking-sequential-agile")]
+#[case("logistics")]
+#[case("logistics-typed")]
+#[case("logistics-untyped")]
+#[case("miconic")]
+#[case("movie")]
+#[case("mystery")]
+#[case("rovers")]
+#[case("satellite")]
+#[case("sokoban")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+#[case("zenotravel")]
+fn json_round_trip(#[case] domain_name: &str) {
+    let domain_content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    assert_eq!(domain_from_json(&domain_to_json(&domain)), Ok(domain));
+
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let problem = problem::parse(&problem_content);
+    let json = problem_to_json(&problem);
+    assert_eq!(problem_from_json(&json), Ok(problem));
+}
+
+#[test]
+fn json_golden() {
+    let domain_content = fs::read_to_string("tests/data/gripper/domain.pddl").unwrap();
+    let domain_json = fs::read_to_string("tests/data/gripper/domain.json").unwrap();
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    assert_eq!(domain_to_json(&domain), domain_json.trim_end());
+    assert_eq!(domain_from_json(&domain_json), Ok(domain));
+
+    let problem_content = fs::read_to_string("tests/data/gripper/problem.pddl").unwrap();
+    let problem_json = fs::read_to_string("tests/data/gripper/problem.json").unwrap();
+    let problem = problem::parse(&problem_content);
+    assert_eq!(problem_to_json(&problem), problem_json.trim_end());
+    assert_eq!(problem_from_json(&problem_json), Ok(problem));
+}
+
+#[test]
+fn json_plan() {
+    let plan = plan::parse("(pick ball1 rooma left)\n(move rooma roomb)");
+    assert_eq!(plan_from_json(&plan_to_json(&plan)), Ok(plan));
+}
+
+#[test]
+fn json_effects() {
+    let domain = domain::parse_domain(
+        "(define (domain d)
+            (:predicates (p ?x) (q ?x))
+            (:action a
+                :parameters (?x)
+                :effect (and
+                    (forall (?y) (when (q ?y) (not (p ?y))))
+                    (increase (total-cost) (* 2 (cost ?x))))))",
+    )
+    .unwrap();
+    let json: String = domain_to_json(&domain).split_whitespace().collect();
+    assert!(json.contains(r#"{"increase":{"fluent":{"predicate":"total-cost","args":[]}"#));
+    assert_eq!(domain_from_json(&json), Ok(domain));
+
+    let invalid = json.replace(
+        r#"{"not":{"atom":{"predicate":"p","args":["?y"]}}}"#,
+        r#"{"not":{"and":[]}}"#,
+    );
+    assert!(domain_from_json(&invalid)
+        .unwrap_err()
+        .contains("only atoms can be negated in effects"));
+}
+
+#[test]
+fn json_quantified() {
+    let domain = domain::parse_domain(
+        "(define (domain d)
+            (:types t)
+            (:predicates (p ?x) (q ?x ?y))
+            (:action a
+                :parameters (?x)
+                :precondition (forall (?y - t) (exists (?z) (q ?y ?z)))
+                :effect (p ?x)))",
+    )
+    .unwrap();
+    let json: String = domain_to_json(&domain).split_whitespace().collect();
+    assert!(json.contains(r#"{"forall":{"parameters":[{"name":"?y","type":"t"}]"#));
+    assert_eq!(domain_from_json(&json), Ok(domain));
+
+    let problem =
+        problem::parse("(define (problem p) (:objects o - t) (:goal (exists (?x - t) (p ?x))))");
+    assert_eq!(problem_from_json(&problem_to_json(&problem)), Ok(problem));
+}
@@ -0,0 +1,201 @@
+use std::fs;
+
+use spingus::{
+    domain::{
+        self,
+        builder::{typed, untyped, ActionBuilder, DomainBuilder},
+    },
+    plan,
+    problem::{self, builder::ProblemBuilder},
+    writer::{Indent, WritePddl, WriterConfig},
+};
+
+use rstest::*;
+
+#[rstest]
+#[case("barman-agile")]
+#[case("barman-mco14-strips")]
+#[case("barman-satisficing")]
+#[case("blocks-typed")]
+#[case("blocks-untyped")]
+#[case("childsnack")]
+#[case("child-snack-agile")]
+#[case("child-snack-satisficing")]
+#[case("driverlog-automatic")]
+#[case("driverlog-hand-coded")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("ferry")]
+#[case("floortile")]
+#[case("freecell-typed")]
+#[case("freecell-untyped")]
+#[case("grid")]
+#[case("gripper")]
+#[case("hiking-sequential-agile")]
+#[case("logistics")]
+#[case("logistics-typed")]
+#[case("logistics-untyped")]
+#[case("miconic")]
+#[case("movie")]
+#[case("mystery")]
+#[case("rovers")]
+#[case("satellite")]
+#[case("sokoban")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+#[case("zenotravel")]
+fn round_trip(#[case] domain_name: &str) {
+    let domain_content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    assert_eq!(
+        domain::parse_domain(&domain.to_string()),
+        Ok(domain.clone())
+    );
+    let config = WriterConfig {
+        indent: Indent::Tabs,
+    };
+    assert_eq!(
+        domain::parse_domain(&domain.to_pddl_with(&config)),
+        Ok(domain.clone())
+    );
+    let split = domain.split_disjunctive_actions(64).unwrap();
+    assert_eq!(split.actions.len(), domain.actions.len());
+    assert_eq!(domain::parse_domain(&split.to_string()), Ok(split));
+
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let problem = problem::parse(&problem_content);
+    let written = problem.to_string();
+    assert_eq!(problem::try_parse(&written), Ok(problem.clone()));
+    let written = problem.to_pddl_with(&config);
+    assert_eq!(problem::try_parse(&written), Ok(problem));
+}
+
+#[test]
+fn round_trip_plan() {
+    let input = "(pick-up b)\n(stack b a) ; step 2\n(noop)\n; cost = 3 (unit cost)\n";
+    let plan = plan::parse(input);
+    assert_eq!(plan::try_parse(&plan.to_pddl()), Ok(plan));
+}
+
+#[test]
+fn round_trip_split() {
+    let domain = domain::parse_domain(
+        "(define (domain d)
+            (:requirements :adl)
+            (:predicates (a ?x) (b ?x) (c ?x) (d))
+            (:action x
+                :parameters (?x)
+                :precondition (and (imply (a ?x) (b ?x)) (not (and (c ?x) (d))))
+                :effect (and (d) (not (a ?x))))
+            (:action y :parameters () :precondition (d) :effect (not (d))))",
+    )
+    .unwrap();
+    let split = domain.split_disjunctive_actions(8).unwrap();
+    let names: Vec<&str> = split
+        .actions
+        .iter()
+        .map(|action| action.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["x-1", "x-2", "x-3", "x-4", "y"]);
+    assert_eq!(
+        split.actions[3].precondition.as_ref().unwrap().to_string(),
+        "(and\n    (b ?x)\n    (not (d))\n)"
+    );
+    assert_eq!(domain::parse_domain(&split.to_string()), Ok(split));
+    assert!(domain.split_disjunctive_actions(3).is_err());
+}
+
+#[test]
+fn round_trip_quantified() {
+    let domain = domain::parse_domain(
+        "(define (domain d)
+            (:requirements :adl)
+            (:types t)
+            (:predicates (a ?x) (b ?x ?y))
+            (:action x
+                :parameters (?x)
+                :precondition (and (a ?x) (Forall (?y ?z - t) (exists (?w) (b ?y ?w))))
+                :effect (not (a ?x))))",
+    )
+    .unwrap();
+    let precondition = domain.actions[0].precondition.as_ref().unwrap();
+    assert_eq!(
+        precondition.to_string(),
+        "(and\n    (a ?x)\n    (forall (?y ?z - t) (exists (?w) (b ?y ?w)))\n)"
+    );
+    assert_eq!(
+        domain::parse_domain(&domain.to_string()),
+        Ok(domain.clone())
+    );
+    assert!(domain.split_disjunctive_actions(8).is_err());
+
+    let problem = problem::parse(
+        "(define (problem p)
+            (:domain d)
+            (:objects o - t)
+            (:goal (exists (?x - t) (forall (?y) (b ?x ?y)))))",
+    );
+    assert_eq!(problem::try_parse(&problem.to_string()), Ok(problem));
+}
+
+#[test]
+fn round_trip_untyped_before_typed() {
+    let domain = DomainBuilder::new("d")
+        .types(["t"], "object")
+        .predicate("p", [untyped("?a"), typed("?b", "t")])
+        .action(ActionBuilder::new("a").parameters([untyped("?a"), typed("?b", "t")]))
+        .build()
+        .unwrap();
+    let written = domain.predicates[0].to_string();
+    assert_eq!(written, "(p ?a - object ?b - t)");
+    let parsed = domain::parse_domain(&domain.to_string()).unwrap();
+    assert_eq!(
+        parsed.predicates[0].parameters,
+        vec![typed("?a", "object"), typed("?b", "t")]
+    );
+    assert_eq!(
+        parsed.actions[0].parameters,
+        parsed.predicates[0].parameters
+    );
+
+    let builder = ProblemBuilder::new("p", "d")
+        .object("x", None)
+        .object("y", Some("t"))
+        .object("z", None);
+    let problem = builder.build().unwrap();
+    let written = problem.to_string();
+    assert!(written.contains("x - object y - t z"), "{}", written);
+    let parsed = problem::parse(&written);
+    let types: Vec<(&str, Option<&str>)> = parsed
+        .objects
+        .iter()
+        .flatten()
+        .map(|object| (object.name, object.type_name))
+        .collect();
+    assert_eq!(
+        types,
+        vec![("x", Some("object")), ("y", Some("t")), ("z", None)]
+    );
+}
+
+#[test]
+fn split_fresh_names() {
+    let domain = domain::parse_domain(
+        "(define (domain d)
+            (:predicates (a) (b))
+            (:action x :parameters () :precondition (or (a) (b)) :effect (not (a)))
+            (:action x-1 :parameters () :precondition (a) :effect (b)))",
+    )
+    .unwrap();
+    let split = domain.split_disjunctive_actions(8).unwrap();
+    let names: Vec<&str> = split
+        .actions
+        .iter()
+        .map(|action| action.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["x-1-2", "x-2", "x-1"]);
+    assert_eq!(domain::parse_domain(&split.to_string()), Ok(split));
+}
@@ -0,0 +1,174 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+};
+
+use spingus::{
+    domain,
+    ground::GroundTask,
+    problem,
+    sas::{output, Operator, SasTask},
+    symbol::{AtomId, Id},
+};
+
+use rstest::*;
+
+/// Whether `operator` applies in `state`, and the state it leads to if it does
+fn apply(operator: &Operator, state: &[usize]) -> Option<Vec<usize>> {
+    let holds = |(variable, value): &(usize, usize)| state[*variable] == *value;
+    if !operator.prevail.iter().all(holds)
+        || !operator.effects.iter().all(|effect| {
+            effect
+                .precondition
+                .is_none_or(|value| state[effect.variable] == value)
+        })
+    {
+        return None;
+    }
+    let mut next = state.to_vec();
+    for effect in &operator.effects {
+        if effect.conditions.iter().all(holds) {
+            next[effect.variable] = effect.value;
+        }
+    }
+    Some(next)
+}
+
+#[rstest]
+#[case("barman-agile")]
+#[case("barman-mco14-strips")]
+#[case("barman-satisficing")]
+#[case("blocks-typed")]
+#[case("blocks-untyped")]
+#[case("childsnack")]
+#[case("child-snack-agile")]
+#[case("child-snack-satisficing")]
+#[case("driverlog-automatic")]
+#[case("driverlog-hand-coded")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("ferry")]
+#[case("floortile")]
+#[case("freecell-typed")]
+#[case("freecell-untyped")]
+#[case("grid")]
+#[case("gripper")]
+#[case("hiking-sequential-agile")]
+#[case("logistics")]
+#[case("logistics-typed")]
+#[case("logistics-untyped")]
+#[case("miconic")]
+#[case("movie")]
+#[case("mystery")]
+#[case("rovers")]
+#[case("satellite")]
+#[case("sokoban")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+#[case("zenotravel")]
+fn sas_data(#[case] domain_name: &str) {
+    let domain_content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    let problem = problem::parse(&problem_content);
+    let ground = GroundTask::new(&domain, &problem).unwrap();
+    let task = SasTask::new(&domain, &problem).unwrap();
+    assert!(
+        task.variables.len() <= ground.atoms.len(),
+        "{}",
+        domain_name
+    );
+    assert_eq!(task.init.len(), task.variables.len());
+    assert_eq!(
+        output::parse(&task.to_string()).as_ref(),
+        Ok(&task),
+        "{}",
+        domain_name
+    );
+
+    // The fact of every atom, and of its negation for binary variables
+    let mut facts: HashMap<String, (usize, usize)> = HashMap::new();
+    for (variable, values) in task.variables.iter().enumerate() {
+        for (value, name) in values.values.iter().enumerate() {
+            facts.insert(name.clone(), (variable, value));
+        }
+    }
+    let atom_fact = |atom: &AtomId, negated: bool| {
+        let fact = ground.symbols.fact(&ground.atoms[atom.index()]);
+        let name = format!(
+            "{} {}({})",
+            if negated { "NegatedAtom" } else { "Atom" },
+            fact.predicate,
+            fact.objects.join(", ")
+        );
+        facts.get(&name).copied()
+    };
+    let operators: HashMap<&str, &Operator> = task
+        .operators
+        .iter()
+        .map(|operator| (operator.name.as_str(), operator))
+        .collect();
+
+    // Walks the ground task and the translation in lockstep, checking that they agree on every state
+    let mut ground_state: BTreeSet<AtomId> = ground.init.clone();
+    let mut state = task.init.clone();
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    for _ in 0..40 {
+        for atom in 0..ground.atoms.len() {
+            let atom = AtomId::new(atom);
+            let (variable, value) = atom_fact(&atom, false).expect("atom has no fact");
+            assert_eq!(
+                ground_state.contains(&atom),
+                state[variable] == value,
+                "{}: {}",
+                domain_name,
+                ground.atom_name(atom)
+            );
+        }
+        let goal = ground.goal.iter().all(|atom| ground_state.contains(atom))
+            && ground
+                .negative_goal
+                .iter()
+                .all(|atom| !ground_state.contains(atom));
+        assert_eq!(
+            goal,
+            task.goal
+                .iter()
+                .all(|(variable, value)| state[*variable] == *value),
+            "{}",
+            domain_name
+        );
+
+        let mut successors = Vec::new();
+        for action in &ground.actions {
+            let applicable = action
+                .precondition
+                .iter()
+                .all(|atom| ground_state.contains(atom))
+                && action
+                    .negative_precondition
+                    .iter()
+                    .all(|atom| !ground_state.contains(atom));
+            let name = ground.action_name(action);
+            let operator = operators.get(&name[1..name.len() - 1]);
+            let next = operator.and_then(|operator| apply(operator, &state));
+            assert_eq!(applicable, next.is_some(), "{}: {}", domain_name, name);
+            if let Some(next) = next {
+                let mut ground_next = ground_state.clone();
+                ground_next.retain(|atom| !action.del.contains(atom));
+                ground_next.extend(&action.add);
+                successors.push((ground_next, next));
+            }
+        }
+        if successors.is_empty() {
+            break;
+        }
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (ground_state, state) = successors.swap_remove(seed as usize % successors.len());
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    collections::{BTreeSet, HashSet, VecDeque},
+    fs,
+};
+
+use spingus::{
+    domain,
+    ground::GroundTask,
+    problem,
+    sas::{invariant::synthesize, mutex_groups},
+    sat::{parse_model, Cnf, SatEncoding, Semantics},
+};
+
+use rstest::*;
+
+/// A model of `cnf` as its true literals, found by DPLL with unit propagation, or `None` if it is unsatisfiable
+fn solve(cnf: &Cnf) -> Option<Vec<i32>> {
+    fn value(assignment: &[Option<bool>], literal: i32) -> Option<bool> {
+        assignment[literal.unsigned_abs() as usize].map(|value| value == (literal > 0))
+    }
+
+    fn dpll(cnf: &Cnf, mut assignment: Vec<Option<bool>>) -> Option<Vec<Option<bool>>> {
+        // Propagates units until none are left, and picks a literal of the shortest open clause
+        let branch = loop {
+            let mut unit = None;
+            let mut shortest: Option<(usize, i32)> = None;
+            for clause in &cnf.clauses {
+                if clause.iter().any(|l| value(&assignment, *l) == Some(true)) {
+                    continue;
+                }
+                let open: Vec<i32> = clause
+                    .iter()
+                    .copied()
+                    .filter(|l| value(&assignment, *l).is_none())
+                    .collect();
+                match open.len() {
+                    0 => return None,
+                    1 => {
+                        unit = Some(open[0]);
+                        break;
+                    }
+                    length if shortest.is_none_or(|(shortest, _)| length < shortest) => {
+                        shortest = Some((length, open[0]))
+                    }
+                    _ => {}
+                }
+            }
+            match unit {
+                Some(literal) => assignment[literal.unsigned_abs() as usize] = Some(literal > 0),
+                None => break shortest.map(|(_, literal)| literal),
+            }
+        };
+        let Some(literal) = branch else {
+            return Some(assignment);
+        };
+        for choice in [literal, -literal] {
+            let mut next = assignment.clone();
+            next[choice.unsigned_abs() as usize] = Some(choice > 0);
+            if let Some(model) = dpll(cnf, next) {
+                return Some(model);
+            }
+        }
+        None
+    }
+
+    let model = dpll(cnf, vec![None; cnf.variables + 1])?;
+    Some(
+        (1..=cnf.variables as i32)
+            .map(|variable| match model[variable as usize] {
+                Some(false) => -variable,
+                _ => variable,
+            })
+            .collect(),
+    )
+}
+
+/// The length of a shortest plan, by breadth first search
+fn plan_length(task: &GroundTask) -> Option<usize> {
+    let mut queue = VecDeque::from([(task.init.clone(), 0)]);
+    let mut visited = HashSet::from([task.init.clone()]);
+    while let Some((state, length)) = queue.pop_front() {
+        if task.goal.is_subset(&state) && task.negative_goal.is_disjoint(&state) {
+            return Some(length);
+        }
+        for action in &task.actions {
+            if action.precondition.iter().all(|atom| state.contains(atom))
+                && action
+                    .negative_precondition
+                    .iter()
+                    .all(|atom| !state.contains(atom))
+            {
+                let mut next: BTreeSet<_> = state.clone();
+                action.del.iter().for_each(|atom| {
+                    next.remove(atom);
+                });
+                next.extend(&action.add);
+                if visited.insert(next.clone()) {
+                    queue.push_back((next, length + 1));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether `plan` reaches the goal of `task`, with its steps as names of ground actions
+fn valid(task: &GroundTask, plan: &[String]) -> bool {
+    let mut state = task.init.clone();
+    for step in plan {
+        let Some(action) = task
+            .actions
+            .iter()
+            .find(|action| task.action_name(action) == *step)
+        else {
+            return false;
+        };
+        if !action.precondition.iter().all(|atom| state.contains(atom))
+            || action
+                .negative_precondition
+                .iter()
+                .any(|atom| state.contains(atom))
+        {
+            return false;
+        }
+        action.del.iter().for_each(|atom| {
+            state.remove(atom);
+        });
+        state.extend(&action.add);
+    }
+    task.goal.is_subset(&state) && task.negative_goal.is_disjoint(&state)
+}
+
+#[rstest]
+#[case("childsnack")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("miconic")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+fn sat_data(#[case] domain_name: &str) {
+    let domain_content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    let problem = problem::parse(&problem_content);
+    let task = GroundTask::new(&domain, &problem).unwrap();
+    let length = plan_length(&task).expect("the task has a plan");
+    let mutexes = mutex_groups(&task, &synthesize(&domain, &problem));
+
+    for semantics in [Semantics::Sequential, Semantics::ForallStep] {
+        // The shortest horizon with a model, which is the length of a shortest plan for sequential plans
+        let mut horizon = 0;
+        let plan = loop {
+            let encoding = SatEncoding::with_mutexes(&task, horizon, semantics, &mutexes).unwrap();
+            let dimacs = encoding.cnf.to_string();
+            assert_eq!(dimacs.lines().count(), encoding.cnf.clauses.len() + 1);
+            if let Some(model) = solve(&encoding.cnf) {
+                assert_eq!(
+                    SatEncoding::new(&task, horizon, semantics)
+                        .unwrap()
+                        .cnf
+                        .variables,
+                    encoding.cnf.variables
+                );
+                let output = format!(
+                    "s SATISFIABLE\nv {} 0\n",
+                    model
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                );
+                let model = parse_model(&output).unwrap().unwrap();
+                break encoding.decode(&model);
+            }
+            assert!(
+                horizon < length,
+                "{}: no model at the plan length",
+                domain_name
+            );
+            horizon += 1;
+        };
+        let plan: Vec<String> = plan
+            .iter()
+            .map(|(action, objects)| {
+                let mut name = format!("({}", action);
+                objects
+                    .iter()
+                    .for_each(|object| name.push_str(&format!(" {}", object)));
+                name + ")"
+            })
+            .collect();
+        assert!(valid(&task, &plan), "{}: {:?}", domain_name, plan);
+        match semantics {
+            Semantics::Sequential => {
+                assert_eq!(horizon, length, "{}", domain_name);
+                assert_eq!(plan.len(), length, "{}", domain_name);
+            }
+            Semantics::ForallStep => assert!(plan.len() >= length, "{}", domain_name),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "serde")]
+
+use std::fs;
+
+use spingus::{
+    domain, domain::Domain, formula::Formula, plan, problem, problem::init::Fact, problem::Problem,
+};
+
+use rstest::*;
+
+#[rstest]
+#[case("barman-agile")]
+#[case("barman-mco14-strips")]
+#[case("barman-satisficing")]
+#[case("blocks-typed")]
+#[case("blocks-untyped")]
+#[case("childsnack")]
+#[case("child-snack-agile")]
+#[case("child-snack-satisficing")]
+#[case("driverlog-automatic")]
+#[case("driverlog-hand-coded")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("ferry")]
+#[case("floortile")]
+#[case("freecell-typed")]
+#[case("freecell-untyped")]
+#[case("grid")]
+#[case("gripper")]
+#[case("hiking-sequential-agile")]
+#[case("logistics")]
+#[case("logistics-typed")]
+#[case("logistics-untyped")]
+#[case("miconic")]
+#[case("movie")]
+#[case("mystery")]
+#[case("rovers")]
+#[case("satellite")]
+#[case("sokoban")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+#[case("zenotravel")]
+fn serde_round_trip(#[case] domain_name: &str) {
+    let domain_content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    let json = serde_json::to_string(&domain).unwrap();
+    assert_eq!(serde_json::from_str::<Domain>(&json).unwrap(), domain);
+
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let problem = problem::parse(&problem_content);
+    let json = serde_json::to_string(&problem).unwrap();
+    assert_eq!(serde_json::from_str::<Problem>(&json).unwrap(), problem);
+}
+
+#[test]
+fn serde_plan() {
+    let plan = plan::parse("(a b c)\n(d)");
+    let json = serde_json::to_string(&plan).unwrap();
+    assert_eq!(json, r#"[["a",["b","c"]],["d",[]]]"#);
+    assert_eq!(serde_json::from_str::<plan::Plan>(&json).unwrap(), plan);
+}
+
+#[test]
+fn serde_goal() {
+    let problem = problem::parse("(define (problem p) (:goal (and (p a) (not (q)))))");
+    let json = serde_json::to_string(&problem.goal).unwrap();
+    assert_eq!(
+        json,
+        r#"{"And":[{"Atom":{"predicate":"p","objects":["a"]}},{"Not":{"Atom":{"predicate":"q","objects":[]}}}]}"#
+    );
+    assert_eq!(
+        serde_json::from_str::<Option<Formula<Fact>>>(&json).unwrap(),
+        problem.goal
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn serde_deprecated_goal() {
+    use spingus::problem::goal::Goal;
+    let goal = Goal::Not(Box::new(Goal::Fact {
+        predicate: "q",
+        objects: vec!["a"],
+    }));
+    let json = serde_json::to_string(&goal).unwrap();
+    assert_eq!(
+        json,
+        r#"{"Not":{"Fact":{"predicate":"q","objects":["a"]}}}"#
+    );
+    assert_eq!(serde_json::from_str::<Goal>(&json).unwrap(), goal);
+}
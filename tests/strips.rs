@@ -0,0 +1,82 @@
+use std::fs;
+
+use spingus::{compile::strips, domain, ground::GroundTask, owned::AsBorrowed, problem};
+
+use rstest::*;
+
+#[rstest]
+#[case("barman-agile")]
+#[case("barman-mco14-strips")]
+#[case("barman-satisficing")]
+#[case("blocks-typed")]
+#[case("blocks-untyped")]
+#[case("childsnack")]
+#[case("child-snack-agile")]
+#[case("child-snack-satisficing")]
+#[case("driverlog-automatic")]
+#[case("driverlog-hand-coded")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("ferry")]
+#[case("floortile")]
+#[case("freecell-typed")]
+#[case("freecell-untyped")]
+#[case("grid")]
+#[case("gripper")]
+#[case("hiking-sequential-agile")]
+#[case("logistics")]
+#[case("logistics-typed")]
+#[case("logistics-untyped")]
+#[case("miconic")]
+#[case("movie")]
+#[case("mystery")]
+#[case("rovers")]
+#[case("satellite")]
+#[case("sokoban")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+#[case("zenotravel")]
+fn strips_data(#[case] domain_name: &str) {
+    let domain_content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    let problem = problem::parse(&problem_content);
+    let strips: strips::Strips = strips::compile(&domain, &problem, 64).unwrap();
+
+    assert_eq!(
+        domain::parse_domain(&strips.domain.to_string()),
+        Ok(strips.domain.clone())
+    );
+    let problem_content = strips.problem.to_string();
+    assert_eq!(
+        problem::parse(&problem_content),
+        strips.problem.as_borrowed()
+    );
+
+    let task = GroundTask::new(&strips.domain, &strips.problem.as_borrowed()).unwrap();
+    assert!(!task.actions.is_empty(), "{}", domain_name);
+    assert!(task.negative_goal.is_empty(), "{}", domain_name);
+    assert_eq!(task.unreachable_goal, vec![], "{}", domain_name);
+    for action in &task.actions {
+        assert!(action.negative_precondition.is_empty(), "{}", domain_name);
+    }
+    let original = GroundTask::new(&domain, &problem).unwrap();
+    assert_eq!(
+        task.goal.len(),
+        original.goal.len() + original.negative_goal.len()
+    );
+
+    let untyped = strips::compile_untyped(&domain, &problem, 64).unwrap();
+    assert_eq!(untyped.domain.types, None);
+    assert_eq!(untyped.domain.requirements, Some(vec!["strips".to_owned()]));
+    let untyped_task = GroundTask::new(&untyped.domain, &untyped.problem.as_borrowed()).unwrap();
+    assert_eq!(
+        untyped_task.actions.len(),
+        task.actions.len(),
+        "{}",
+        domain_name
+    );
+}
@@ -0,0 +1,223 @@
+use std::fs;
+
+use spingus::{
+    domain::{
+        self,
+        validate::{Diagnostic, Severity},
+    },
+    lint::{lint_domain, lint_problem, LintConfig},
+    problem::{self, validate::validate_problem},
+};
+
+use rstest::*;
+
+#[rstest]
+#[case("barman-agile")]
+#[case("barman-mco14-strips")]
+#[case("barman-satisficing")]
+#[case("blocks-typed")]
+#[case("blocks-untyped")]
+#[case("childsnack")]
+#[case("child-snack-agile")]
+#[case("child-snack-satisficing")]
+#[case("driverlog-automatic")]
+#[case("driverlog-hand-coded")]
+#[case("elevator-typed")]
+#[case("elevator-untyped")]
+#[case("ferry")]
+#[case("floortile")]
+#[case("freecell-typed")]
+#[case("freecell-untyped")]
+#[case("grid")]
+#[case("gripper")]
+#[case("hiking-sequential-agile")]
+#[case("logistics")]
+#[case("logistics-typed")]
+#[case("logistics-untyped")]
+#[case("miconic")]
+#[case("movie")]
+#[case("mystery")]
+#[case("rovers")]
+#[case("satellite")]
+#[case("sokoban")]
+#[case("spanner")]
+#[case("storage")]
+#[case("transport")]
+#[case("zenotravel")]
+fn validate_data(#[case] domain_name: &str) {
+    let content = fs::read_to_string(format!("tests/data/{}/domain.pddl", domain_name))
+        .expect("Could not open domain");
+    let errors: Vec<Diagnostic> = domain::parse_domain(&content)
+        .unwrap()
+        .validate()
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .collect();
+    assert_eq!(errors, vec![], "{}", domain_name);
+
+    let problem_content = fs::read_to_string(format!("tests/data/{}/problem.pddl", domain_name))
+        .expect("Could not open problem");
+    let domain = domain::parse_domain(&content).unwrap();
+    let problem = problem::parse(&problem_content);
+    let diagnostics = validate_problem(&domain, &problem);
+    assert_eq!(diagnostics, vec![], "{}", domain_name);
+
+    let config = LintConfig::default();
+    let lints: Vec<(&str, String)> = lint_domain(&domain, &config)
+        .into_iter()
+        .chain(lint_problem(&domain, &problem, &config))
+        .map(|lint| (lint.code.expect("lints have codes"), lint.message))
+        .collect();
+    let expected: Vec<(&str, String)> = expected_lints(domain_name)
+        .iter()
+        .map(|&(code, message)| (code, message.to_owned()))
+        .collect();
+    assert_eq!(lints, expected, "{}", domain_name);
+}
+
+/// The codes and messages of the lints of a bundled domain and problem
+fn expected_lints(domain_name: &str) -> &'static [(&'static str, &'static str)] {
+    match domain_name {
+        "elevator-typed" => &[
+            ("L001", "predicate 'not-boarded' is never used by an action"),
+            ("L001", "predicate 'not-served' is never used by an action"),
+        ],
+        "floortile" => &[("L001", "predicate 'free-color' is never used by an action")],
+        "freecell-typed" | "freecell-untyped" => &[
+            (
+                "L007",
+                "action 'sendtohome-b' has 7 parameters, more than 6",
+            ),
+            (
+                "L007",
+                "action 'homefromfreecell' has 7 parameters, more than 6",
+            ),
+        ],
+        "logistics-typed" => &[("L002", "type 'location' is never used")],
+        "mystery" => &[("L001", "predicate 'province' is never used by an action")],
+        "storage" => &[
+            ("L001", "predicate 'compatible' is never used by an action"),
+            ("L002", "type 'container' is never used"),
+            ("L002", "type 'depot' is never used"),
+        ],
+        _ => &[],
+    }
+}
+
+#[rstest]
+#[case(
+    "undeclared-predicate",
+    Severity::Error,
+    "action 'move': predicate 'connected' is not declared"
+)]
+#[case(
+    "wrong-arity",
+    Severity::Error,
+    "action 'move': predicate 'connected' takes 2 arguments, but is given 1"
+)]
+#[case(
+    "unbound-variable",
+    Severity::Error,
+    "action 'move': variable '?to' is not bound"
+)]
+#[case(
+    "undeclared-type",
+    Severity::Error,
+    "action 'move': type 'hall' is not declared"
+)]
+#[case("duplicate-action", Severity::Error, "action 'move' is declared twice")]
+#[case(
+    "undeclared-constant",
+    Severity::Error,
+    "action 'enter': constant 'kitchen' is not declared"
+)]
+#[case("type-cycle", Severity::Error, "type 'hall' is its own supertype")]
+#[case(
+    "negative-precondition",
+    Severity::Warning,
+    "action 'enter': 'not' requires :negative-preconditions"
+)]
+#[case(
+    "missing-requirement",
+    Severity::Warning,
+    "action 'enter': 'forall' requires :conditional-effects"
+)]
+fn validate_invalid(#[case] name: &str, #[case] severity: Severity, #[case] message: &str) {
+    let content =
+        fs::read_to_string(format!("tests/invalid/{}.pddl", name)).expect("Could not open domain");
+    let diagnostics = domain::parse_domain(&content).unwrap().validate();
+    let diagnostic = &diagnostics[0];
+    assert_eq!(
+        (diagnostic.severity, diagnostic.message.as_str()),
+        (severity, message)
+    );
+    let span = diagnostic.span(&content).expect("subject not found");
+    assert_eq!(content[span].to_lowercase(), diagnostic.subject);
+    assert!(diagnostic.render(&content).contains(message));
+}
+
+#[rstest]
+#[case(
+    "(:action a :parameters (?x) :precondition (p ?x ?x) :effect (and (p ?x)))",
+    "action 'a': predicate 'p' takes 2 arguments, but is given 1",
+    "(and ("
+)]
+#[case(
+    "(:action a :parameters (?x) :effect (and (forall (?y) (p ?y ?y)) (p ?y ?y)))",
+    "action 'a': variable '?y' is not bound",
+    ")) (p "
+)]
+fn validate_span(#[case] action: &str, #[case] message: &str, #[case] before: &str) {
+    let content = format!(
+        "(define (domain d) (:requirements :adl) (:predicates (p ?a ?b)) {})",
+        action
+    );
+    let diagnostics = domain::parse_domain(&content).unwrap().validate();
+    let diagnostic = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic.message == message)
+        .expect("diagnostic not reported");
+    let span = diagnostic.span(&content).expect("subject not found");
+    assert!(content[..span.start].ends_with(before));
+    assert_eq!(content[span].to_lowercase(), diagnostic.subject);
+}
+
+#[rstest]
+#[case(
+    "wrong-domain",
+    "problem is for domain 'gripper', but the domain is 'rooms'",
+    "gripper"
+)]
+#[case("undeclared-type", "type 'box' of object 'b' is not declared", "box")]
+#[case("undeclared-predicate", "predicate 'in' is not declared", "(in b a)")]
+#[case(
+    "wrong-arity",
+    "predicate 'at' takes 2 arguments, but '(at b)' gives 1",
+    "(at b)"
+)]
+#[case(
+    "wrong-type",
+    "object 'a' in '(at a b)' is of type room, but predicate 'at' expects ball",
+    "(at   a\n        b)"
+)]
+#[case(
+    "unknown-object",
+    "object 'c' in '(at c a)' is not declared",
+    "(at c a)"
+)]
+#[case(
+    "constant-clash",
+    "object 'hall' is already a constant of the domain",
+    "hall"
+)]
+fn validate_invalid_problem(#[case] name: &str, #[case] message: &str, #[case] spanned: &str) {
+    let domain_content = fs::read_to_string("tests/invalid/problem/domain.pddl").unwrap();
+    let content = fs::read_to_string(format!("tests/invalid/problem/{}.pddl", name))
+        .expect("Could not open problem");
+    let domain = domain::parse_domain(&domain_content).unwrap();
+    let diagnostics = validate_problem(&domain, &problem::parse(&content));
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].message, message);
+    let span = diagnostics[0].span(&content).expect("subject not found");
+    assert_eq!(&content[span], spanned);
+}